@@ -0,0 +1,153 @@
+//! Scrollback throughput and memory benchmark.
+//!
+//! Run with `cargo bench --bench scrollback`. Feeds synthetic process output
+//! through `TerminalBuffer` and reports write throughput plus the heap held by
+//! the buffer once scrollback is full (measured with a counting allocator).
+
+use panex::process::buffer::TerminalBuffer;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+struct CountingAlloc;
+
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LIVE_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        LIVE_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const SCROLLBACK: usize = 10_000;
+const LINES: usize = 50_000;
+/// Throughput is the best of several runs to reduce noise
+const RUNS: usize = 5;
+
+/// Plain 200-column log lines
+fn plain_output() -> Vec<u8> {
+    let mut out = Vec::new();
+    for i in 0..LINES {
+        let line = format!("{i:>8} request handled in 12ms path=/api/v1/items?page=3 ");
+        let mut line = line.repeat(200 / line.len() + 1);
+        line.truncate(200);
+        out.extend_from_slice(line.as_bytes());
+        out.extend_from_slice(b"\r\n");
+    }
+    out
+}
+
+/// Colored log lines: dim timestamp, colored level, bold target, plain message
+fn colored_output() -> Vec<u8> {
+    let levels = [
+        "\x1b[32mINFO \x1b[0m",
+        "\x1b[33mWARN \x1b[0m",
+        "\x1b[31mERROR\x1b[0m",
+    ];
+    let mut out = Vec::new();
+    for i in 0..LINES {
+        let line = format!(
+            "\x1b[2m2026-01-01T12:00:{:02}.{:03}Z\x1b[0m {} \x1b[1mserver::http\x1b[0m: \
+             request id={i} method=GET path=/api/v1/items status=200 elapsed=12ms \
+             user_agent=\"Mozilla/5.0 (X11; Linux x86_64)\" \x1b[38;2;120;120;255mtrace=abcdef0123456789\x1b[0m\r\n",
+            i % 60,
+            i % 1000,
+            levels[i % 3]
+        );
+        out.extend_from_slice(line.as_bytes());
+    }
+    out
+}
+
+/// Lines with box-drawing and CJK characters
+fn unicode_output() -> Vec<u8> {
+    let mut out = Vec::new();
+    for i in 0..LINES {
+        let line = format!(
+            "│ {i:>6} │ ✓ 完了 ─ building crate ── {} │\r\n",
+            "─".repeat(60)
+        );
+        out.extend_from_slice(line.as_bytes());
+    }
+    out
+}
+
+/// A full-screen app scrolling its whole-screen region (htop, `tail -f` in vim)
+fn region_output() -> Vec<u8> {
+    let mut out = b"\x1b[?1049h\x1b[1;50r\x1b[50;1H".to_vec();
+    for i in 0..LINES {
+        out.extend_from_slice(
+            format!("\r\n{i:>8} tailing a log inside a full-screen app").as_bytes(),
+        );
+    }
+    out
+}
+
+/// A fixed header and footer around a scrolling middle region of a
+/// `rows`-high screen. Each scroll rotates the region's rows in place, so
+/// its cost grows with the region height.
+fn partial_region_output(rows: usize) -> Vec<u8> {
+    let mut out = format!(
+        "\x1b[?1049h\x1b[1;1Hheader\x1b[{rows};1Hfooter\x1b[2;{}r\x1b[{};1H",
+        rows - 1,
+        rows - 1
+    )
+    .into_bytes();
+    for i in 0..LINES {
+        out.extend_from_slice(format!("\r\n{i:>8} process list row").as_bytes());
+    }
+    out
+}
+
+fn run(name: &str, rows: usize, data: &[u8]) {
+    let mut best = f64::MAX;
+    let mut held = 0;
+    for _ in 0..RUNS {
+        let before = LIVE_BYTES.load(Ordering::Relaxed);
+        let start = Instant::now();
+        let mut buffer = TerminalBuffer::with_max_scrollback(200, rows, SCROLLBACK);
+        // Feed in 4 KiB chunks
+        for chunk in data.chunks(4096) {
+            buffer.write(chunk);
+        }
+        best = best.min(start.elapsed().as_secs_f64());
+        held = LIVE_BYTES.load(Ordering::Relaxed).saturating_sub(before);
+        drop(buffer);
+    }
+    let mb = data.len() as f64 / (1024.0 * 1024.0);
+    println!(
+        "{name:<10} {:>7.1} MiB/s   {:>7.2} MiB held for {SCROLLBACK} lines ({:.0} B/line)",
+        mb / best,
+        held as f64 / (1024.0 * 1024.0),
+        held as f64 / SCROLLBACK as f64,
+    );
+}
+
+fn main() {
+    let workloads = [
+        ("plain", 50, plain_output()),
+        ("colored", 50, colored_output()),
+        ("unicode", 50, unicode_output()),
+        ("region", 50, region_output()),
+        ("partial", 50, partial_region_output(50)),
+        ("partial200", 200, partial_region_output(200)),
+    ];
+    for (name, rows, data) in &workloads {
+        run(name, *rows, data);
+    }
+}
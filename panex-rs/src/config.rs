@@ -1,16 +1,50 @@
+use crate::{dotenv, procfile, scripts};
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Project config file picked up from the working directory when no
+/// commands are given on the command line.
+pub const DEFAULT_CONFIG_FILE: &str = "panex.toml";
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ProcessStatus {
+    /// Spawned, reader thread not yet running
+    Starting,
     Running,
+    /// SIGSTOP sent; resumed with SIGCONT
+    Paused,
+    /// Stop signal sent, waiting for exit (SIGKILL after the timeout)
+    Stopping,
+    /// Exited on its own; 0 is shown as completed rather than as a failure
     Exited(i32),
     Failed(String),
     Stopped,
 }
 
 impl ProcessStatus {
+    /// True while a child process exists (starting, running or stopping).
+    pub fn is_alive(&self) -> bool {
+        matches!(
+            self,
+            ProcessStatus::Starting
+                | ProcessStatus::Running
+                | ProcessStatus::Paused
+                | ProcessStatus::Stopping
+        )
+    }
+
     pub fn icon(&self) -> &'static str {
         match self {
+            ProcessStatus::Starting => "○",
             ProcessStatus::Running => "●",
-            ProcessStatus::Exited(0) => " ",
+            ProcessStatus::Paused => "‖",
+            ProcessStatus::Stopping => "◌",
+            ProcessStatus::Exited(0) => "✓",
             ProcessStatus::Exited(_) => "✗",
             ProcessStatus::Failed(_) => "✗",
             ProcessStatus::Stopped => " ",
@@ -20,8 +54,11 @@ impl ProcessStatus {
     pub fn color(&self) -> ratatui::style::Color {
         use ratatui::style::Color;
         match self {
+            ProcessStatus::Starting => Color::Yellow,
             ProcessStatus::Running => Color::Green,
-            ProcessStatus::Exited(0) => Color::Gray,
+            ProcessStatus::Paused => Color::Cyan,
+            ProcessStatus::Stopping => Color::Yellow,
+            ProcessStatus::Exited(0) => Color::Green,
             ProcessStatus::Exited(_) => Color::Red,
             ProcessStatus::Failed(_) => Color::Red,
             ProcessStatus::Stopped => Color::Gray,
@@ -29,14 +66,316 @@ impl ProcessStatus {
     }
 }
 
+/// Signals panex can send to a process group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Signal {
+    Int,
+    Hup,
+    Usr1,
+    Usr2,
+    Quit,
+    #[default]
+    Term,
+    Kill,
+    Stop,
+    Cont,
+}
+
+impl Signal {
+    /// Signals offered by the signal menu, in order.
+    pub const MENU: [Signal; 7] = [
+        Signal::Int,
+        Signal::Hup,
+        Signal::Usr1,
+        Signal::Usr2,
+        Signal::Quit,
+        Signal::Term,
+        Signal::Kill,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Signal::Int => "SIGINT",
+            Signal::Hup => "SIGHUP",
+            Signal::Usr1 => "SIGUSR1",
+            Signal::Usr2 => "SIGUSR2",
+            Signal::Quit => "SIGQUIT",
+            Signal::Term => "SIGTERM",
+            Signal::Kill => "SIGKILL",
+            Signal::Stop => "SIGSTOP",
+            Signal::Cont => "SIGCONT",
+        }
+    }
+
+    #[cfg(unix)]
+    pub fn number(self) -> i32 {
+        match self {
+            Signal::Int => libc::SIGINT,
+            Signal::Hup => libc::SIGHUP,
+            Signal::Usr1 => libc::SIGUSR1,
+            Signal::Usr2 => libc::SIGUSR2,
+            Signal::Quit => libc::SIGQUIT,
+            Signal::Term => libc::SIGTERM,
+            Signal::Kill => libc::SIGKILL,
+            Signal::Stop => libc::SIGSTOP,
+            Signal::Cont => libc::SIGCONT,
+        }
+    }
+}
+
+/// Accepts `SIGINT`, `INT` or `int`.
+impl FromStr for Signal {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let upper = s.trim().to_ascii_uppercase();
+        let bare = upper.strip_prefix("SIG").unwrap_or(&upper);
+        [Signal::Stop, Signal::Cont]
+            .into_iter()
+            .chain(Signal::MENU)
+            .find(|sig| &sig.name()[3..] == bare)
+            .ok_or_else(|| format!("unknown signal {s:?}"))
+    }
+}
+
+/// Lifecycle hook of a process. Hooks are shell commands run in their own
+/// PTY, with output shown dimmed in the process's panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    /// Before every start; the process only starts if it exits 0
+    BeforeStart,
+    /// After every exit, including stops
+    AfterExit,
+    /// After a non-zero exit, a failed start or a failed `before_start`
+    OnFailure,
+    /// Once the process is ready: on `ready_pattern`, or once it has started
+    OnReady,
+}
+
+impl Hook {
+    pub const ALL: [Hook; 4] = [
+        Hook::BeforeStart,
+        Hook::AfterExit,
+        Hook::OnFailure,
+        Hook::OnReady,
+    ];
+
+    /// Setting name in `panex.toml`, also passed to the hook in `PANEX_HOOK`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Hook::BeforeStart => "before_start",
+            Hook::AfterExit => "after_exit",
+            Hook::OnFailure => "on_failure",
+            Hook::OnReady => "on_ready",
+        }
+    }
+}
+
+/// Port of a process, put in its environment and referable from other
+/// processes as `${<name>.port}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Port {
+    /// A free localhost port, picked when first needed
+    Auto,
+    Fixed(u16),
+}
+
+/// Variable a process's port is passed in, unless `port_env` names another.
+pub const DEFAULT_PORT_ENV: &str = "PORT";
+
+/// A free localhost port that isn't in `taken`.
+pub fn free_port(taken: &[u16]) -> Result<u16> {
+    for _ in 0..16 {
+        let port = TcpListener::bind(("127.0.0.1", 0))?.local_addr()?.port();
+        if !taken.contains(&port) {
+            return Ok(port);
+        }
+    }
+    bail!("no free port found")
+}
+
+/// Whether nothing is listening on localhost `port`.
+pub fn port_is_free(port: u16) -> bool {
+    TcpListener::bind(("127.0.0.1", port)).is_ok()
+}
+
 #[derive(Debug, Clone)]
 pub struct ProcessConfig {
     pub name: String,
     pub command: String,
     pub no_shift_tab: bool, // Per-process shift-tab disable
     pub wrap_enabled: bool, // Per-process line wrapping
+    /// Sent by `x`, restart and quit before escalating to SIGKILL
+    pub stop_signal: Signal,
+    /// Run instead of sending `stop_signal` (SIGKILL still follows at the timeout)
+    pub stop_command: Option<String>,
+    /// Started with the others at launch; otherwise only on `s` or `r`
+    pub autostart: bool,
+    pub before_start: Option<String>,
+    pub after_exit: Option<String>,
+    pub on_failure: Option<String>,
+    pub on_ready: Option<String>,
+    /// Output substring that marks the process ready for `on_ready`
+    pub ready_pattern: Option<String>,
+    /// Added to the inherited environment of the process and its hooks
+    pub env: Vec<(String, String)>,
+    /// `.env` files read into `env` at every start, relative to `cwd`
+    pub env_file: Vec<PathBuf>,
+    /// Working directory of the process and its hooks, relative to panex's
+    pub cwd: Option<PathBuf>,
+    pub port: Option<Port>,
+    /// Variable the port is passed in (default `PORT`)
+    pub port_env: Option<String>,
+}
+
+impl ProcessConfig {
+    /// Command of a lifecycle hook, if set.
+    pub fn hook(&self, hook: Hook) -> Option<&str> {
+        match hook {
+            Hook::BeforeStart => self.before_start.as_deref(),
+            Hook::AfterExit => self.after_exit.as_deref(),
+            Hook::OnFailure => self.on_failure.as_deref(),
+            Hook::OnReady => self.on_ready.as_deref(),
+        }
+    }
+
+    /// This process as it is run: the global `env_files`, then its own
+    /// `env_file`s, then its `env` table make up `env`, and `${VAR}`
+    /// references are expanded in `cwd` and `env` values. Commands only get
+    /// `${<name>.port}` expanded, and the shell does the rest.
+    /// `ports` holds the port of every process that has one; this process's
+    /// own goes in `port_env`, and `${<name>.port}` refers to the others.
+    /// Env files are read again on every call.
+    pub fn resolve(
+        &self,
+        env_files: &[PathBuf],
+        ports: &HashMap<String, u16>,
+    ) -> Result<ProcessConfig> {
+        let port = match self.port {
+            Some(Port::Fixed(port)) => Some(port),
+            Some(Port::Auto) => Some(
+                *ports
+                    .get(&self.name)
+                    .ok_or_else(|| anyhow!("no port assigned to {}", self.name))?,
+            ),
+            None => None,
+        };
+        let port_env = self.port_env.as_deref().unwrap_or(DEFAULT_PORT_ENV);
+        let var = |env: &[(String, String)], name: &str| {
+            name.strip_suffix(".port")
+                .and_then(|process| ports.get(process))
+                .map(u16::to_string)
+                .or_else(|| dotenv::lookup_env(env, name))
+        };
+        let cwd = self.cwd.as_ref().map(|cwd| {
+            PathBuf::from(dotenv::interpolate(&cwd.to_string_lossy(), |name| {
+                std::env::var(name).ok()
+            }))
+        });
+        let dir = match cwd {
+            Some(ref cwd) => std::env::current_dir()?.join(cwd),
+            None => std::env::current_dir()?,
+        };
+        let mut env: Vec<(String, String)> = Vec::new();
+        let set = |env: &mut Vec<(String, String)>, key: String, value: String| {
+            env.retain(|(k, _)| *k != key);
+            env.push((key, value));
+        };
+        // First so that env files and `env` can use it, and set again below
+        // so that they can't replace it
+        if let Some(port) = port {
+            set(&mut env, port_env.to_string(), port.to_string());
+        }
+        let files = env_files
+            .iter()
+            .map(|path| (path, false))
+            .chain(self.env_file.iter().map(|path| (path, true)));
+        for (path, own) in files {
+            let path = PathBuf::from(dotenv::interpolate(&path.to_string_lossy(), |name| {
+                var(&env, name)
+            }));
+            let path = if own { dir.join(path) } else { path };
+            for (key, value) in dotenv::load_env_file(&path, |name| var(&env, name))? {
+                set(&mut env, key, value);
+            }
+        }
+        for (key, value) in &self.env {
+            let value = dotenv::interpolate(value, |name| var(&env, name));
+            set(&mut env, key.clone(), value);
+        }
+        if let Some(port) = port {
+            set(&mut env, port_env.to_string(), port.to_string());
+        }
+        // The shell expands the rest from `env`, which the process inherits
+        let command = |command: &str| {
+            dotenv::interpolate_command(command, |process| ports.get(process).copied())
+        };
+        Ok(ProcessConfig {
+            command: command(&self.command),
+            stop_command: self.stop_command.as_deref().map(command),
+            before_start: self.before_start.as_deref().map(command),
+            after_exit: self.after_exit.as_deref().map(command),
+            on_failure: self.on_failure.as_deref().map(command),
+            on_ready: self.on_ready.as_deref().map(command),
+            cwd,
+            env_file: Vec::new(),
+            port: port.map(Port::Fixed),
+            env,
+            ..self.clone()
+        })
+    }
+
+    /// A resolved process for display: secret-looking `env` values are
+    /// masked, in the commands too.
+    fn masked(mut self) -> ProcessConfig {
+        let mask = |command: &mut String| *command = dotenv::mask_secrets(command, &self.env);
+        mask(&mut self.command);
+        for command in [
+            &mut self.stop_command,
+            &mut self.before_start,
+            &mut self.after_exit,
+            &mut self.on_failure,
+            &mut self.on_ready,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            mask(command);
+        }
+        for (key, value) in &mut self.env {
+            *value = dotenv::mask_value(key, value);
+        }
+        self
+    }
+}
+
+impl Default for ProcessConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            command: String::new(),
+            no_shift_tab: false,
+            wrap_enabled: false,
+            stop_signal: Signal::default(),
+            stop_command: None,
+            autostart: true,
+            before_start: None,
+            after_exit: None,
+            on_failure: None,
+            on_ready: None,
+            ready_pattern: None,
+            env: Vec::new(),
+            env_file: Vec::new(),
+            cwd: None,
+            port: None,
+            port_env: None,
+        }
+    }
 }
 
+/// Default render rate cap (frames per second).
+pub const DEFAULT_FPS: u16 = 60;
+
 #[derive(Debug, Clone)]
 pub struct PanexConfig {
     pub processes: Vec<ProcessConfig>,
@@ -47,6 +386,20 @@ pub struct PanexConfig {
     pub panel_width: Option<u16>,
     /// Preserve scrollback and show a restart marker instead of clearing output.
     pub show_restart_marker: bool,
+    /// Global scrollback memory cap in bytes, shared by all processes. None = unlimited.
+    pub memory_limit: Option<usize>,
+    /// Maximum redraws per second; output arriving faster is batched per frame.
+    pub fps: u16,
+    /// Config file the processes were loaded from, if any
+    pub config_path: Option<PathBuf>,
+    /// `.env` files read by every process, before its own `env_file`s
+    pub env_files: Vec<PathBuf>,
+    /// Command template that opens `{file}` at `{line}` and `{col}`
+    pub editor: Option<String>,
+    /// Shell command that `|` pipes the selection or scrollback to
+    pub pipe_command: Option<String>,
+    /// Extra regexes for hint mode, tried before the built-in ones
+    pub hint_patterns: Vec<String>,
 }
 
 impl PanexConfig {
@@ -95,6 +448,7 @@ impl PanexConfig {
                     command: cmd,
                     no_shift_tab: proc_no_shift_tab,
                     wrap_enabled,
+                    ..ProcessConfig::default()
                 }
             })
             .collect();
@@ -106,9 +460,129 @@ impl PanexConfig {
             buffer_size,
             panel_width,
             show_restart_marker,
+            memory_limit: None,
+            fps: DEFAULT_FPS,
+            config_path: None,
+            env_files: Vec::new(),
+            editor: None,
+            pipe_command: None,
+            hint_patterns: Vec::new(),
         }
     }
 
+    /// Add the processes of a config file ahead of those from the command line.
+    pub fn load_file(&mut self, path: &Path) -> Result<()> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let mut contents =
+            parse_config_file(&text).with_context(|| format!("invalid {}", path.display()))?;
+        contents.env_files.append(&mut self.env_files);
+        self.env_files = contents.env_files;
+        self.editor = contents.editor.or(self.editor.take());
+        self.pipe_command = contents.pipe_command.or(self.pipe_command.take());
+        self.hint_patterns = contents.hint_patterns;
+        self.config_path = Some(path.to_path_buf());
+        self.prepend(contents.processes)
+    }
+
+    /// Add the processes of a Procfile ahead of those from the command line,
+    /// with `.env` from the Procfile's directory in their environment. Ports
+    /// start at `PORT` from `.env` or the environment, else 5000.
+    pub fn load_procfile(&mut self, path: &Path, formation: &[(String, usize)]) -> Result<()> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let entries = procfile::parse_procfile(&text)
+            .with_context(|| format!("invalid {}", path.display()))?;
+        let dotenv_path = path.with_file_name(".env");
+        let env = match std::fs::read_to_string(&dotenv_path) {
+            Ok(text) => dotenv::parse_dotenv(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(e).with_context(|| format!("failed to read {}", dotenv_path.display()))
+            }
+        };
+        let base_port = match env.iter().rev().find(|(key, _)| key == "PORT") {
+            Some((_, port)) => Some(port.clone()),
+            None => std::env::var("PORT").ok(),
+        };
+        let base_port = match base_port {
+            Some(port) => port
+                .parse()
+                .with_context(|| format!("invalid PORT {port:?}"))?,
+            None => procfile::DEFAULT_BASE_PORT,
+        };
+        self.prepend(procfile::procfile_processes(
+            &entries, formation, base_port, &env,
+        )?)
+    }
+
+    /// Add `package.json` scripts matching `patterns` ahead of the processes
+    /// from the command line.
+    pub fn load_scripts(&mut self, patterns: &[String]) -> Result<()> {
+        let cwd = std::env::current_dir()?;
+        self.prepend(scripts::script_processes(&cwd, patterns)?)
+    }
+
+    /// Add `script` of every workspace package ahead of the processes from
+    /// the command line.
+    pub fn load_workspace_scripts(&mut self, script: &str) -> Result<()> {
+        let cwd = std::env::current_dir()?;
+        self.prepend(scripts::workspace_script_processes(&cwd, script)?)
+    }
+
+    fn prepend(&mut self, mut processes: Vec<ProcessConfig>) -> Result<()> {
+        processes.append(&mut self.processes);
+        self.processes = processes;
+        self.check_unique_names()
+    }
+
+    /// Process names key the process table, so they must be unique.
+    pub fn check_unique_names(&self) -> Result<()> {
+        for (i, process) in self.processes.iter().enumerate() {
+            if self.processes[..i].iter().any(|p| p.name == process.name) {
+                bail!("duplicate process name {:?}", process.name);
+            }
+        }
+        Ok(())
+    }
+
+    /// The processes as they would run, for `--print-config`: a `panex.toml`
+    /// with env files read, `${VAR}` references expanded and secrets masked.
+    /// Auto ports are picked as they would be at launch.
+    pub fn format_resolved(&self) -> Result<String> {
+        let mut ports: HashMap<String, u16> = HashMap::new();
+        for process in &self.processes {
+            let port = match process.port {
+                Some(Port::Fixed(port)) => port,
+                Some(Port::Auto) => free_port(&ports.values().copied().collect::<Vec<_>>())?,
+                None => continue,
+            };
+            ports.insert(process.name.clone(), port);
+        }
+        let processes = self
+            .processes
+            .iter()
+            .map(|process| {
+                process
+                    .resolve(&self.env_files, &ports)
+                    .map(ProcessConfig::masked)
+                    .with_context(|| format!("in process {}", process.name))
+            })
+            .collect::<Result<_>>()?;
+        format_config_file(&ConfigFileContents {
+            editor: self.editor.clone(),
+            pipe_command: self.pipe_command.clone(),
+            hint_patterns: self.hint_patterns.clone(),
+            env_files: Vec::new(),
+            processes,
+        })
+    }
+
+    /// Minimum time between redraws.
+    pub fn frame_interval(&self) -> Duration {
+        Duration::from_secs(1) / self.fps.max(1) as u32
+    }
+
     /// Compute the actual column count for the process list panel.
     ///
     /// - `None` → fixed 20 columns (legacy default)
@@ -123,3 +597,257 @@ impl PanexConfig {
         }
     }
 }
+
+/// Parse a memory size such as `512M`, `1.5G`, `64KiB` or a plain byte count.
+/// Units are binary (K = 1024).
+pub fn parse_memory_limit(s: &str) -> Result<usize, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid memory size: {s:?}"))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        other => return Err(format!("unknown memory unit {other:?} (use K, M or G)")),
+    };
+    let bytes = (number * multiplier as f64) as usize;
+    if bytes == 0 {
+        return Err("memory limit must be greater than zero".to_string());
+    }
+    Ok(bytes)
+}
+
+/// `panex.toml` layout. Each `[processes.<name>]` table is one process, in
+/// file order.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    editor: Option<String>,
+    pipe_command: Option<String>,
+    #[serde(default)]
+    hint_patterns: Vec<String>,
+    env_file: Option<EnvFiles>,
+    #[serde(default)]
+    processes: toml::Table,
+}
+
+/// `env_file` is one path or a list of them.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum EnvFiles {
+    One(PathBuf),
+    Many(Vec<PathBuf>),
+}
+
+impl EnvFiles {
+    fn into_paths(self) -> Vec<PathBuf> {
+        match self {
+            EnvFiles::One(path) => vec![path],
+            EnvFiles::Many(paths) => paths,
+        }
+    }
+}
+
+/// Contents of a `panex.toml`.
+#[derive(Debug, Default)]
+pub struct ConfigFileContents {
+    /// Editor command template for file references in output
+    pub editor: Option<String>,
+    /// Shell command the selection or scrollback is piped to
+    pub pipe_command: Option<String>,
+    /// Regexes hint mode looks for besides the built-in ones
+    pub hint_patterns: Vec<String>,
+    /// Top-level `env_file`, read by every process
+    pub env_files: Vec<PathBuf>,
+    pub processes: Vec<ProcessConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProcessEntry {
+    command: String,
+    #[serde(default)]
+    wrap: bool,
+    #[serde(default)]
+    no_shift_tab: bool,
+    stop_signal: Option<String>,
+    stop_command: Option<String>,
+    autostart: Option<bool>,
+    before_start: Option<String>,
+    after_exit: Option<String>,
+    on_failure: Option<String>,
+    on_ready: Option<String>,
+    ready_pattern: Option<String>,
+    #[serde(default)]
+    env: toml::Table,
+    env_file: Option<EnvFiles>,
+    cwd: Option<PathBuf>,
+    port: Option<toml::Value>,
+    port_env: Option<String>,
+}
+
+/// Parse the contents of a `panex.toml`.
+pub fn parse_config_file(text: &str) -> Result<ConfigFileContents> {
+    let file: ConfigFile = toml::from_str(text)?;
+    let processes = file
+        .processes
+        .into_iter()
+        .map(|(name, value)| {
+            let entry: ProcessEntry = value
+                .try_into()
+                .with_context(|| format!("in [processes.{name}]"))?;
+            let stop_signal = match entry.stop_signal {
+                Some(s) => s.parse().map_err(|e| anyhow!("[processes.{name}]: {e}"))?,
+                None => Signal::default(),
+            };
+            if matches!(stop_signal, Signal::Stop | Signal::Cont) {
+                bail!(
+                    "[processes.{name}]: stop_signal can't be {}",
+                    stop_signal.name()
+                );
+            }
+            let env = entry
+                .env
+                .into_iter()
+                .map(|(key, value)| match value {
+                    toml::Value::String(s) => Ok((key, s)),
+                    toml::Value::Integer(n) => Ok((key, n.to_string())),
+                    toml::Value::Boolean(b) => Ok((key, b.to_string())),
+                    _ => Err(anyhow!("[processes.{name}]: env.{key} must be a string")),
+                })
+                .collect::<Result<_>>()?;
+            let port = match entry.port {
+                None => None,
+                Some(toml::Value::String(s)) if s == "auto" => Some(Port::Auto),
+                Some(toml::Value::Integer(n)) if (1..=65535).contains(&n) => {
+                    Some(Port::Fixed(n as u16))
+                }
+                Some(other) => {
+                    bail!("[processes.{name}]: port must be \"auto\" or a port number, not {other}")
+                }
+            };
+            Ok(ProcessConfig {
+                name,
+                command: entry.command,
+                no_shift_tab: entry.no_shift_tab,
+                wrap_enabled: entry.wrap,
+                stop_signal,
+                stop_command: entry.stop_command,
+                autostart: entry.autostart.unwrap_or(true),
+                before_start: entry.before_start,
+                after_exit: entry.after_exit,
+                on_failure: entry.on_failure,
+                on_ready: entry.on_ready,
+                ready_pattern: entry.ready_pattern,
+                env,
+                env_file: entry.env_file.map_or_else(Vec::new, EnvFiles::into_paths),
+                cwd: entry.cwd,
+                port,
+                port_env: entry.port_env,
+            })
+        })
+        .collect::<Result<_>>()?;
+    for pattern in &file.hint_patterns {
+        regex::Regex::new(pattern).with_context(|| format!("in hint_patterns: {pattern:?}"))?;
+    }
+    Ok(ConfigFileContents {
+        editor: file.editor,
+        pipe_command: file.pipe_command,
+        hint_patterns: file.hint_patterns,
+        env_files: file.env_file.map_or_else(Vec::new, EnvFiles::into_paths),
+        processes,
+    })
+}
+
+/// Render a `panex.toml`. Settings at their default are left out.
+pub fn format_config_file(contents: &ConfigFileContents) -> Result<String> {
+    let paths = |paths: &[PathBuf]| -> toml::Value {
+        match paths {
+            [path] => path.to_string_lossy().into_owned().into(),
+            _ => paths
+                .iter()
+                .map(|path| toml::Value::from(path.to_string_lossy().into_owned()))
+                .collect::<Vec<_>>()
+                .into(),
+        }
+    };
+    let mut tables = toml::Table::new();
+    for process in &contents.processes {
+        let mut table = toml::Table::new();
+        table.insert("command".into(), process.command.clone().into());
+        if process.wrap_enabled {
+            table.insert("wrap".into(), true.into());
+        }
+        if process.no_shift_tab {
+            table.insert("no_shift_tab".into(), true.into());
+        }
+        if !process.autostart {
+            table.insert("autostart".into(), false.into());
+        }
+        if process.stop_signal != Signal::default() {
+            table.insert("stop_signal".into(), process.stop_signal.name().into());
+        }
+        if let Some(ref command) = process.stop_command {
+            table.insert("stop_command".into(), command.clone().into());
+        }
+        for hook in Hook::ALL {
+            if let Some(command) = process.hook(hook) {
+                table.insert(hook.name().into(), command.into());
+            }
+        }
+        if let Some(ref pattern) = process.ready_pattern {
+            table.insert("ready_pattern".into(), pattern.clone().into());
+        }
+        if let Some(ref cwd) = process.cwd {
+            table.insert("cwd".into(), cwd.to_string_lossy().into_owned().into());
+        }
+        match process.port {
+            Some(Port::Auto) => {
+                table.insert("port".into(), "auto".into());
+            }
+            Some(Port::Fixed(port)) => {
+                table.insert("port".into(), i64::from(port).into());
+            }
+            None => {}
+        }
+        if let Some(ref port_env) = process.port_env {
+            table.insert("port_env".into(), port_env.clone().into());
+        }
+        if !process.env_file.is_empty() {
+            table.insert("env_file".into(), paths(&process.env_file));
+        }
+        if !process.env.is_empty() {
+            let env: toml::Table = process
+                .env
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone().into()))
+                .collect();
+            table.insert("env".into(), env.into());
+        }
+        tables.insert(process.name.clone(), table.into());
+    }
+    let mut file = toml::Table::new();
+    if let Some(ref editor) = contents.editor {
+        file.insert("editor".into(), editor.clone().into());
+    }
+    if let Some(ref command) = contents.pipe_command {
+        file.insert("pipe_command".into(), command.clone().into());
+    }
+    if !contents.hint_patterns.is_empty() {
+        file.insert(
+            "hint_patterns".into(),
+            contents.hint_patterns.clone().into(),
+        );
+    }
+    if !contents.env_files.is_empty() {
+        file.insert("env_file".into(), paths(&contents.env_files));
+    }
+    file.insert("processes".into(), tables.into());
+    Ok(toml::to_string(&file)?)
+}
@@ -3,12 +3,16 @@ use crossterm::event::Event as CrosstermEvent;
 /// Generation counter to distinguish events from old vs new process instances
 pub type Generation = u64;
 
+/// Bound on queued events. When it fills, reader threads block, so a flooding
+/// child is throttled by its PTY instead of growing panex's memory.
+pub const EVENT_QUEUE_CAPACITY: usize = 256;
+
 #[derive(Debug)]
 #[allow(dead_code)]
 pub enum AppEvent {
     Input(CrosstermEvent),
     ProcessOutput(String, Generation, Vec<u8>),
-    ProcessStarted(String),
+    ProcessStarted(String, Generation),
     ProcessExited(String, Generation, Option<i32>),
     ProcessError(String, Generation, String),
     Tick,
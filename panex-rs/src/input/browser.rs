@@ -0,0 +1,24 @@
+use std::io;
+use std::process::{Command, Stdio};
+
+/// Open `url` with the platform's default handler, without waiting for it.
+pub fn open_in_browser(url: &str) -> io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    let mut child = command
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    // Reap the opener once it hands the URL over
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
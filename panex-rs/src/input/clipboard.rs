@@ -13,6 +13,12 @@ pub fn copy_to_clipboard(text: &str) -> bool {
     osc52_copy(text)
 }
 
+/// Read text from the system clipboard. Returns None if unavailable or empty.
+pub fn paste_from_clipboard() -> Option<String> {
+    let mut clipboard = arboard::Clipboard::new().ok()?;
+    clipboard.get_text().ok().filter(|text| !text.is_empty())
+}
+
 fn osc52_copy(text: &str) -> bool {
     let encoded = base64_encode(text.as_bytes());
     let seq = format!("\x1b]52;c;{}\x07", encoded);
@@ -0,0 +1,741 @@
+use super::selection::{clamp_pos, BufferPos, SelectionMode, SelectionState};
+use crate::process::buffer::LineView;
+use crate::process::{ManagedProcess, WrapIndex};
+use crate::ui::output_panel::{scroll_down, scroll_up};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+
+/// Largest count that can be typed before a motion.
+const MAX_COUNT: usize = 99_999;
+
+/// Vim motions for the cursor of a keyboard selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motion {
+    Left,
+    Right,
+    Up,
+    Down,
+    /// `0`
+    LineStart,
+    /// `^`
+    FirstNonBlank,
+    /// `$`
+    LineEnd,
+    /// `w`/`W`; `true` for WORDs (runs of non-blanks)
+    WordForward(bool),
+    /// `b`/`B`
+    WordBackward(bool),
+    /// `e`/`E`
+    WordEnd(bool),
+    /// `gg`: first line, or line `count`
+    Top,
+    /// `G`: last line, or line `count`
+    Bottom,
+    /// `{`
+    ParagraphBackward,
+    /// `}`
+    ParagraphForward,
+    /// `f`/`F`/`t`/`T` on the cursor's line. `again` for `;` and `,`,
+    /// where t/T skip the match they already stopped before.
+    Find {
+        target: char,
+        forward: bool,
+        till: bool,
+        again: bool,
+    },
+    /// `H`
+    ScreenTop,
+    /// `M`
+    ScreenMiddle,
+    /// `L`
+    ScreenBottom,
+    /// `Ctrl-u`
+    HalfPageUp,
+    /// `Ctrl-d`
+    HalfPageDown,
+}
+
+/// The lines a motion moves over and the display rows in view.
+pub struct View<'a> {
+    pub lines: LineView<'a>,
+    /// Lines up to the last non-empty one
+    pub content: usize,
+    /// Set in wrap mode
+    pub wrap: Option<&'a WrapIndex>,
+    /// First display row in view
+    pub top: usize,
+    pub height: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    /// Whitespace, or past the end of a line
+    Blank,
+    /// An empty line, which counts as a word of its own
+    Empty,
+    Punct,
+    Word,
+}
+
+impl View<'_> {
+    fn len(&self, row: usize) -> usize {
+        self.lines.get(row).map_or(0, |line| line.len())
+    }
+
+    fn char_at(&self, pos: BufferPos) -> Option<char> {
+        self.lines.get(pos.row)?.char_at(pos.col)
+    }
+
+    fn is_blank_line(&self, row: usize) -> bool {
+        (0..self.len(row)).all(|col| {
+            self.char_at(BufferPos::new(row, col))
+                .is_none_or(char::is_whitespace)
+        })
+    }
+
+    fn first_non_blank(&self, row: usize) -> BufferPos {
+        let col = (0..self.len(row))
+            .find(|&col| {
+                self.char_at(BufferPos::new(row, col))
+                    .is_some_and(|c| !c.is_whitespace())
+            })
+            .unwrap_or(0);
+        BufferPos::new(row, col)
+    }
+
+    fn line_end(&self, row: usize) -> BufferPos {
+        BufferPos::new(row, self.len(row).saturating_sub(1))
+    }
+
+    fn last_row(&self) -> usize {
+        self.content.saturating_sub(1)
+    }
+
+    fn class(&self, pos: BufferPos, big: bool) -> Class {
+        if self.len(pos.row) == 0 {
+            return Class::Empty;
+        }
+        match self.char_at(pos) {
+            None => Class::Blank,
+            Some(c) if c.is_whitespace() => Class::Blank,
+            Some(_) if big => Class::Word,
+            Some(c) if c.is_alphanumeric() || c == '_' => Class::Word,
+            Some(_) => Class::Punct,
+        }
+    }
+
+    fn next(&self, pos: BufferPos) -> Option<BufferPos> {
+        if pos.col + 1 < self.len(pos.row) {
+            Some(BufferPos::new(pos.row, pos.col + 1))
+        } else if pos.row + 1 < self.content {
+            Some(BufferPos::new(pos.row + 1, 0))
+        } else {
+            None
+        }
+    }
+
+    fn prev(&self, pos: BufferPos) -> Option<BufferPos> {
+        if pos.col > 0 {
+            Some(BufferPos::new(pos.row, pos.col - 1))
+        } else if pos.row > 0 {
+            Some(self.line_end(pos.row - 1))
+        } else {
+            None
+        }
+    }
+
+    fn word_forward(&self, mut pos: BufferPos, big: bool) -> BufferPos {
+        let start = self.class(pos, big);
+        // Past the rest of the current word; a new line always ends it
+        loop {
+            let Some(next) = self.next(pos) else {
+                return pos;
+            };
+            let crossed = next.row != pos.row;
+            pos = next;
+            if crossed || self.class(pos, big) != start {
+                break;
+            }
+        }
+        while self.class(pos, big) == Class::Blank {
+            match self.next(pos) {
+                Some(next) => pos = next,
+                None => return pos,
+            }
+        }
+        pos
+    }
+
+    fn word_end(&self, mut pos: BufferPos, big: bool) -> BufferPos {
+        let Some(next) = self.next(pos) else {
+            return pos;
+        };
+        pos = next;
+        while matches!(self.class(pos, big), Class::Blank | Class::Empty) {
+            match self.next(pos) {
+                Some(next) => pos = next,
+                None => return pos,
+            }
+        }
+        let class = self.class(pos, big);
+        while let Some(next) = self.next(pos) {
+            if next.row != pos.row || self.class(next, big) != class {
+                break;
+            }
+            pos = next;
+        }
+        pos
+    }
+
+    fn word_backward(&self, mut pos: BufferPos, big: bool) -> BufferPos {
+        let Some(prev) = self.prev(pos) else {
+            return pos;
+        };
+        pos = prev;
+        while self.class(pos, big) == Class::Blank {
+            match self.prev(pos) {
+                Some(prev) => pos = prev,
+                None => return pos,
+            }
+        }
+        let class = self.class(pos, big);
+        if class == Class::Empty {
+            return pos;
+        }
+        while let Some(prev) = self.prev(pos) {
+            if prev.row != pos.row || self.class(prev, big) != class {
+                break;
+            }
+            pos = prev;
+        }
+        pos
+    }
+
+    /// Display row showing `pos`.
+    fn display_row(&self, pos: BufferPos) -> usize {
+        match self.wrap {
+            Some(wrap) if wrap.width() > 0 => {
+                let col = pos.col.min(self.len(pos.row).saturating_sub(1));
+                wrap.row_start(pos.row) + col / wrap.width()
+            }
+            _ => pos.row,
+        }
+    }
+
+    fn total_rows(&self) -> usize {
+        match self.wrap {
+            Some(wrap) => wrap.total_rows(self.content),
+            None => self.content.max(1),
+        }
+    }
+
+    /// The position on `display_row` at `offset` columns into it.
+    fn at_display(&self, display_row: usize, offset: usize) -> BufferPos {
+        let display_row = display_row.min(self.total_rows() - 1);
+        match self.wrap {
+            Some(wrap) if wrap.width() > 0 => {
+                let (row, start) = wrap.locate(display_row);
+                BufferPos::new(row, start + offset % wrap.width())
+            }
+            _ => BufferPos::new(display_row, offset),
+        }
+    }
+
+    /// The start of `display_row`, on its first non-blank if it starts a line.
+    fn screen_line(&self, display_row: usize) -> BufferPos {
+        let pos = self.at_display(display_row, 0);
+        if pos.col == 0 {
+            self.first_non_blank(pos.row)
+        } else {
+            pos
+        }
+    }
+
+    /// Rows the view shows, up to the end of the content.
+    fn visible_rows(&self) -> usize {
+        let bottom = (self.top + self.height).min(self.total_rows());
+        bottom.saturating_sub(self.top).max(1)
+    }
+}
+
+/// Where `motion`, repeated or aimed by `count`, takes `cursor`.
+pub fn motion_target(
+    motion: Motion,
+    count: Option<usize>,
+    cursor: BufferPos,
+    view: &View<'_>,
+) -> BufferPos {
+    let n = count.unwrap_or(1).max(1);
+    // j and k keep the column, so it comes back on longer lines
+    let unclamped = cursor;
+    let cursor = clamp_pos(cursor, view.lines);
+    let repeat = |step: &dyn Fn(BufferPos) -> BufferPos| (0..n).fold(cursor, |pos, _| step(pos));
+    let target = match motion {
+        Motion::Left => BufferPos::new(cursor.row, cursor.col.saturating_sub(n)),
+        Motion::Right => BufferPos::new(cursor.row, cursor.col + n),
+        Motion::Up => return BufferPos::new(unclamped.row.saturating_sub(n), unclamped.col),
+        Motion::Down => {
+            return BufferPos::new((unclamped.row + n).min(view.last_row()), unclamped.col)
+        }
+        Motion::LineStart => BufferPos::new(cursor.row, 0),
+        Motion::FirstNonBlank => view.first_non_blank(cursor.row),
+        Motion::LineEnd => view.line_end((cursor.row + n - 1).min(view.last_row())),
+        Motion::WordForward(big) => repeat(&|pos| view.word_forward(pos, big)),
+        Motion::WordBackward(big) => repeat(&|pos| view.word_backward(pos, big)),
+        Motion::WordEnd(big) => repeat(&|pos| view.word_end(pos, big)),
+        Motion::Top => view.first_non_blank(count.map_or(0, |line| line - 1)),
+        Motion::Bottom => view.first_non_blank(count.map_or(view.last_row(), |line| line - 1)),
+        Motion::ParagraphForward => repeat(&|pos| {
+            let row = (pos.row..view.content)
+                .skip_while(|&row| view.is_blank_line(row))
+                .find(|&row| view.is_blank_line(row));
+            match row {
+                Some(row) => BufferPos::new(row, 0),
+                None => view.line_end(view.last_row()),
+            }
+        }),
+        Motion::ParagraphBackward => repeat(&|pos| {
+            let row = (0..=pos.row)
+                .rev()
+                .skip_while(|&row| view.is_blank_line(row))
+                .find(|&row| view.is_blank_line(row));
+            BufferPos::new(row.unwrap_or(0), 0)
+        }),
+        Motion::Find {
+            target,
+            forward,
+            till,
+            again,
+        } => {
+            let hits = |col: &usize| view.char_at(BufferPos::new(cursor.row, *col)) == Some(target);
+            let skip = usize::from(till && again);
+            let till = usize::from(till);
+            let found = if forward {
+                (cursor.col + 1 + skip..view.len(cursor.row))
+                    .filter(hits)
+                    .nth(n - 1)
+                    .map(|col| col - till)
+            } else {
+                (0..cursor.col.saturating_sub(skip))
+                    .rev()
+                    .filter(hits)
+                    .nth(n - 1)
+                    .map(|col| col + till)
+            };
+            found.map_or(cursor, |col| BufferPos::new(cursor.row, col))
+        }
+        Motion::ScreenTop => {
+            let offset = (n - 1).min(view.visible_rows() - 1);
+            view.screen_line(view.top + offset)
+        }
+        Motion::ScreenMiddle => view.screen_line(view.top + (view.visible_rows() - 1) / 2),
+        Motion::ScreenBottom => {
+            let offset = (n - 1).min(view.visible_rows() - 1);
+            view.screen_line(view.top + view.visible_rows() - 1 - offset)
+        }
+        Motion::HalfPageUp | Motion::HalfPageDown => {
+            let half = (view.height / 2).max(1);
+            let row = view.display_row(cursor);
+            let row = if motion == Motion::HalfPageUp {
+                row.saturating_sub(half)
+            } else {
+                row + half
+            };
+            let offset = match view.wrap {
+                Some(wrap) if wrap.width() > 0 => cursor.col % wrap.width(),
+                _ => cursor.col,
+            };
+            view.at_display(row, offset)
+        }
+    };
+    clamp_pos(target, view.lines)
+}
+
+/// A key that waits for the next one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pending {
+    G,
+    Find { forward: bool, till: bool },
+    SetMark,
+    JumpMark,
+}
+
+/// Copy mode: vim keys for a keyboard (`v`/`V`) selection. Holds a typed
+/// count, a key waiting for its argument, and marks per process.
+#[derive(Debug, Default)]
+pub struct CopyMode {
+    count: Option<usize>,
+    pending: Option<Pending>,
+    /// Last f/F/t/T, for `;` and `,`
+    last_find: Option<(char, bool, bool)>,
+    marks: HashMap<String, HashMap<char, BufferPos>>,
+}
+
+impl CopyMode {
+    /// Handle a key while the keyboard selection is being moved. Returns
+    /// false for keys copy mode leaves to the caller (Esc, y, Enter, …).
+    pub fn handle_key(
+        &mut self,
+        key: KeyEvent,
+        selection: &mut SelectionState,
+        process: &mut ManagedProcess,
+        visible_height: usize,
+        viewport_width: usize,
+    ) -> bool {
+        let KeyCode::Char(c) = key.code else {
+            let motion = match key.code {
+                KeyCode::Left => Motion::Left,
+                KeyCode::Right => Motion::Right,
+                KeyCode::Up => Motion::Up,
+                KeyCode::Down => Motion::Down,
+                KeyCode::Home => Motion::LineStart,
+                KeyCode::End => Motion::LineEnd,
+                _ => {
+                    self.reset();
+                    return false;
+                }
+            };
+            self.apply(motion, selection, process, visible_height, viewport_width);
+            return true;
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        if let Some(pending) = self.pending.take() {
+            match pending {
+                Pending::G if c == 'g' => {
+                    self.apply(
+                        Motion::Top,
+                        selection,
+                        process,
+                        visible_height,
+                        viewport_width,
+                    );
+                }
+                Pending::Find { forward, till } => {
+                    self.last_find = Some((c, forward, till));
+                    let motion = Motion::Find {
+                        target: c,
+                        forward,
+                        till,
+                        again: false,
+                    };
+                    self.apply(motion, selection, process, visible_height, viewport_width);
+                }
+                Pending::SetMark if c.is_ascii_lowercase() => {
+                    self.marks
+                        .entry(process.config.name.clone())
+                        .or_default()
+                        .insert(c, selection.cursor);
+                }
+                Pending::JumpMark => {
+                    let mark = self
+                        .marks
+                        .get(&process.config.name)
+                        .and_then(|marks| marks.get(&c));
+                    if let Some(&pos) = mark {
+                        selection.move_cursor(clamp_pos(pos, process.buffer.get_all_lines()));
+                        keep_visible(process, selection.cursor, visible_height, viewport_width);
+                    }
+                }
+                _ => {}
+            }
+            self.count = None;
+            return true;
+        }
+
+        if ctrl {
+            let motion = match c {
+                'u' => Motion::HalfPageUp,
+                'd' => Motion::HalfPageDown,
+                _ => {
+                    self.reset();
+                    return false;
+                }
+            };
+            // The view moves with the cursor
+            let half = (visible_height / 2).max(1);
+            if motion == Motion::HalfPageUp {
+                scroll_up(process, half);
+            } else {
+                scroll_down(process, half, visible_height, viewport_width);
+            }
+            self.apply(motion, selection, process, visible_height, viewport_width);
+            return true;
+        }
+
+        let digit = c.to_digit(10).map(|d| d as usize);
+        match (digit, self.count) {
+            (Some(d), Some(count)) => {
+                self.count = Some((count * 10 + d).min(MAX_COUNT));
+                return true;
+            }
+            (Some(d), None) if d > 0 => {
+                self.count = Some(d);
+                return true;
+            }
+            _ => {}
+        }
+
+        let motion = match c {
+            'h' => Motion::Left,
+            'l' => Motion::Right,
+            'k' => Motion::Up,
+            'j' => Motion::Down,
+            '0' => Motion::LineStart,
+            '^' => Motion::FirstNonBlank,
+            '$' => Motion::LineEnd,
+            'w' | 'W' => Motion::WordForward(c == 'W'),
+            'b' | 'B' => Motion::WordBackward(c == 'B'),
+            'e' | 'E' => Motion::WordEnd(c == 'E'),
+            'G' => Motion::Bottom,
+            '{' => Motion::ParagraphBackward,
+            '}' => Motion::ParagraphForward,
+            'H' => Motion::ScreenTop,
+            'M' => Motion::ScreenMiddle,
+            'L' => Motion::ScreenBottom,
+            ';' | ',' => {
+                let Some((target, forward, till)) = self.last_find else {
+                    self.count = None;
+                    return true;
+                };
+                Motion::Find {
+                    target,
+                    forward: forward == (c == ';'),
+                    till,
+                    again: true,
+                }
+            }
+            'g' => return self.wait(Pending::G),
+            'f' | 'F' | 't' | 'T' => {
+                return self.wait(Pending::Find {
+                    forward: c.is_ascii_lowercase(),
+                    till: c.eq_ignore_ascii_case(&'t'),
+                })
+            }
+            'm' => return self.wait(Pending::SetMark),
+            '\'' | '`' => return self.wait(Pending::JumpMark),
+            'o' => {
+                std::mem::swap(&mut selection.anchor, &mut selection.cursor);
+                keep_visible(process, selection.cursor, visible_height, viewport_width);
+                self.count = None;
+                return true;
+            }
+            'v' | 'V' => {
+                let mode = if c == 'v' {
+                    SelectionMode::Char
+                } else {
+                    SelectionMode::Line
+                };
+                // Like vim, the same key again leaves visual mode
+                if selection.mode == mode {
+                    selection.clear();
+                } else {
+                    selection.mode = mode;
+                }
+                self.count = None;
+                return true;
+            }
+            _ => {
+                self.reset();
+                return false;
+            }
+        };
+        self.apply(motion, selection, process, visible_height, viewport_width);
+        true
+    }
+
+    fn wait(&mut self, pending: Pending) -> bool {
+        self.pending = Some(pending);
+        true
+    }
+
+    /// Forget a half-typed count or key.
+    pub fn reset(&mut self) {
+        self.count = None;
+        self.pending = None;
+    }
+
+    fn apply(
+        &mut self,
+        motion: Motion,
+        selection: &mut SelectionState,
+        process: &mut ManagedProcess,
+        visible_height: usize,
+        viewport_width: usize,
+    ) {
+        let count = self.count.take();
+        process.sync_wrap(viewport_width);
+        let wrap = (process.wrap_enabled && viewport_width > 0).then_some(&process.wrap_index);
+        let view = View {
+            lines: process.buffer.get_all_lines(),
+            content: process.buffer.content_line_count(),
+            wrap,
+            top: process.scroll_offset,
+            height: visible_height,
+        };
+        let target = motion_target(motion, count, selection.cursor, &view);
+        selection.move_cursor(target);
+        keep_visible(process, target, visible_height, viewport_width);
+    }
+}
+
+/// Scroll as little as needed to bring `pos` into view.
+fn keep_visible(
+    process: &mut ManagedProcess,
+    pos: BufferPos,
+    visible_height: usize,
+    viewport_width: usize,
+) {
+    let display_row = if process.wrap_enabled && viewport_width > 0 {
+        let len = process
+            .buffer
+            .get_all_lines()
+            .get(pos.row)
+            .map_or(0, |line| line.len());
+        let col = pos.col.min(len.saturating_sub(1));
+        process.sync_wrap(viewport_width);
+        process.wrap_index.row_start(pos.row) + col / viewport_width
+    } else {
+        pos.row
+    };
+    if display_row < process.scroll_offset {
+        process.scroll_offset = display_row;
+        process.auto_scroll = false;
+    } else if display_row >= process.scroll_offset + visible_height.max(1) {
+        process.scroll_offset = display_row + 1 - visible_height.max(1);
+        process.auto_scroll = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::buffer::TerminalBuffer;
+
+    fn buffer(text: &str) -> TerminalBuffer {
+        let mut buffer = TerminalBuffer::new(40, 10);
+        buffer.write(text.replace('\n', "\r\n").as_bytes());
+        buffer
+    }
+
+    fn view(buffer: &TerminalBuffer) -> View<'_> {
+        View {
+            lines: buffer.get_all_lines(),
+            content: buffer.content_line_count(),
+            wrap: None,
+            top: 0,
+            height: 4,
+        }
+    }
+
+    fn go(
+        view: &View<'_>,
+        motion: Motion,
+        count: Option<usize>,
+        from: (usize, usize),
+    ) -> (usize, usize) {
+        let pos = motion_target(motion, count, BufferPos::new(from.0, from.1), view);
+        (pos.row, pos.col)
+    }
+
+    #[test]
+    fn word_motions_cross_lines_and_punctuation() {
+        let buffer = buffer("foo.bar  baz\n\n  qux-1");
+        let view = view(&buffer);
+        let w = Motion::WordForward(false);
+        assert_eq!(go(&view, w, None, (0, 0)), (0, 3));
+        assert_eq!(go(&view, w, Some(3), (0, 0)), (0, 9));
+        // The empty line is a stop of its own
+        assert_eq!(go(&view, w, None, (0, 9)), (1, 0));
+        assert_eq!(go(&view, w, None, (1, 0)), (2, 2));
+        assert_eq!(go(&view, Motion::WordForward(true), None, (0, 0)), (0, 9));
+        assert_eq!(go(&view, Motion::WordEnd(false), None, (0, 0)), (0, 2));
+        assert_eq!(go(&view, Motion::WordEnd(true), None, (0, 0)), (0, 6));
+        assert_eq!(go(&view, Motion::WordEnd(false), None, (0, 11)), (2, 4));
+        assert_eq!(go(&view, Motion::WordBackward(false), None, (2, 2)), (1, 0));
+        assert_eq!(
+            go(&view, Motion::WordBackward(false), Some(2), (0, 9)),
+            (0, 3)
+        );
+        assert_eq!(go(&view, Motion::WordBackward(true), None, (0, 9)), (0, 0));
+    }
+
+    #[test]
+    fn line_paragraph_and_find_motions() {
+        let buffer = buffer("  one two\nthree\n\nfour\n\nfive six");
+        let view = view(&buffer);
+        assert_eq!(go(&view, Motion::FirstNonBlank, None, (0, 7)), (0, 2));
+        assert_eq!(go(&view, Motion::LineStart, None, (0, 7)), (0, 0));
+        assert_eq!(go(&view, Motion::LineEnd, None, (0, 0)), (0, 8));
+        assert_eq!(go(&view, Motion::LineEnd, Some(2), (0, 0)), (1, 4));
+        assert_eq!(go(&view, Motion::Top, None, (3, 1)), (0, 2));
+        assert_eq!(go(&view, Motion::Bottom, None, (0, 0)), (5, 0));
+        assert_eq!(go(&view, Motion::Bottom, Some(2), (0, 0)), (1, 0));
+        assert_eq!(go(&view, Motion::ParagraphForward, None, (0, 3)), (2, 0));
+        assert_eq!(go(&view, Motion::ParagraphForward, Some(2), (0, 3)), (4, 0));
+        assert_eq!(go(&view, Motion::ParagraphForward, Some(3), (0, 3)), (5, 7));
+        assert_eq!(go(&view, Motion::ParagraphBackward, None, (5, 3)), (4, 0));
+        assert_eq!(
+            go(&view, Motion::ParagraphBackward, Some(2), (5, 3)),
+            (2, 0)
+        );
+        assert_eq!(
+            go(&view, Motion::ParagraphBackward, Some(3), (5, 3)),
+            (0, 0)
+        );
+        let find = |target, forward, till, again| Motion::Find {
+            target,
+            forward,
+            till,
+            again,
+        };
+        assert_eq!(
+            go(&view, find('o', true, false, false), None, (0, 0)),
+            (0, 2)
+        );
+        assert_eq!(
+            go(&view, find('o', true, false, false), Some(2), (0, 0)),
+            (0, 8)
+        );
+        assert_eq!(
+            go(&view, find('t', true, true, false), None, (0, 0)),
+            (0, 5)
+        );
+        assert_eq!(
+            go(&view, find('o', true, true, false), None, (0, 1)),
+            (0, 1)
+        );
+        // `;` after a t doesn't get stuck before the same match
+        assert_eq!(go(&view, find('o', true, true, true), None, (0, 1)), (0, 7));
+        assert_eq!(
+            go(&view, find('n', false, false, false), None, (0, 8)),
+            (0, 3)
+        );
+        assert_eq!(
+            go(&view, find('o', false, true, false), None, (0, 8)),
+            (0, 3)
+        );
+        assert_eq!(
+            go(&view, find('x', true, false, false), None, (0, 0)),
+            (0, 0)
+        );
+    }
+
+    #[test]
+    fn screen_motions_follow_wrapped_rows() {
+        // Width 4: "aaaaaaaaaa" takes three display rows
+        let buffer = buffer("aaaaaaaaaa\nb\nc\nd\ne");
+        let wrap = WrapIndex::new(buffer.get_all_lines(), 4);
+        let view = View {
+            wrap: Some(&wrap),
+            top: 1,
+            ..view(&buffer)
+        };
+        assert_eq!(go(&view, Motion::ScreenTop, None, (4, 0)), (0, 4));
+        assert_eq!(go(&view, Motion::ScreenMiddle, None, (4, 0)), (0, 8));
+        assert_eq!(go(&view, Motion::ScreenBottom, None, (0, 0)), (2, 0));
+        assert_eq!(go(&view, Motion::ScreenBottom, Some(2), (0, 0)), (1, 0));
+        assert_eq!(go(&view, Motion::HalfPageDown, None, (0, 1)), (0, 9));
+        assert_eq!(go(&view, Motion::HalfPageUp, None, (1, 0)), (0, 4));
+    }
+}
@@ -0,0 +1,143 @@
+use crate::process::links::FileRef;
+use crate::process::pty::shell_invocation;
+use crate::process::ManagedProcess;
+use crate::ui::App;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+
+/// Editors that take `-g file:line:col`.
+const GOTO_FLAG: [&str; 6] = [
+    "code",
+    "code-insiders",
+    "codium",
+    "cursor",
+    "windsurf",
+    "vscodium",
+];
+/// Editors that take `file:line:col`.
+const FILE_LINE_COL: [&str; 4] = ["hx", "helix", "subl", "zed"];
+/// Editors that take `+line file`.
+const PLUS_LINE: [&str; 8] = ["vi", "vim", "nvim", "nano", "emacs", "micro", "kak", "mg"];
+
+/// Editor template for `$VISUAL` or `$EDITOR`, if either is set.
+pub fn default_template() -> Option<String> {
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.trim().is_empty())?;
+    Some(template_for(&editor))
+}
+
+/// A template that jumps to the line with `editor`, as far as it is known.
+fn template_for(editor: &str) -> String {
+    let program = editor.split_whitespace().next().unwrap_or_default();
+    let program = Path::new(program)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(program);
+    if GOTO_FLAG.contains(&program) {
+        format!("{editor} -g {{file}}:{{line}}:{{col}}")
+    } else if FILE_LINE_COL.contains(&program) {
+        format!("{editor} {{file}}:{{line}}:{{col}}")
+    } else if PLUS_LINE.contains(&program) {
+        format!("{editor} +{{line}} {{file}}")
+    } else {
+        format!("{editor} {{file}}")
+    }
+}
+
+/// Where `path` from a process's output is: relative to the process's
+/// working directory, else to panex's own. `None` if it doesn't exist.
+pub fn resolve_path(path: &str, cwd: Option<&Path>) -> Option<PathBuf> {
+    let path = match path.strip_prefix("~/") {
+        Some(rest) => PathBuf::from(std::env::var_os("HOME")?).join(rest),
+        None => PathBuf::from(path),
+    };
+    if path.is_absolute() {
+        return path.exists().then_some(path);
+    }
+    cwd.map(|cwd| cwd.join(&path))
+        .into_iter()
+        .chain([path])
+        .find(|candidate| candidate.exists())
+}
+
+/// Fill in `{file}`, `{line}` and `{col}` of an editor template. `{file}`
+/// is quoted for the shell, and appended if the template lacks it.
+pub fn editor_command(template: &str, file: &Path, file_ref: &FileRef) -> String {
+    let template = if template.contains("{file}") {
+        template.to_string()
+    } else {
+        format!("{template} {{file}}")
+    };
+    template
+        .replace("{line}", &file_ref.line.to_string())
+        .replace("{col}", &file_ref.col.unwrap_or(1).to_string())
+        .replace("{file}", &shell_quote(&file.to_string_lossy()))
+}
+
+fn shell_quote(s: &str) -> String {
+    if cfg!(windows) {
+        format!("\"{s}\"")
+    } else {
+        format!("'{}'", s.replace('\'', r"'\''"))
+    }
+}
+
+/// Queue `file_ref` from `process`'s output for the editor, or say in the
+/// status bar why it can't be opened.
+pub fn open_file_ref(app: &mut App, process: &ManagedProcess, file_ref: &FileRef) {
+    let Some(file) = resolve_path(&file_ref.path, process.cwd.as_deref()) else {
+        app.set_status(&format!("No such file: {}", file_ref.path));
+        return;
+    };
+    let Some(template) = app.editor.clone().or_else(default_template) else {
+        app.set_status("No editor: set editor in panex.toml, --editor or $EDITOR");
+        return;
+    };
+    app.pending_editor = Some(editor_command(&template, &file, file_ref));
+}
+
+/// Run `command` through the shell with the terminal's stdio, waiting for
+/// it. The caller hands the terminal over first.
+pub fn run_in_foreground(command: &str) -> std::io::Result<ExitStatus> {
+    let (program, args) = shell_invocation(command);
+    Command::new(program).args(args).status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_follow_the_editor() {
+        assert_eq!(
+            template_for("/usr/bin/code --wait"),
+            "/usr/bin/code --wait -g {file}:{line}:{col}"
+        );
+        assert_eq!(template_for("hx"), "hx {file}:{line}:{col}");
+        assert_eq!(template_for("nvim"), "nvim +{line} {file}");
+        assert_eq!(template_for("ed"), "ed {file}");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn editor_command_fills_and_quotes() {
+        let file_ref = FileRef {
+            path: "it's.rs".into(),
+            line: 42,
+            col: None,
+            start: 0,
+            end: 0,
+        };
+        let file = Path::new("src/it's.rs");
+        assert_eq!(
+            editor_command("code -g {file}:{line}:{col}", file, &file_ref),
+            r"code -g 'src/it'\''s.rs':42:1"
+        );
+        assert_eq!(
+            editor_command("myedit --line {line}", file, &file_ref),
+            r"myedit --line 42 'src/it'\''s.rs'"
+        );
+    }
+}
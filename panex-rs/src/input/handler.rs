@@ -1,13 +1,27 @@
-use crate::input::clipboard::copy_to_clipboard;
+use crate::config::{
+    format_config_file, ConfigFileContents, ProcessConfig, Signal, DEFAULT_CONFIG_FILE,
+};
+use crate::input::browser::open_in_browser;
+use crate::input::clipboard::{copy_to_clipboard, paste_from_clipboard};
+use crate::input::keyboard::encode_key;
+use crate::input::pipe::PipeJob;
 use crate::input::selection::{
-    extract_selected_text, visual_row_to_buffer_row, BufferPos, SelectionPhase,
+    extract_selected_text, selected_spans, visual_row_to_buffer_row, BufferPos, SelectionPhase,
+    SelectionState,
 };
-use crate::process::ProcessManager;
-use crate::ui::app::{RestartAction, RestartPhase};
+use crate::process::export::{export, file_stem, ExportFormat};
+use crate::process::{metrics, ManagedProcess, ProcessManager};
+use crate::ui::app::{PendingPaste, RestartAction, RestartPhase};
+use crate::ui::command::{self, Command};
+use crate::ui::help_popup::PipePopup;
+use crate::ui::hints::{find_hints, HintInput, HintState};
 use crate::ui::output_panel::{scroll_down, scroll_to_bottom, scroll_to_top, scroll_up};
 use crate::ui::search::{find_matches, nearest_match_index};
 use crate::ui::{App, InputMode};
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use anyhow::Context;
+use chrono::Local;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::path::PathBuf;
 
 /// Returns Some((cols, rows)) if a resize event was received (for debouncing in main loop)
 pub fn handle_event(
@@ -28,10 +42,69 @@ pub fn handle_event(
             None
         }
         Event::Resize(cols, rows) => Some((cols, rows)),
+        Event::Paste(text) => {
+            handle_paste(&text, app, pm);
+            None
+        }
         _ => None,
     }
 }
 
+/// Terminal paste (bracketed paste from the host terminal)
+fn handle_paste(text: &str, app: &mut App, pm: &mut ProcessManager) {
+    if app.show_help
+        || app.show_info
+        || app.show_tree
+        || app.signal_menu.is_some()
+        || app.link_menu.is_some()
+        || app.hints.is_some()
+        || app.pipe_output.is_some()
+        || app.pending_quit.is_some()
+        || app.pending_paste.is_some()
+    {
+        return;
+    }
+    match app.mode {
+        InputMode::Focus => {
+            if let Some(name) = pm.process_names().get(app.selected_index).cloned() {
+                paste_to_process(pm, &name, text);
+            }
+        }
+        InputMode::Browse => {
+            // Paste into the prompt or search query; neither can hold newlines
+            if let Some(input) = app.command_prompt.as_mut() {
+                input.extend(text.chars().filter(|c| !c.is_control()));
+            } else if app.search.is_typing() {
+                for c in text.chars().filter(|c| !c.is_control()) {
+                    app.search.push_char(c);
+                }
+            }
+        }
+    }
+}
+
+/// Write pasted text to a process, honoring its bracketed paste mode.
+fn paste_to_process(pm: &ProcessManager, name: &str, text: &str) {
+    let bracketed = pm
+        .get_process(name)
+        .map(|p| p.buffer.wants_bracketed_paste())
+        .unwrap_or(false);
+    let _ = pm.write_to_process(name, &paste_bytes(text, bracketed));
+}
+
+/// Encode pasted text for a child PTY. Newlines become CR like a real terminal
+/// paste; with bracketed paste the text is wrapped in `ESC[200~ … ESC[201~` and
+/// any embedded end marker is dropped so the paste can't terminate early.
+fn paste_bytes(text: &str, bracketed: bool) -> Vec<u8> {
+    let normalized = text.replace("\r\n", "\r").replace('\n', "\r");
+    if bracketed {
+        let body = normalized.replace("\x1b[201~", "");
+        format!("\x1b[200~{body}\x1b[201~").into_bytes()
+    } else {
+        normalized.into_bytes()
+    }
+}
+
 fn handle_key(
     key: KeyEvent,
     app: &mut App,
@@ -39,6 +112,11 @@ fn handle_key(
     visible_height: usize,
     viewport_width: usize,
 ) {
+    // Only presses and repeats act; release events (kitty/Windows) are ignored
+    if key.kind == KeyEventKind::Release {
+        return;
+    }
+
     // Help popup: scroll or close
     if app.show_help {
         match key.code {
@@ -61,6 +139,168 @@ fn handle_key(
         return;
     }
 
+    // Process tree popup: scroll or close
+    if app.show_tree {
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                app.tree_scroll = app.tree_scroll.saturating_add(1);
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                app.tree_scroll = app.tree_scroll.saturating_sub(1);
+            }
+            KeyCode::PageDown => {
+                app.tree_scroll = app.tree_scroll.saturating_add(10);
+            }
+            KeyCode::PageUp => {
+                app.tree_scroll = app.tree_scroll.saturating_sub(10);
+            }
+            _ => {
+                app.show_tree = false;
+            }
+        }
+        return;
+    }
+
+    // Signal menu: move with j/k, send with Enter or the entry's number,
+    // anything else cancels
+    if let Some(index) = app.signal_menu.take() {
+        let last = Signal::MENU.len() - 1;
+        let chosen = match key.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                app.signal_menu = Some((index + 1).min(last));
+                None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                app.signal_menu = Some(index.saturating_sub(1));
+                None
+            }
+            KeyCode::Enter => Some(index),
+            KeyCode::Char(c) => c
+                .to_digit(10)
+                .and_then(|d| (d as usize).checked_sub(1))
+                .filter(|&i| i <= last),
+            _ => None,
+        };
+        if let Some(i) = chosen {
+            send_signal(app, pm, Signal::MENU[i]);
+        }
+        return;
+    }
+
+    // Pipe output: scroll, copy with y, anything else closes
+    if let Some(output) = app.pipe_output.as_mut() {
+        // The popup covers the status bar row too
+        let max_scroll = PipePopup::max_scroll(output, visible_height as u16 + 1);
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                output.scroll = output.scroll.saturating_add(1).min(max_scroll)
+            }
+            KeyCode::Up | KeyCode::Char('k') => output.scroll = output.scroll.saturating_sub(1),
+            KeyCode::PageDown => output.scroll = output.scroll.saturating_add(10).min(max_scroll),
+            KeyCode::PageUp => output.scroll = output.scroll.saturating_sub(10),
+            KeyCode::Char('y') => {
+                if copy_to_clipboard(&output.text) {
+                    app.set_status("Copied!");
+                }
+                app.pipe_output = None;
+            }
+            _ => app.pipe_output = None,
+        }
+        return;
+    }
+
+    // Links popup: move with j/k, open with Enter/o, copy with y, anything
+    // else closes
+    if let Some(index) = app.link_menu.take() {
+        let links = pm
+            .process_names()
+            .get(app.selected_index)
+            .and_then(|name| pm.get_process(name))
+            .map(ManagedProcess::quick_links)
+            .unwrap_or_default();
+        let Some(last) = links.len().checked_sub(1) else {
+            return;
+        };
+        let index = index.min(last);
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => app.link_menu = Some((index + 1).min(last)),
+            KeyCode::Up | KeyCode::Char('k') => app.link_menu = Some(index.saturating_sub(1)),
+            KeyCode::Enter | KeyCode::Char('o') => match open_in_browser(&links[index]) {
+                Ok(()) => app.set_status(&format!("Opened {}", links[index])),
+                Err(e) => app.set_status(&format!("Failed to open browser: {e}")),
+            },
+            KeyCode::Char('y') if copy_to_clipboard(&links[index]) => app.set_status("Copied!"),
+            _ => {}
+        }
+        return;
+    }
+
+    // Hint mode: a label copies its match, Shift+label pastes it into the
+    // selected process, Backspace takes a letter back, anything else cancels
+    if let Some(mut hints) = app.hints.take() {
+        let c = match key.code {
+            KeyCode::Char(c)
+                if c.is_ascii_alphabetic() && !key.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                c
+            }
+            KeyCode::Backspace => {
+                hints.typed.pop();
+                app.hints = Some(hints);
+                return;
+            }
+            _ => return,
+        };
+        let paste = c.is_ascii_uppercase() || key.modifiers.contains(KeyModifiers::SHIFT);
+        match hints.type_char(c) {
+            HintInput::Pending => app.hints = Some(hints),
+            HintInput::Picked(text) if paste => {
+                if let Some(name) = pm.process_names().get(app.selected_index).cloned() {
+                    paste_to_process(pm, &name, &text);
+                }
+            }
+            HintInput::Picked(text) => {
+                if copy_to_clipboard(&text) {
+                    app.set_status("Copied!");
+                }
+            }
+            HintInput::NoMatch => {}
+        }
+        return;
+    }
+
+    // Escaped descendants at quit: k kills them too, q/Enter leaves them
+    // running, anything else cancels the quit
+    if let Some(escaped) = app.pending_quit.take() {
+        let is_ctrl_c =
+            key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('k') => {
+                pm.kill_escaped_on_shutdown(escaped.into_iter().map(|(_, e)| e).collect());
+                app.quit();
+            }
+            KeyCode::Char('q') | KeyCode::Enter => app.quit(),
+            _ if is_ctrl_c => app.quit(),
+            _ => {}
+        }
+        return;
+    }
+
+    // Process detail popup: any key closes
+    if app.show_info {
+        app.show_info = false;
+        return;
+    }
+
+    // Multi-line paste confirmation: y/Enter pastes, anything else cancels
+    if let Some(paste) = app.pending_paste.take() {
+        if matches!(key.code, KeyCode::Char('y') | KeyCode::Enter) {
+            paste_to_process(pm, &paste.name, &paste.text);
+            app.set_status("Pasted!");
+        }
+        return;
+    }
+
     match app.mode {
         InputMode::Browse => handle_browse_key(key, app, pm, visible_height, viewport_width),
         InputMode::Focus => handle_focus_key(key, app, pm),
@@ -77,6 +317,11 @@ fn handle_browse_key(
     let count = pm.process_count();
     let selected_name = pm.process_names().get(app.selected_index).cloned();
 
+    if app.command_prompt.is_some() {
+        handle_command_key(key, app, pm);
+        return;
+    }
+
     // Handle search mode keys first
     if app.search.is_typing() {
         handle_search_typing(key, app, pm, visible_height, viewport_width);
@@ -89,6 +334,20 @@ fn handle_browse_key(
 
     // Handle selection mode keys first
     if app.selection.is_active() {
+        // Copy-mode motions while a keyboard selection is being moved
+        if app.selection.phase == SelectionPhase::Selecting {
+            if let Some(process) = selected_name.as_ref().and_then(|n| pm.get_process_mut(n)) {
+                if app.copy_mode.handle_key(
+                    key,
+                    &mut app.selection,
+                    process,
+                    visible_height,
+                    viewport_width,
+                ) {
+                    return;
+                }
+            }
+        }
         // Ctrl-C with active selection = copy (not quit)
         let is_copy = matches!(key.code, KeyCode::Char('y') | KeyCode::Enter)
             || (matches!(key.code, KeyCode::Char('c'))
@@ -113,41 +372,13 @@ fn handle_browse_key(
                 app.selection.clear();
                 return;
             }
-            // Movement keys while in visual select
-            KeyCode::Up | KeyCode::Char('k') => {
-                if app.selection.phase == SelectionPhase::Selecting {
-                    let cur = app.selection.cursor;
-                    if cur.row > 0 {
-                        app.selection
-                            .move_cursor(BufferPos::new(cur.row - 1, cur.col));
-                    }
-                }
+            KeyCode::Char('|') => {
+                pipe_key(app, pm);
                 return;
             }
-            KeyCode::Down | KeyCode::Char('j') => {
-                if app.selection.phase == SelectionPhase::Selecting {
-                    let cur = app.selection.cursor;
-                    app.selection
-                        .move_cursor(BufferPos::new(cur.row + 1, cur.col));
-                }
-                return;
-            }
-            KeyCode::Left | KeyCode::Char('h') => {
-                if app.selection.phase == SelectionPhase::Selecting {
-                    let cur = app.selection.cursor;
-                    if cur.col > 0 {
-                        app.selection
-                            .move_cursor(BufferPos::new(cur.row, cur.col - 1));
-                    }
-                }
-                return;
-            }
-            KeyCode::Right | KeyCode::Char('l') => {
-                if app.selection.phase == SelectionPhase::Selecting {
-                    let cur = app.selection.cursor;
-                    app.selection
-                        .move_cursor(BufferPos::new(cur.row, cur.col + 1));
-                }
+            // The selection stays, for `:pipe` or `:export`
+            KeyCode::Char(':') => {
+                app.command_prompt = Some(String::new());
                 return;
             }
             _ => {
@@ -159,8 +390,10 @@ fn handle_browse_key(
 
     match key.code {
         // Quit
-        KeyCode::Char('q') => app.quit(),
-        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => app.quit(),
+        KeyCode::Char('q') => request_quit(app, pm),
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            request_quit(app, pm)
+        }
 
         // Navigation
         KeyCode::Up => app.select_prev(count),
@@ -176,10 +409,22 @@ fn handle_browse_key(
             }
         }
         KeyCode::Char('R') => {
-            let count = pm.process_count();
+            let count = pm.restart_all_count();
             app.restarting = Some((RestartAction::All(count), RestartPhase::Pending));
         }
 
+        // Start a stopped process, keeping its output
+        KeyCode::Char('s') => {
+            if let Some(name) = selected_name {
+                let alive = pm.get_process(&name).is_some_and(|p| p.status.is_alive());
+                if alive {
+                    app.set_status(&format!("{name} is already running"));
+                } else if let Err(e) = pm.start_process(&name) {
+                    app.set_status(&e.to_string());
+                }
+            }
+        }
+
         // Kill
         KeyCode::Char('x') => {
             if let Some(name) = selected_name {
@@ -187,25 +432,81 @@ fn handle_browse_key(
             }
         }
 
+        // Signals
+        KeyCode::Char('K') if selected_name.is_some() => app.signal_menu = Some(0),
+
+        // Hint mode over the visible output
+        KeyCode::Char('f') => {
+            if let Some(process) = selected_name.as_deref().and_then(|n| pm.get_process_mut(n)) {
+                let hints = find_hints(process, viewport_width, visible_height, &app.hint_patterns);
+                if hints.is_empty() {
+                    app.set_status("Nothing to hint");
+                } else {
+                    app.hints = Some(HintState::new(hints));
+                }
+            }
+        }
+
+        // Pipe the scrollback to a command
+        KeyCode::Char('|') => pipe_key(app, pm),
+
+        // URLs and listening ports
+        KeyCode::Char('u') => {
+            if let Some(name) = selected_name {
+                let has_links = pm
+                    .get_process(&name)
+                    .is_some_and(|p| !p.quick_links().is_empty());
+                if has_links {
+                    app.link_menu = Some(0);
+                } else {
+                    app.set_status(&format!("No URLs or listening ports for {name}"));
+                }
+            }
+        }
+        KeyCode::Char('z') => {
+            if let Some(name) = selected_name {
+                match pm.toggle_pause(&name) {
+                    Ok(true) => app.set_status(&format!("Paused {name}")),
+                    Ok(false) => app.set_status(&format!("Resumed {name}")),
+                    Err(e) => app.set_status(&e.to_string()),
+                }
+            }
+        }
+
+        // Paste system clipboard into the selected process
+        KeyCode::Char('p') => {
+            if let Some(name) = selected_name {
+                match paste_from_clipboard() {
+                    Some(text) if text.contains('\n') => {
+                        app.pending_paste = Some(PendingPaste { name, text });
+                    }
+                    Some(text) => {
+                        paste_to_process(pm, &name, &text);
+                        app.set_status("Pasted!");
+                    }
+                    None => app.set_status("Clipboard empty"),
+                }
+            }
+        }
+
         // Toggle wrap
         KeyCode::Char('w') => {
             if let Some(name) = selected_name {
-                if let Some(process) = pm.get_process_mut(&name) {
-                    process.wrap_enabled = !process.wrap_enabled;
-                }
+                pm.toggle_wrap(&name);
             }
         }
 
         // Visual select
         KeyCode::Char('v') => {
             if let Some(name) = &selected_name {
-                if let Some(process) = pm.get_process(name) {
+                if let Some(process) = pm.get_process_mut(name) {
+                    process.sync_wrap(viewport_width);
                     // Start char-wise visual at top-left of visible area
                     let pos = if process.wrap_enabled {
                         visual_row_to_buffer_row(
                             process.scroll_offset,
                             process.buffer.get_all_lines(),
-                            viewport_width,
+                            &process.wrap_index,
                         )
                     } else {
                         BufferPos::new(process.scroll_offset, 0)
@@ -216,13 +517,14 @@ fn handle_browse_key(
         }
         KeyCode::Char('V') => {
             if let Some(name) = &selected_name {
-                if let Some(process) = pm.get_process(name) {
+                if let Some(process) = pm.get_process_mut(name) {
+                    process.sync_wrap(viewport_width);
                     // Start line-wise visual at current scroll position
                     let pos = if process.wrap_enabled {
                         visual_row_to_buffer_row(
                             process.scroll_offset,
                             process.buffer.get_all_lines(),
-                            viewport_width,
+                            &process.wrap_index,
                         )
                     } else {
                         BufferPos::new(process.scroll_offset, 0)
@@ -283,6 +585,27 @@ fn handle_browse_key(
             }
         }
 
+        // Metrics
+        KeyCode::Char('m') => {
+            if metrics::SUPPORTED {
+                app.show_metrics = !app.show_metrics;
+            } else {
+                app.set_status("Metrics need /proc (Linux only)");
+            }
+        }
+        KeyCode::Char('i') if selected_name.is_some() => app.show_info = true,
+        KeyCode::Char('T') => {
+            if metrics::SUPPORTED {
+                app.show_tree = true;
+                app.tree_scroll = 0;
+            } else {
+                app.set_status("Process tree needs /proc (Linux only)");
+            }
+        }
+
+        // Command prompt
+        KeyCode::Char(':') => app.command_prompt = Some(String::new()),
+
         // Help
         KeyCode::Char('?') => app.toggle_help(),
 
@@ -290,6 +613,208 @@ fn handle_browse_key(
     }
 }
 
+/// Keys while the `:` prompt is open: Tab completes, Enter runs, Esc (or
+/// Backspace on an empty line) cancels.
+fn handle_command_key(key: KeyEvent, app: &mut App, pm: &mut ProcessManager) {
+    let Some(input) = app.command_prompt.as_mut() else {
+        return;
+    };
+    match key.code {
+        KeyCode::Esc => app.command_prompt = None,
+        KeyCode::Backspace if input.is_empty() => app.command_prompt = None,
+        KeyCode::Backspace => {
+            input.pop();
+        }
+        KeyCode::Tab => {
+            if let Some(completed) = command::complete(input, pm.process_names()) {
+                *input = completed;
+            }
+        }
+        KeyCode::Enter => {
+            let line = std::mem::take(input);
+            app.command_prompt = None;
+            match command::parse(&line) {
+                Ok(cmd) => run_command(cmd, app, pm),
+                Err(e) => app.set_status(&e),
+            }
+            app.selection.clear();
+        }
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => input.push(c),
+        _ => {}
+    }
+}
+
+/// `|`: pipe to the configured command, or ask for one at the prompt.
+fn pipe_key(app: &mut App, pm: &ProcessManager) {
+    match app.pipe_command.clone() {
+        Some(command) => match pipe_to(app, pm, &command) {
+            Ok(msg) => app.set_status(&msg),
+            Err(e) => app.set_status(&e.to_string()),
+        },
+        // The selection stays, for the command typed at the prompt
+        None => app.command_prompt = Some("pipe ".to_string()),
+    }
+}
+
+/// Pipe the selection, or the selected process's whole scrollback, to
+/// `command`. Its output shows up later through `pipe::poll`; a command
+/// still running from an earlier pipe is killed.
+fn pipe_to(app: &mut App, pm: &ProcessManager, command: &str) -> anyhow::Result<String> {
+    let process = pm
+        .process_names()
+        .get(app.selected_index)
+        .and_then(|name| pm.get_process(name))
+        .ok_or_else(|| anyhow::anyhow!("no process selected"))?;
+    let lines = process.buffer.get_all_lines();
+    let mut text = if app.selection.is_active() {
+        extract_selected_text(&app.selection, lines)
+    } else {
+        let all = SelectionState::all_lines(process.buffer.content_line_count());
+        extract_selected_text(&all, lines)
+    };
+    text.push('\n');
+    app.selection.clear();
+    app.pipe_job = Some(PipeJob::spawn(command, text, process.cwd.as_deref()));
+    Ok(format!("Piping to {command}"))
+}
+
+/// Write the selection, or the selected process's whole scrollback, to
+/// `path` (default: a timestamped file named after the process).
+fn export_to(
+    app: &mut App,
+    pm: &ProcessManager,
+    format: Option<ExportFormat>,
+    path: Option<PathBuf>,
+) -> anyhow::Result<String> {
+    let name = pm
+        .process_names()
+        .get(app.selected_index)
+        .ok_or_else(|| anyhow::anyhow!("no process selected"))?;
+    let process = pm
+        .get_process(name)
+        .ok_or_else(|| anyhow::anyhow!("no process selected"))?;
+    let format = format
+        .or_else(|| path.as_deref().and_then(ExportFormat::from_path))
+        .unwrap_or(ExportFormat::Text);
+    let path = path.unwrap_or_else(|| {
+        let stamp = Local::now().format("%Y%m%d-%H%M%S");
+        format!("{}-{stamp}.{}", file_stem(name), format.extension()).into()
+    });
+    let lines = process.buffer.get_all_lines();
+    let spans = if app.selection.is_active() {
+        selected_spans(&app.selection, lines)
+    } else {
+        let all = SelectionState::all_lines(process.buffer.content_line_count());
+        selected_spans(&all, lines)
+    };
+    std::fs::write(&path, export(lines, &spans, format, name))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    app.selection.clear();
+    Ok(format!(
+        "Exported {} lines to {}",
+        spans.len(),
+        path.display()
+    ))
+}
+
+/// Run a prompt command, reporting the outcome in the status bar.
+fn run_command(cmd: Command, app: &mut App, pm: &mut ProcessManager) {
+    let selected = pm.process_names().get(app.selected_index).cloned();
+    let target = |name: Option<String>| {
+        name.or_else(|| selected.clone())
+            .ok_or_else(|| anyhow::anyhow!("no process selected"))
+    };
+    let result = match cmd {
+        Command::New { name, command } => {
+            let config = ProcessConfig {
+                name: name.clone(),
+                command,
+                ..ProcessConfig::default()
+            };
+            pm.insert_process(config, usize::MAX).map(|()| {
+                app.selected_index = pm.process_count() - 1;
+                format!("Added {name}")
+            })
+        }
+        Command::Remove(name) => target(name).and_then(|name| {
+            pm.remove_process(&name)?;
+            app.selected_index = app.selected_index.min(pm.process_count().saturating_sub(1));
+            Ok(format!("Removed {name}"))
+        }),
+        Command::Edit(None) => {
+            // Open the prompt again with the current command to edit
+            if let Some(process) = selected.as_ref().and_then(|name| pm.get_process(name)) {
+                app.command_prompt = Some(format!("edit {}", process.config.command));
+            }
+            return;
+        }
+        Command::Edit(Some(command)) => target(None).and_then(|name| {
+            pm.set_command(&name, &command)?;
+            Ok(format!("Restarting {name}"))
+        }),
+        Command::Rename(new_name) => target(None).and_then(|name| {
+            pm.rename_process(&name, &new_name)?;
+            Ok(format!("Renamed {name} to {new_name}"))
+        }),
+        Command::Dup(name) => target(name).and_then(|name| {
+            let copy = pm.duplicate_process(&name)?;
+            if let Some(index) = pm.process_names().iter().position(|n| *n == copy) {
+                app.selected_index = index;
+            }
+            Ok(format!("Started {copy}"))
+        }),
+        Command::Pipe(command) => command
+            .or_else(|| app.pipe_command.clone())
+            .ok_or_else(|| anyhow::anyhow!("usage: pipe <command>"))
+            .and_then(|command| pipe_to(app, pm, &command)),
+        Command::Export { format, path } => export_to(app, pm, format, path),
+        Command::Write(path) => {
+            let path = path
+                .or_else(|| app.config_path.clone())
+                .unwrap_or_else(|| DEFAULT_CONFIG_FILE.into());
+            format_config_file(&ConfigFileContents {
+                editor: app.editor.clone(),
+                pipe_command: app.pipe_command.clone(),
+                hint_patterns: app.hint_patterns.user().to_vec(),
+                env_files: pm.env_files().to_vec(),
+                processes: pm.process_configs(),
+            })
+            .and_then(|text| Ok(std::fs::write(&path, text)?))
+            .map(|()| {
+                let msg = format!("Wrote {}", path.display());
+                app.config_path = Some(path);
+                msg
+            })
+        }
+    };
+    match result {
+        Ok(msg) => app.set_status(&msg),
+        Err(e) => app.set_status(&e.to_string()),
+    }
+}
+
+/// Send `signal` to the selected process and report it in the status bar.
+fn send_signal(app: &mut App, pm: &ProcessManager, signal: Signal) {
+    let Some(name) = pm.process_names().get(app.selected_index).cloned() else {
+        return;
+    };
+    match pm.send_signal(&name, signal) {
+        Ok(()) => app.set_status(&format!("Sent {} to {name}", signal.name())),
+        Err(e) => app.set_status(&e.to_string()),
+    }
+}
+
+/// Quit, unless some descendants escaped their process group: those would
+/// survive the shutdown, so ask first.
+fn request_quit(app: &mut App, pm: &mut ProcessManager) {
+    let escaped = pm.escaped_processes();
+    if escaped.is_empty() {
+        app.quit();
+    } else {
+        app.pending_quit = Some(escaped);
+    }
+}
+
 fn handle_search_typing(
     key: KeyEvent,
     app: &mut App,
@@ -319,7 +844,8 @@ fn handle_search_typing(
                 return;
             }
             if let Some(name) = &selected_name {
-                if let Some(process) = pm.get_process(name) {
+                if let Some(process) = pm.get_process_mut(name) {
+                    process.sync_wrap(viewport_width);
                     let matches = find_matches(&query, process.buffer.get_all_lines());
                     let saved_scroll = app.search.saved_scroll().unwrap_or(0);
                     if matches.is_empty() {
@@ -331,9 +857,16 @@ fn handle_search_typing(
                         );
                         app.set_status("No matches");
                     } else {
-                        let current = nearest_match_index(&matches, process.scroll_offset);
+                        // Compare against the buffer row at the top of the view
+                        let top_row = if process.wrap_enabled {
+                            process.wrap_index.locate(process.scroll_offset).0
+                        } else {
+                            process.scroll_offset
+                        };
+                        let current = nearest_match_index(&matches, top_row);
                         // Scroll to show the current match
-                        let match_row = matches[current].row;
+                        let m = &matches[current];
+                        let (match_row, match_col) = (m.row, m.col_start);
                         app.search = crate::ui::search::SearchState::new_active(
                             query,
                             matches,
@@ -341,7 +874,13 @@ fn handle_search_typing(
                             saved_scroll,
                         );
                         if let Some(process) = pm.get_process_mut(name) {
-                            scroll_to_match(process, match_row, visible_height, viewport_width);
+                            scroll_to_match(
+                                process,
+                                match_row,
+                                match_col,
+                                visible_height,
+                                viewport_width,
+                            );
                         }
                     }
                 }
@@ -385,10 +924,10 @@ fn handle_search_active(
         KeyCode::Char('n') => {
             app.search.next_match();
             if let Some(m) = app.search.current_match() {
-                let row = m.row;
+                let (row, col) = (m.row, m.col_start);
                 if let Some(name) = &selected_name {
                     if let Some(process) = pm.get_process_mut(name) {
-                        scroll_to_match(process, row, visible_height, viewport_width);
+                        scroll_to_match(process, row, col, visible_height, viewport_width);
                     }
                 }
             }
@@ -396,10 +935,10 @@ fn handle_search_active(
         KeyCode::Char('N') => {
             app.search.prev_match();
             if let Some(m) = app.search.current_match() {
-                let row = m.row;
+                let (row, col) = (m.row, m.col_start);
                 if let Some(name) = &selected_name {
                     if let Some(process) = pm.get_process_mut(name) {
-                        scroll_to_match(process, row, visible_height, viewport_width);
+                        scroll_to_match(process, row, col, visible_height, viewport_width);
                     }
                 }
             }
@@ -411,21 +950,24 @@ fn handle_search_active(
     }
 }
 
-/// Scroll the output so that the given buffer row is visible
+/// Scroll the output so that the given buffer position is visible
 fn scroll_to_match(
     process: &mut crate::process::ManagedProcess,
     match_row: usize,
+    match_col: usize,
     visible_height: usize,
-    _viewport_width: usize,
+    viewport_width: usize,
 ) {
     process.auto_scroll = false;
-    // Center the match in the viewport if possible
-    let half = visible_height / 2;
-    if match_row > half {
-        process.scroll_offset = match_row - half;
+    // In wrap mode the match sits on the display row holding its column
+    let display_row = if process.wrap_enabled && viewport_width > 0 {
+        process.sync_wrap(viewport_width);
+        process.wrap_index.row_start(match_row) + match_col / viewport_width
     } else {
-        process.scroll_offset = 0;
-    }
+        match_row
+    };
+    // Center the match in the viewport if possible
+    process.scroll_offset = display_row.saturating_sub(visible_height / 2);
 }
 
 fn handle_focus_key(key: KeyEvent, app: &mut App, pm: &mut ProcessManager) {
@@ -452,9 +994,13 @@ fn handle_focus_key(key: KeyEvent, app: &mut App, pm: &mut ProcessManager) {
         _ => {}
     }
 
-    // Forward key to PTY
+    // Forward key to PTY, encoded per the child's requested keyboard modes
     if let Some(name) = selected_name {
-        if let Some(bytes) = key_to_bytes(key) {
+        let modes = pm
+            .get_process(&name)
+            .map(|p| p.buffer.keyboard_modes())
+            .unwrap_or_default();
+        if let Some(bytes) = encode_key(&key, modes) {
             // Special keys produce escape sequences that get echoed as garbage
             // in non-interactive processes. Only forward them when the process
             // has indicated it handles keyboard input (DECCKM, alt screen, mouse).
@@ -493,62 +1039,6 @@ fn is_escape_producing_key(key: &KeyEvent) -> bool {
     )
 }
 
-fn key_to_bytes(key: KeyEvent) -> Option<Vec<u8>> {
-    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
-    let alt = key.modifiers.contains(KeyModifiers::ALT);
-
-    let bytes = match key.code {
-        KeyCode::Char(c) => {
-            if ctrl {
-                // Ctrl+A = 0x01, Ctrl+Z = 0x1A
-                let ctrl_byte = (c.to_ascii_lowercase() as u8).saturating_sub(b'a' - 1);
-                if alt {
-                    vec![0x1b, ctrl_byte]
-                } else {
-                    vec![ctrl_byte]
-                }
-            } else if alt {
-                vec![0x1b, c as u8]
-            } else {
-                c.to_string().into_bytes()
-            }
-        }
-        KeyCode::Enter => vec![0x0d],
-        KeyCode::Tab => vec![0x09],
-        KeyCode::BackTab => vec![0x1b, b'[', b'Z'], // Shift-Tab (CSI Z)
-        KeyCode::Backspace => vec![0x7f],
-        KeyCode::Delete => vec![0x1b, b'[', b'3', b'~'],
-        KeyCode::Esc => vec![0x1b],
-        KeyCode::Up => vec![0x1b, b'[', b'A'],
-        KeyCode::Down => vec![0x1b, b'[', b'B'],
-        KeyCode::Right => vec![0x1b, b'[', b'C'],
-        KeyCode::Left => vec![0x1b, b'[', b'D'],
-        KeyCode::Home => vec![0x1b, b'[', b'H'],
-        KeyCode::End => vec![0x1b, b'[', b'F'],
-        KeyCode::PageUp => vec![0x1b, b'[', b'5', b'~'],
-        KeyCode::PageDown => vec![0x1b, b'[', b'6', b'~'],
-        KeyCode::Insert => vec![0x1b, b'[', b'2', b'~'],
-        KeyCode::F(n) => match n {
-            1 => vec![0x1b, b'O', b'P'],
-            2 => vec![0x1b, b'O', b'Q'],
-            3 => vec![0x1b, b'O', b'R'],
-            4 => vec![0x1b, b'O', b'S'],
-            5 => vec![0x1b, b'[', b'1', b'5', b'~'],
-            6 => vec![0x1b, b'[', b'1', b'7', b'~'],
-            7 => vec![0x1b, b'[', b'1', b'8', b'~'],
-            8 => vec![0x1b, b'[', b'1', b'9', b'~'],
-            9 => vec![0x1b, b'[', b'2', b'0', b'~'],
-            10 => vec![0x1b, b'[', b'2', b'1', b'~'],
-            11 => vec![0x1b, b'[', b'2', b'3', b'~'],
-            12 => vec![0x1b, b'[', b'2', b'4', b'~'],
-            _ => return None,
-        },
-        _ => return None,
-    };
-
-    Some(bytes)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -556,7 +1046,7 @@ mod tests {
     use tokio::sync::mpsc;
 
     fn test_manager(names: &[&str]) -> ProcessManager {
-        let (event_tx, _event_rx) = mpsc::unbounded_channel();
+        let (event_tx, _event_rx) = mpsc::channel(crate::event::EVENT_QUEUE_CAPACITY);
         let mut pm = ProcessManager::new(event_tx, 80, 24, 500, 10_000, false);
 
         for name in names {
@@ -565,6 +1055,7 @@ mod tests {
                 command: "true".to_string(),
                 no_shift_tab: false,
                 wrap_enabled: false,
+                ..ProcessConfig::default()
             });
         }
 
@@ -575,6 +1066,330 @@ mod tests {
         KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
     }
 
+    #[test]
+    fn search_jump_centers_wrapped_display_row() {
+        let mut pm = test_manager(&["one"]);
+        pm.toggle_wrap("one");
+        let gen = pm.get_process("one").unwrap().generation;
+        // Each line wraps to 3 rows at 80 columns; the match is on the last row of line 20
+        let mut output = String::new();
+        for i in 0..40 {
+            let tail = if i == 20 { "needle" } else { "......" };
+            output.push_str(&format!("{}{tail}\r\n", "x".repeat(170)));
+        }
+        pm.handle_output("one", gen, output.as_bytes());
+
+        let process = pm.get_process_mut("one").unwrap();
+        scroll_to_match(process, 20, 170, 10, 80);
+        assert_eq!(process.scroll_offset, 20 * 3 + 2 - 5);
+        assert!(!process.auto_scroll);
+    }
+
+    #[test]
+    fn paste_bytes_converts_newlines_to_carriage_returns() {
+        assert_eq!(paste_bytes("a\nb\r\nc", false), b"a\rb\rc");
+    }
+
+    #[test]
+    fn paste_bytes_wraps_in_markers_when_bracketed() {
+        assert_eq!(paste_bytes("ls\n", true), b"\x1b[200~ls\r\x1b[201~");
+    }
+
+    #[test]
+    fn paste_bytes_strips_embedded_end_marker() {
+        assert_eq!(
+            paste_bytes("a\x1b[201~rm -rf", true),
+            b"\x1b[200~arm -rf\x1b[201~"
+        );
+    }
+
+    #[test]
+    fn any_key_but_confirm_cancels_pending_paste() {
+        let mut app = App::new(false, true);
+        let mut pm = test_manager(&["one"]);
+        app.pending_paste = Some(PendingPaste {
+            name: "one".to_string(),
+            text: "a\nb".to_string(),
+        });
+
+        handle_key(press('n'), &mut app, &mut pm, 24, 80);
+
+        assert!(app.pending_paste.is_none());
+        assert!(app.active_status().is_none());
+    }
+
+    #[test]
+    fn info_popup_opens_on_i_and_any_key_closes_it() {
+        let mut app = App::new(false, true);
+        let mut pm = test_manager(&["one"]);
+
+        handle_key(press('i'), &mut app, &mut pm, 24, 80);
+        assert!(app.show_info);
+
+        // The closing key is swallowed rather than acting in browse mode
+        handle_key(press('q'), &mut app, &mut pm, 24, 80);
+        assert!(!app.show_info);
+        assert!(!app.shutting_down);
+    }
+
+    #[test]
+    fn quit_prompt_cancels_on_other_keys_and_kills_on_k() {
+        let mut app = App::new(false, true);
+        let mut pm = test_manager(&["one"]);
+        let escaped = crate::process::tree::TreeEntry {
+            pid: 4_000_001,
+            pgrp: 4_000_001,
+            start_time: 1,
+            depth: 1,
+            command: "daemon".to_string(),
+            escaped: true,
+            adopted: false,
+        };
+
+        app.pending_quit = Some(vec![("one".to_string(), escaped.clone())]);
+        handle_key(press('x'), &mut app, &mut pm, 24, 80);
+        assert!(app.pending_quit.is_none());
+        assert!(!app.shutting_down);
+
+        app.pending_quit = Some(vec![("one".to_string(), escaped)]);
+        handle_key(press('k'), &mut app, &mut pm, 24, 80);
+        assert!(app.shutting_down);
+    }
+
+    #[test]
+    fn hint_mode_labels_visible_matches() {
+        let mut app = App::new(false, true);
+        let mut pm = test_manager(&["one"]);
+
+        handle_key(press('f'), &mut app, &mut pm, 24, 80);
+        assert!(app.hints.is_none());
+        assert_eq!(app.active_status(), Some("Nothing to hint"));
+
+        let gen = pm.get_process("one").unwrap().generation;
+        pm.write_output("one", gen, b"commit 3f9a2c1d\r\nsee src/lib.rs:3\r\n");
+        handle_key(press('f'), &mut app, &mut pm, 24, 80);
+        let hints = &app.hints.as_ref().unwrap().hints;
+        let labelled: Vec<_> = hints.iter().map(|h| (&*h.label, &*h.text, h.row)).collect();
+        assert_eq!(labelled, [("a", "src/lib.rs:3", 1), ("s", "3f9a2c1d", 0)]);
+
+        // A letter no label starts with cancels, as does Esc
+        handle_key(press('x'), &mut app, &mut pm, 24, 80);
+        assert!(app.hints.is_none());
+        handle_key(press('f'), &mut app, &mut pm, 24, 80);
+        handle_key(KeyEvent::from(KeyCode::Esc), &mut app, &mut pm, 24, 80);
+        assert!(app.hints.is_none());
+    }
+
+    #[test]
+    fn copy_mode_counts_marks_and_scrolls() {
+        let mut app = App::new(false, true);
+        let mut pm = test_manager(&["one"]);
+        let gen = pm.get_process("one").unwrap().generation;
+        let output: String = (0..40).map(|i| format!("line {i:02} word\r\n")).collect();
+        pm.write_output("one", gen, output.as_bytes());
+        pm.get_process_mut("one").unwrap().scroll_offset = 30;
+        let keys = |app: &mut App, pm: &mut ProcessManager, keys: &str| {
+            for c in keys.chars() {
+                handle_key(press(c), app, pm, 10, 80);
+            }
+        };
+
+        // Visual starts at the top of the view, which shows rows 30..40
+        keys(&mut app, &mut pm, "v5k");
+        assert_eq!(app.selection.cursor, BufferPos::new(25, 0));
+        assert_eq!(pm.get_process("one").unwrap().scroll_offset, 25);
+
+        keys(&mut app, &mut pm, "maG");
+        assert_eq!(app.selection.cursor, BufferPos::new(39, 0));
+        keys(&mut app, &mut pm, "'a");
+        assert_eq!(app.selection.cursor, BufferPos::new(25, 0));
+
+        keys(&mut app, &mut pm, "o");
+        assert_eq!(app.selection.anchor, BufferPos::new(25, 0));
+        assert_eq!(app.selection.cursor, BufferPos::new(30, 0));
+        // `w` moves by word here rather than toggling wrap
+        keys(&mut app, &mut pm, "fw2w");
+        assert_eq!(app.selection.cursor, BufferPos::new(31, 5));
+        assert!(!pm.get_process("one").unwrap().wrap_enabled);
+
+        keys(&mut app, &mut pm, "gg");
+        assert_eq!(app.selection.cursor, BufferPos::new(0, 0));
+        let process = pm.get_process("one").unwrap();
+        assert_eq!(process.scroll_offset, 0);
+        assert!(!process.auto_scroll);
+
+        keys(&mut app, &mut pm, "v");
+        assert!(!app.selection.is_active());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn pipe_shows_command_output() {
+        let mut app = App::new(false, true);
+        let mut pm = test_manager(&["one"]);
+        let gen = pm.get_process("one").unwrap().generation;
+        pm.write_output("one", gen, b"hello\r\nworld\r\n");
+
+        // Without a configured command, `|` asks for one and keeps the selection
+        handle_key(press('V'), &mut app, &mut pm, 24, 80);
+        handle_key(press('|'), &mut app, &mut pm, 24, 80);
+        assert_eq!(app.command_prompt.as_deref(), Some("pipe "));
+        assert!(app.selection.is_active());
+        handle_key(KeyEvent::from(KeyCode::Esc), &mut app, &mut pm, 24, 80);
+        handle_key(KeyEvent::from(KeyCode::Esc), &mut app, &mut pm, 24, 80);
+
+        app.pipe_command = Some("tr a-z A-Z".to_string());
+        handle_key(press('|'), &mut app, &mut pm, 24, 80);
+        let start = std::time::Instant::now();
+        while !crate::input::pipe::poll(&mut app) {
+            assert!(start.elapsed() < std::time::Duration::from_secs(5));
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(app.pipe_output.as_ref().unwrap().text, "HELLO\nWORLD");
+
+        // Scrolling stops where the output ends, so k works right away
+        for _ in 0..5 {
+            handle_key(press('j'), &mut app, &mut pm, 24, 80);
+        }
+        assert_eq!(app.pipe_output.as_ref().unwrap().scroll, 0);
+
+        // Any other key closes the output, without acting on it
+        handle_key(press('q'), &mut app, &mut pm, 24, 80);
+        assert!(app.pipe_output.is_none());
+        assert!(!app.shutting_down);
+    }
+
+    #[test]
+    fn export_writes_selection_or_scrollback() {
+        let mut app = App::new(false, true);
+        let mut pm = test_manager(&["one"]);
+        let gen = pm.get_process("one").unwrap().generation;
+        pm.write_output("one", gen, b"hello\r\nworld\r\n");
+        let dir = std::env::temp_dir().join(format!("panex-export-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("one.html");
+
+        // `:` keeps the selection for the command
+        handle_key(press('V'), &mut app, &mut pm, 24, 80);
+        handle_key(press(':'), &mut app, &mut pm, 24, 80);
+        assert!(app.selection.is_active());
+        app.command_prompt = Some(format!("export text {}", path.display()));
+        handle_key(KeyEvent::from(KeyCode::Enter), &mut app, &mut pm, 24, 80);
+        assert!(!app.selection.is_active());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello\n");
+
+        // The whole scrollback, in the format the extension names
+        handle_key(press(':'), &mut app, &mut pm, 24, 80);
+        app.command_prompt = Some(format!("export {}", path.display()));
+        handle_key(KeyEvent::from(KeyCode::Enter), &mut app, &mut pm, 24, 80);
+        let html = std::fs::read_to_string(&path).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<pre>hello\nworld\n</pre>"));
+        assert_eq!(
+            app.active_status(),
+            Some(format!("Exported 2 lines to {}", path.display()).as_str())
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn link_menu_lists_links_and_closes() {
+        let mut app = App::new(false, true);
+        let mut pm = test_manager(&["one"]);
+
+        handle_key(press('u'), &mut app, &mut pm, 24, 80);
+        assert_eq!(app.link_menu, None);
+        assert_eq!(
+            app.active_status(),
+            Some("No URLs or listening ports for one")
+        );
+
+        pm.get_process_mut("one").unwrap().listening = vec![3000, 9229];
+        handle_key(press('u'), &mut app, &mut pm, 24, 80);
+        assert_eq!(app.link_menu, Some(0));
+        handle_key(press('j'), &mut app, &mut pm, 24, 80);
+        handle_key(press('j'), &mut app, &mut pm, 24, 80);
+        assert_eq!(app.link_menu, Some(1));
+        handle_key(KeyEvent::from(KeyCode::Esc), &mut app, &mut pm, 24, 80);
+        assert_eq!(app.link_menu, None);
+    }
+
+    #[test]
+    fn signal_menu_moves_sends_and_cancels() {
+        let mut app = App::new(false, true);
+        let mut pm = test_manager(&["one"]);
+
+        handle_key(press('K'), &mut app, &mut pm, 24, 80);
+        assert_eq!(app.signal_menu, Some(0));
+        handle_key(press('j'), &mut app, &mut pm, 24, 80);
+        handle_key(press('j'), &mut app, &mut pm, 24, 80);
+        handle_key(press('k'), &mut app, &mut pm, 24, 80);
+        assert_eq!(app.signal_menu, Some(1));
+
+        // The process was never started, so sending reports that
+        handle_key(KeyEvent::from(KeyCode::Enter), &mut app, &mut pm, 24, 80);
+        assert_eq!(app.signal_menu, None);
+        assert_eq!(app.active_status(), Some("one is not running"));
+
+        handle_key(press('K'), &mut app, &mut pm, 24, 80);
+        handle_key(press('x'), &mut app, &mut pm, 24, 80);
+        assert_eq!(app.signal_menu, None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn s_starts_a_stopped_process_only() {
+        let mut app = App::new(false, true);
+        let mut pm = test_manager(&["one"]);
+
+        handle_browse_key(press('s'), &mut app, &mut pm, 24, 80);
+        assert!(pm.get_process("one").unwrap().status.is_alive());
+        let gen = pm.get_process("one").unwrap().generation;
+
+        handle_browse_key(press('s'), &mut app, &mut pm, 24, 80);
+        assert_eq!(pm.get_process("one").unwrap().generation, gen);
+        assert_eq!(app.active_status(), Some("one is already running"));
+    }
+
+    fn type_command(app: &mut App, pm: &mut ProcessManager, text: &str) {
+        handle_key(press(':'), app, pm, 24, 80);
+        for c in text.chars() {
+            handle_key(press(c), app, pm, 24, 80);
+        }
+    }
+
+    #[test]
+    fn command_prompt_completes_and_runs_commands() {
+        let mut app = App::new(false, true);
+        let mut pm = test_manager(&["one", "two"]);
+        app.selected_index = 1;
+
+        type_command(&mut app, &mut pm, "ren");
+        handle_key(KeyEvent::from(KeyCode::Tab), &mut app, &mut pm, 24, 80);
+        assert_eq!(app.command_prompt.as_deref(), Some("rename "));
+        for c in "deux".chars() {
+            handle_key(press(c), &mut app, &mut pm, 24, 80);
+        }
+        handle_key(KeyEvent::from(KeyCode::Enter), &mut app, &mut pm, 24, 80);
+        assert_eq!(app.command_prompt, None);
+        assert_eq!(pm.process_names(), ["one", "deux"]);
+
+        // Without a command, edit fills the prompt with the current one
+        type_command(&mut app, &mut pm, "edit");
+        handle_key(KeyEvent::from(KeyCode::Enter), &mut app, &mut pm, 24, 80);
+        assert_eq!(app.command_prompt.as_deref(), Some("edit true"));
+        handle_key(KeyEvent::from(KeyCode::Esc), &mut app, &mut pm, 24, 80);
+
+        type_command(&mut app, &mut pm, "rm one");
+        handle_key(KeyEvent::from(KeyCode::Enter), &mut app, &mut pm, 24, 80);
+        assert_eq!(pm.process_names(), ["deux"]);
+        assert_eq!(app.selected_index, 0);
+
+        type_command(&mut app, &mut pm, "bogus");
+        handle_key(KeyEvent::from(KeyCode::Enter), &mut app, &mut pm, 24, 80);
+        assert_eq!(app.active_status(), Some("unknown command: bogus"));
+    }
+
     #[test]
     fn uppercase_r_restarts_all_processes_in_browse_mode() {
         let mut app = App::new(false, true);
@@ -0,0 +1,454 @@
+use crate::process::buffer::KeyboardModes;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventState, KeyModifiers};
+
+/// Kitty flag: disambiguate escape codes (modified keys and Esc use CSI u)
+const KITTY_DISAMBIGUATE: u16 = 0b1;
+/// Kitty flag: report all keys, including plain text, as escape codes
+const KITTY_REPORT_ALL_KEYS: u16 = 0b1000;
+
+/// Encode a key event as the bytes a terminal would send to the child,
+/// honoring the keyboard modes the child requested.
+pub fn encode_key(key: &KeyEvent, modes: KeyboardModes) -> Option<Vec<u8>> {
+    if modes.kitty_flags & (KITTY_DISAMBIGUATE | KITTY_REPORT_ALL_KEYS) != 0 {
+        if let Some(bytes) = encode_kitty(key, modes) {
+            return Some(bytes);
+        }
+    }
+    if key.state.contains(KeyEventState::KEYPAD) && modes.application_keypad {
+        if let Some(bytes) = encode_application_keypad(key) {
+            return Some(bytes);
+        }
+    }
+    encode_legacy(key, modes)
+}
+
+/// xterm modifier parameter: 1 + Shift(1) + Alt(2) + Ctrl(4) + Super(8)
+fn modifier_param(mods: KeyModifiers) -> u8 {
+    let mut param = 1;
+    if mods.contains(KeyModifiers::SHIFT) {
+        param += 1;
+    }
+    if mods.contains(KeyModifiers::ALT) {
+        param += 2;
+    }
+    if mods.contains(KeyModifiers::CONTROL) {
+        param += 4;
+    }
+    if mods.contains(KeyModifiers::SUPER) {
+        param += 8;
+    }
+    param
+}
+
+/// `CSI 1 ; m X` with modifiers, otherwise `CSI X` (or `SS3 X` if `ss3`)
+fn letter_key(final_byte: char, mods: KeyModifiers, ss3: bool) -> Vec<u8> {
+    let m = modifier_param(mods);
+    if m > 1 {
+        format!("\x1b[1;{m}{final_byte}").into_bytes()
+    } else if ss3 {
+        format!("\x1bO{final_byte}").into_bytes()
+    } else {
+        format!("\x1b[{final_byte}").into_bytes()
+    }
+}
+
+/// `CSI n ; m ~` with modifiers, otherwise `CSI n ~`
+fn tilde_key(n: u8, mods: KeyModifiers) -> Vec<u8> {
+    let m = modifier_param(mods);
+    if m > 1 {
+        format!("\x1b[{n};{m}~").into_bytes()
+    } else {
+        format!("\x1b[{n}~").into_bytes()
+    }
+}
+
+/// xterm modifyOtherKeys form: `CSI 27 ; m ; code ~`
+fn modify_other_key(code: u32, mods: KeyModifiers) -> Vec<u8> {
+    format!("\x1b[27;{};{code}~", modifier_param(mods)).into_bytes()
+}
+
+/// Legacy control byte for Ctrl+char, if the terminal has one
+fn ctrl_byte(c: char) -> Option<u8> {
+    match c {
+        'a'..='z' => Some(c as u8 - b'a' + 1),
+        'A'..='Z' => Some(c as u8 - b'A' + 1),
+        '@' | ' ' | '2' => Some(0x00),
+        '[' | '3' => Some(0x1b),
+        '\\' | '4' => Some(0x1c),
+        ']' | '5' => Some(0x1d),
+        '^' | '6' => Some(0x1e),
+        '_' | '7' | '/' => Some(0x1f),
+        '?' | '8' => Some(0x7f),
+        _ => None,
+    }
+}
+
+fn with_alt_prefix(alt: bool, mut bytes: Vec<u8>) -> Vec<u8> {
+    if alt {
+        bytes.insert(0, 0x1b);
+    }
+    bytes
+}
+
+fn encode_legacy(key: &KeyEvent, modes: KeyboardModes) -> Option<Vec<u8>> {
+    let mods = key.modifiers;
+    let ctrl = mods.contains(KeyModifiers::CONTROL);
+    let alt = mods.contains(KeyModifiers::ALT);
+    let shift = mods.contains(KeyModifiers::SHIFT);
+    let mok = modes.modify_other_keys;
+
+    let bytes = match key.code {
+        KeyCode::Char(c) => {
+            // Shift alone is carried by the character itself
+            let has_mods = ctrl || alt;
+            if mok >= 2 && has_mods {
+                return Some(modify_other_key(c as u32, mods));
+            }
+            if ctrl {
+                match ctrl_byte(c) {
+                    // Ctrl+Shift+letter is indistinguishable from Ctrl+letter in legacy mode
+                    Some(_) if mok >= 1 && shift && c.is_ascii_alphabetic() => {
+                        return Some(modify_other_key(c as u32, mods));
+                    }
+                    Some(b) => with_alt_prefix(alt, vec![b]),
+                    None if mok >= 1 => return Some(modify_other_key(c as u32, mods)),
+                    None => with_alt_prefix(alt, c.to_string().into_bytes()),
+                }
+            } else {
+                with_alt_prefix(alt, c.to_string().into_bytes())
+            }
+        }
+        KeyCode::Enter => {
+            if mok >= 1 && (ctrl || shift) {
+                return Some(modify_other_key(13, mods));
+            }
+            with_alt_prefix(alt, vec![0x0d])
+        }
+        KeyCode::Tab => {
+            if mok >= 1 && (ctrl || alt) {
+                return Some(modify_other_key(9, mods));
+            }
+            if shift {
+                b"\x1b[Z".to_vec()
+            } else {
+                vec![0x09]
+            }
+        }
+        KeyCode::BackTab => {
+            if mok >= 1 && (ctrl || alt) {
+                return Some(modify_other_key(9, mods | KeyModifiers::SHIFT));
+            }
+            b"\x1b[Z".to_vec()
+        }
+        KeyCode::Backspace => {
+            if mok >= 1 && (ctrl || shift) {
+                return Some(modify_other_key(127, mods));
+            }
+            let b = if ctrl { 0x08 } else { 0x7f };
+            with_alt_prefix(alt, vec![b])
+        }
+        KeyCode::Esc => {
+            if mok >= 1 && (ctrl || shift) {
+                return Some(modify_other_key(27, mods));
+            }
+            with_alt_prefix(alt, vec![0x1b])
+        }
+        KeyCode::Up => letter_key('A', mods, modes.application_cursor),
+        KeyCode::Down => letter_key('B', mods, modes.application_cursor),
+        KeyCode::Right => letter_key('C', mods, modes.application_cursor),
+        KeyCode::Left => letter_key('D', mods, modes.application_cursor),
+        KeyCode::Home => letter_key('H', mods, modes.application_cursor),
+        KeyCode::End => letter_key('F', mods, modes.application_cursor),
+        KeyCode::Insert => tilde_key(2, mods),
+        KeyCode::Delete => tilde_key(3, mods),
+        KeyCode::PageUp => tilde_key(5, mods),
+        KeyCode::PageDown => tilde_key(6, mods),
+        KeyCode::F(n @ 1..=4) => {
+            let final_byte = (b'P' + n - 1) as char;
+            letter_key(final_byte, mods, true)
+        }
+        KeyCode::F(n) => tilde_key(function_key_number(n)?, mods),
+        _ => return None,
+    };
+
+    Some(bytes)
+}
+
+/// VT220 numbers for F5–F12 (`CSI n ~`)
+fn function_key_number(n: u8) -> Option<u8> {
+    match n {
+        5 => Some(15),
+        6 => Some(17),
+        7 => Some(18),
+        8 => Some(19),
+        9 => Some(20),
+        10 => Some(21),
+        11 => Some(23),
+        12 => Some(24),
+        _ => None,
+    }
+}
+
+/// DECKPAM sequences for unmodified keypad keys (`ESC O p` for 0, etc.)
+fn encode_application_keypad(key: &KeyEvent) -> Option<Vec<u8>> {
+    if !key.modifiers.difference(KeyModifiers::SHIFT).is_empty() {
+        return None;
+    }
+    let final_byte = match key.code {
+        KeyCode::Char(c @ '0'..='9') => (b'p' + (c as u8 - b'0')) as char,
+        KeyCode::Char('.') => 'n',
+        KeyCode::Char('+') => 'k',
+        KeyCode::Char('-') => 'm',
+        KeyCode::Char('*') => 'j',
+        KeyCode::Char('/') => 'o',
+        KeyCode::Char('=') => 'X',
+        KeyCode::Enter => 'M',
+        _ => return None,
+    };
+    Some(format!("\x1bO{final_byte}").into_bytes())
+}
+
+/// Kitty keypad key codes (KP_0 = 57399 … KP_EQUAL = 57415)
+fn kitty_keypad_code(code: KeyCode) -> Option<u32> {
+    match code {
+        KeyCode::Char(c @ '0'..='9') => Some(57399 + (c as u32 - '0' as u32)),
+        KeyCode::Char('.') => Some(57409),
+        KeyCode::Char('/') => Some(57410),
+        KeyCode::Char('*') => Some(57411),
+        KeyCode::Char('-') => Some(57412),
+        KeyCode::Char('+') => Some(57413),
+        KeyCode::Enter => Some(57414),
+        KeyCode::Char('=') => Some(57415),
+        _ => None,
+    }
+}
+
+/// Kitty keyboard protocol encoding. Returns None for keys that keep their
+/// legacy encoding (unmodified text, arrows without modifiers, etc.).
+fn encode_kitty(key: &KeyEvent, modes: KeyboardModes) -> Option<Vec<u8>> {
+    let mods = key.modifiers;
+    let report_all = modes.kitty_flags & KITTY_REPORT_ALL_KEYS != 0;
+    let m = modifier_param(mods);
+    let csi_u = |code: u32| {
+        if m > 1 {
+            format!("\x1b[{code};{m}u").into_bytes()
+        } else {
+            format!("\x1b[{code}u").into_bytes()
+        }
+    };
+
+    if key.state.contains(KeyEventState::KEYPAD) {
+        if let Some(code) = kitty_keypad_code(key.code) {
+            return Some(csi_u(code));
+        }
+    }
+
+    match key.code {
+        KeyCode::Char(c) => {
+            let text_only = mods.difference(KeyModifiers::SHIFT).is_empty();
+            if text_only && !report_all {
+                return None;
+            }
+            // Report the unshifted key; Shift travels in the modifier parameter
+            let base = c.to_lowercase().next().unwrap_or(c);
+            Some(csi_u(base as u32))
+        }
+        KeyCode::Esc => Some(csi_u(27)),
+        KeyCode::Enter if report_all || m > 1 => Some(csi_u(13)),
+        KeyCode::Tab if report_all || m > 1 => Some(csi_u(9)),
+        KeyCode::BackTab => {
+            Some(format!("\x1b[9;{}u", modifier_param(mods | KeyModifiers::SHIFT)).into_bytes())
+        }
+        KeyCode::Backspace if report_all || m > 1 => Some(csi_u(127)),
+        // F3 uses `CSI 13 ~` because `CSI R` collides with cursor position reports
+        KeyCode::F(3) => Some(tilde_key(13, mods)),
+        KeyCode::F(n @ 1..=4) => Some(letter_key((b'P' + n - 1) as char, mods, false)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, mods: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, mods)
+    }
+
+    fn keypad(code: KeyCode) -> KeyEvent {
+        let mut k = KeyEvent::new(code, KeyModifiers::NONE);
+        k.state = KeyEventState::KEYPAD;
+        k
+    }
+
+    fn legacy() -> KeyboardModes {
+        KeyboardModes::default()
+    }
+
+    #[test]
+    fn plain_arrow_uses_csi() {
+        let bytes = encode_key(&key(KeyCode::Up, KeyModifiers::NONE), legacy());
+        assert_eq!(bytes.unwrap(), b"\x1b[A");
+    }
+
+    #[test]
+    fn arrow_uses_ss3_when_decckm_enabled() {
+        let modes = KeyboardModes {
+            application_cursor: true,
+            ..legacy()
+        };
+        let bytes = encode_key(&key(KeyCode::Left, KeyModifiers::NONE), modes);
+        assert_eq!(bytes.unwrap(), b"\x1bOD");
+    }
+
+    #[test]
+    fn modified_arrow_uses_xterm_parameter() {
+        let ctrl_up = encode_key(&key(KeyCode::Up, KeyModifiers::CONTROL), legacy());
+        assert_eq!(ctrl_up.unwrap(), b"\x1b[1;5A");
+
+        let shift_alt_right = encode_key(
+            &key(KeyCode::Right, KeyModifiers::SHIFT | KeyModifiers::ALT),
+            legacy(),
+        );
+        assert_eq!(shift_alt_right.unwrap(), b"\x1b[1;4C");
+    }
+
+    #[test]
+    fn modified_arrow_ignores_decckm() {
+        let modes = KeyboardModes {
+            application_cursor: true,
+            ..legacy()
+        };
+        let bytes = encode_key(&key(KeyCode::Down, KeyModifiers::CONTROL), modes);
+        assert_eq!(bytes.unwrap(), b"\x1b[1;5B");
+    }
+
+    #[test]
+    fn modified_tilde_and_function_keys() {
+        let ctrl_del = encode_key(&key(KeyCode::Delete, KeyModifiers::CONTROL), legacy());
+        assert_eq!(ctrl_del.unwrap(), b"\x1b[3;5~");
+        let shift_f1 = encode_key(&key(KeyCode::F(1), KeyModifiers::SHIFT), legacy());
+        assert_eq!(shift_f1.unwrap(), b"\x1b[1;2P");
+        let f5 = encode_key(&key(KeyCode::F(5), KeyModifiers::NONE), legacy());
+        assert_eq!(f5.unwrap(), b"\x1b[15~");
+    }
+
+    #[test]
+    fn ctrl_symbols_map_to_control_bytes() {
+        let ctrl_space = encode_key(&key(KeyCode::Char(' '), KeyModifiers::CONTROL), legacy());
+        assert_eq!(ctrl_space.unwrap(), vec![0x00]);
+        let ctrl_bracket = encode_key(&key(KeyCode::Char(']'), KeyModifiers::CONTROL), legacy());
+        assert_eq!(ctrl_bracket.unwrap(), vec![0x1d]);
+    }
+
+    #[test]
+    fn alt_prefixes_utf8_chars_with_escape() {
+        let bytes = encode_key(&key(KeyCode::Char('é'), KeyModifiers::ALT), legacy());
+        assert_eq!(bytes.unwrap(), "\x1bé".as_bytes());
+    }
+
+    #[test]
+    fn ctrl_enter_is_plain_cr_without_modify_other_keys() {
+        let bytes = encode_key(&key(KeyCode::Enter, KeyModifiers::CONTROL), legacy());
+        assert_eq!(bytes.unwrap(), b"\r");
+    }
+
+    #[test]
+    fn modify_other_keys_encodes_ctrl_and_shift_enter() {
+        let modes = KeyboardModes {
+            modify_other_keys: 1,
+            ..legacy()
+        };
+        let ctrl = encode_key(&key(KeyCode::Enter, KeyModifiers::CONTROL), modes);
+        assert_eq!(ctrl.unwrap(), b"\x1b[27;5;13~");
+        let shift = encode_key(&key(KeyCode::Enter, KeyModifiers::SHIFT), modes);
+        assert_eq!(shift.unwrap(), b"\x1b[27;2;13~");
+    }
+
+    #[test]
+    fn modify_other_keys_level_one_keeps_plain_ctrl_letters() {
+        let modes = KeyboardModes {
+            modify_other_keys: 1,
+            ..legacy()
+        };
+        let bytes = encode_key(&key(KeyCode::Char('a'), KeyModifiers::CONTROL), modes);
+        assert_eq!(bytes.unwrap(), vec![0x01]);
+    }
+
+    #[test]
+    fn modify_other_keys_level_two_encodes_all_modified_chars() {
+        let modes = KeyboardModes {
+            modify_other_keys: 2,
+            ..legacy()
+        };
+        let bytes = encode_key(&key(KeyCode::Char('a'), KeyModifiers::CONTROL), modes);
+        assert_eq!(bytes.unwrap(), b"\x1b[27;5;97~");
+    }
+
+    #[test]
+    fn application_keypad_sends_ss3() {
+        let modes = KeyboardModes {
+            application_keypad: true,
+            ..legacy()
+        };
+        assert_eq!(
+            encode_key(&keypad(KeyCode::Char('5')), modes).unwrap(),
+            b"\x1bOu"
+        );
+        assert_eq!(
+            encode_key(&keypad(KeyCode::Enter), modes).unwrap(),
+            b"\x1bOM"
+        );
+        // Without DECKPAM the keypad sends plain characters
+        assert_eq!(
+            encode_key(&keypad(KeyCode::Char('5')), legacy()).unwrap(),
+            b"5"
+        );
+    }
+
+    #[test]
+    fn kitty_disambiguate_encodes_modified_keys_as_csi_u() {
+        let modes = KeyboardModes {
+            kitty_flags: KITTY_DISAMBIGUATE,
+            ..legacy()
+        };
+        let ctrl_enter = encode_key(&key(KeyCode::Enter, KeyModifiers::CONTROL), modes);
+        assert_eq!(ctrl_enter.unwrap(), b"\x1b[13;5u");
+        let ctrl_shift_a = encode_key(
+            &key(
+                KeyCode::Char('A'),
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+            ),
+            modes,
+        );
+        assert_eq!(ctrl_shift_a.unwrap(), b"\x1b[97;6u");
+        let esc = encode_key(&key(KeyCode::Esc, KeyModifiers::NONE), modes);
+        assert_eq!(esc.unwrap(), b"\x1b[27u");
+    }
+
+    #[test]
+    fn kitty_disambiguate_keeps_plain_text_and_arrows() {
+        let modes = KeyboardModes {
+            kitty_flags: KITTY_DISAMBIGUATE,
+            ..legacy()
+        };
+        let a = encode_key(&key(KeyCode::Char('a'), KeyModifiers::NONE), modes);
+        assert_eq!(a.unwrap(), b"a");
+        let enter = encode_key(&key(KeyCode::Enter, KeyModifiers::NONE), modes);
+        assert_eq!(enter.unwrap(), b"\r");
+        let ctrl_up = encode_key(&key(KeyCode::Up, KeyModifiers::CONTROL), modes);
+        assert_eq!(ctrl_up.unwrap(), b"\x1b[1;5A");
+    }
+
+    #[test]
+    fn kitty_report_all_keys_encodes_plain_text() {
+        let modes = KeyboardModes {
+            kitty_flags: KITTY_DISAMBIGUATE | KITTY_REPORT_ALL_KEYS,
+            ..legacy()
+        };
+        let a = encode_key(&key(KeyCode::Char('a'), KeyModifiers::NONE), modes);
+        assert_eq!(a.unwrap(), b"\x1b[97u");
+        let enter = encode_key(&key(KeyCode::Enter, KeyModifiers::NONE), modes);
+        assert_eq!(enter.unwrap(), b"\x1b[13u");
+    }
+}
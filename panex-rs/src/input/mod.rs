@@ -1,6 +1,11 @@
+pub mod browser;
 pub mod clipboard;
+pub mod copy_mode;
+pub mod editor;
 pub mod handler;
+pub mod keyboard;
 pub mod mouse;
+pub mod pipe;
 pub mod selection;
 
 pub use handler::handle_event;
@@ -1,10 +1,14 @@
 use crate::input::clipboard::copy_to_clipboard;
+use crate::input::editor::open_file_ref;
 use crate::input::selection::{
     clamp_pos, expand_to_word, extract_selected_text, screen_to_buffer, screen_to_buffer_wrapped,
     visual_to_buffer, BufferPos, SelectionPhase,
 };
+use crate::process::buffer::{MouseEncoding, MouseModes, MouseTracking};
+use crate::process::links::file_ref_at;
 use crate::process::ProcessManager;
 use crate::ui::app::DragEdge;
+use crate::ui::help_popup::PipePopup;
 use crate::ui::output_panel::{scroll_down, scroll_up};
 use crate::ui::{App, InputMode};
 use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
@@ -24,9 +28,15 @@ fn output_panel_x(panel_cols: u16) -> u16 {
     panel_cols + 1
 }
 
-/// Encode a mouse event as SGR (mode 1006) escape sequence for forwarding to child PTY.
+/// Translate a mouse event to the child's output-panel coordinates and encode it
+/// according to the child's requested mouse modes.
 /// Coordinates are translated so the output panel's top-left is (1,1).
-fn mouse_to_sgr(event: &MouseEvent, visible_height: usize, panel_cols: u16) -> Option<Vec<u8>> {
+fn mouse_to_child(
+    event: &MouseEvent,
+    modes: MouseModes,
+    visible_height: usize,
+    panel_cols: u16,
+) -> Option<Vec<u8>> {
     let opx = output_panel_x(panel_cols);
     // Only forward events in the output panel area
     if event.column < opx || event.row as usize >= visible_height {
@@ -34,25 +44,128 @@ fn mouse_to_sgr(event: &MouseEvent, visible_height: usize, panel_cols: u16) -> O
     }
     let col = event.column - opx + 1; // 1-based
     let row = event.row + 1; // 1-based
+    let cell_size = if modes.encoding == MouseEncoding::SgrPixels {
+        cell_pixel_size()
+    } else {
+        (1, 1)
+    };
+    encode_mouse(event, modes, col, row, cell_size)
+}
+
+/// Pixel size of one terminal cell, falling back to 8x16 when the
+/// terminal doesn't report its pixel dimensions.
+fn cell_pixel_size() -> (u16, u16) {
+    match crossterm::terminal::window_size() {
+        Ok(ws) if ws.width > 0 && ws.height > 0 && ws.columns > 0 && ws.rows > 0 => {
+            (ws.width / ws.columns, ws.height / ws.rows)
+        }
+        _ => (8, 16),
+    }
+}
 
-    let (button, press) = match event.kind {
-        MouseEventKind::Down(MouseButton::Left) => (0, true),
-        MouseEventKind::Down(MouseButton::Middle) => (1, true),
-        MouseEventKind::Down(MouseButton::Right) => (2, true),
-        MouseEventKind::Up(MouseButton::Left) => (0, false),
-        MouseEventKind::Up(MouseButton::Middle) => (1, false),
-        MouseEventKind::Up(MouseButton::Right) => (2, false),
-        MouseEventKind::Drag(MouseButton::Left) => (32, true),
-        MouseEventKind::Drag(MouseButton::Middle) => (33, true),
-        MouseEventKind::Drag(MouseButton::Right) => (34, true),
-        MouseEventKind::ScrollUp => (64, true),
-        MouseEventKind::ScrollDown => (65, true),
-        MouseEventKind::Moved => (35, true),
-        _ => return None,
+/// Encode a mouse event at 1-based cell (`col`, `row`) for a child PTY.
+/// Returns None when the child's tracking mode doesn't include this event,
+/// or the coordinates can't be represented in its encoding.
+fn encode_mouse(
+    event: &MouseEvent,
+    modes: MouseModes,
+    col: u16,
+    row: u16,
+    cell_size: (u16, u16),
+) -> Option<Vec<u8>> {
+    let wanted = match modes.tracking {
+        MouseTracking::Off => false,
+        MouseTracking::X10 => matches!(
+            event.kind,
+            MouseEventKind::Down(_) | MouseEventKind::ScrollUp | MouseEventKind::ScrollDown
+        ),
+        MouseTracking::Normal => {
+            !matches!(event.kind, MouseEventKind::Drag(_) | MouseEventKind::Moved)
+        }
+        MouseTracking::ButtonEvent => !matches!(event.kind, MouseEventKind::Moved),
+        MouseTracking::AnyEvent => true,
+    };
+    if !wanted {
+        return None;
+    }
+
+    let button_code = |b: MouseButton| match b {
+        MouseButton::Left => 0u32,
+        MouseButton::Middle => 1,
+        MouseButton::Right => 2,
+    };
+    let (mut code, release) = match event.kind {
+        MouseEventKind::Down(b) => (button_code(b), false),
+        MouseEventKind::Up(b) => (button_code(b), true),
+        MouseEventKind::Drag(b) => (32 + button_code(b), false),
+        MouseEventKind::Moved => (35, false),
+        MouseEventKind::ScrollUp => (64, false),
+        MouseEventKind::ScrollDown => (65, false),
+        MouseEventKind::ScrollLeft => (66, false),
+        MouseEventKind::ScrollRight => (67, false),
     };
+    // X10 compatibility mode reports no modifiers
+    if modes.tracking != MouseTracking::X10 {
+        if event.modifiers.contains(KeyModifiers::SHIFT) {
+            code += 4;
+        }
+        if event.modifiers.contains(KeyModifiers::ALT) {
+            code += 8;
+        }
+        if event.modifiers.contains(KeyModifiers::CONTROL) {
+            code += 16;
+        }
+    }
+    // Only SGR encodings can say which button was released
+    let legacy_code = if release { (code & !3) | 3 } else { code };
+    let (col, row) = (col as u32, row as u32);
 
-    let suffix = if press { 'M' } else { 'm' };
-    Some(format!("\x1b[<{};{};{}{}", button, col, row, suffix).into_bytes())
+    match modes.encoding {
+        MouseEncoding::Sgr => {
+            let suffix = if release { 'm' } else { 'M' };
+            Some(format!("\x1b[<{};{};{}{}", code, col, row, suffix).into_bytes())
+        }
+        MouseEncoding::SgrPixels => {
+            let suffix = if release { 'm' } else { 'M' };
+            let x = (col - 1) * cell_size.0 as u32 + 1;
+            let y = (row - 1) * cell_size.1 as u32 + 1;
+            Some(format!("\x1b[<{};{};{}{}", code, x, y, suffix).into_bytes())
+        }
+        MouseEncoding::Urxvt => {
+            Some(format!("\x1b[{};{};{}M", legacy_code + 32, col, row).into_bytes())
+        }
+        MouseEncoding::X10 => {
+            let values = [legacy_code + 32, col + 32, row + 32];
+            if values.iter().any(|&v| v > 255) {
+                return None;
+            }
+            let mut out = b"\x1b[M".to_vec();
+            out.extend(values.iter().map(|&v| v as u8));
+            Some(out)
+        }
+        MouseEncoding::Utf8 => {
+            let mut out = String::from("\x1b[M");
+            for v in [legacy_code + 32, col + 32, row + 32] {
+                // xterm caps UTF-8 mouse values at 2047 (two-byte sequences)
+                if v > 2047 {
+                    return None;
+                }
+                out.push(char::from_u32(v)?);
+            }
+            Some(out.into_bytes())
+        }
+    }
+}
+
+/// Arrow key sequence sent for a wheel notch when the child is on the
+/// alternate screen without mouse tracking (xterm's "alternate scroll").
+fn wheel_to_arrow(up: bool, application_cursor: bool) -> &'static [u8] {
+    match (up, application_cursor) {
+        (true, false) => b"\x1b[A",
+        (false, false) => b"\x1b[B",
+        (true, true) => b"\x1bOA",
+        (false, true) => b"\x1bOB",
+    }
 }
 
 pub fn handle_mouse(
@@ -82,6 +195,68 @@ pub fn handle_mouse(
         return;
     }
 
+    // Process tree popup: scroll or close on click
+    if app.show_tree {
+        match event.kind {
+            MouseEventKind::ScrollUp => {
+                app.tree_scroll = app.tree_scroll.saturating_sub(SCROLL_AMOUNT as u16);
+            }
+            MouseEventKind::ScrollDown => {
+                app.tree_scroll = app.tree_scroll.saturating_add(SCROLL_AMOUNT as u16);
+            }
+            MouseEventKind::Down(_) => {
+                app.show_tree = false;
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    // Pipe output: scroll or close on click
+    if let Some(output) = app.pipe_output.as_mut() {
+        match event.kind {
+            MouseEventKind::ScrollUp => {
+                output.scroll = output.scroll.saturating_sub(SCROLL_AMOUNT as u16);
+            }
+            MouseEventKind::ScrollDown => {
+                // The popup covers the status bar row too
+                let max_scroll = PipePopup::max_scroll(output, visible_height as u16 + 1);
+                output.scroll = output
+                    .scroll
+                    .saturating_add(SCROLL_AMOUNT as u16)
+                    .min(max_scroll);
+            }
+            MouseEventKind::Down(_) => {
+                app.pipe_output = None;
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    // Signal menu, links popup and hint mode: a click cancels
+    if app.signal_menu.is_some() || app.link_menu.is_some() || app.hints.is_some() {
+        if let MouseEventKind::Down(_) = event.kind {
+            app.signal_menu = None;
+            app.link_menu = None;
+            app.hints = None;
+        }
+        return;
+    }
+
+    // Quit confirmation waits for a key
+    if app.pending_quit.is_some() {
+        return;
+    }
+
+    // Process detail popup: close on click
+    if app.show_info {
+        if let MouseEventKind::Down(_) = event.kind {
+            app.show_info = false;
+        }
+        return;
+    }
+
     // In Focus mode, forward non-scroll mouse events to the child PTY.
     // Scroll is handled uniformly below (forwarded if alternate screen, else viewport).
     if app.mode == InputMode::Focus
@@ -112,10 +287,9 @@ pub fn handle_mouse(
         let selected_name = pm.process_names().get(app.selected_index).cloned();
         if let Some(name) = selected_name {
             if let Some(process) = pm.get_process(&name) {
-                if process.buffer.wants_mouse() {
-                    if let Some(bytes) = mouse_to_sgr(&event, visible_height, panel_cols) {
-                        let _ = pm.write_to_process(&name, &bytes);
-                    }
+                let modes = process.buffer.mouse_modes();
+                if let Some(bytes) = mouse_to_child(&event, modes, visible_height, panel_cols) {
+                    let _ = pm.write_to_process(&name, &bytes);
                 }
             }
         }
@@ -129,8 +303,17 @@ pub fn handle_mouse(
             if let Some(name) = &selected_name {
                 if let Some(process) = pm.get_process(name) {
                     if process.buffer.is_alternate_screen() {
-                        // TUI app: forward scroll to child PTY
-                        if let Some(bytes) = mouse_to_sgr(&event, visible_height, panel_cols) {
+                        // TUI app: forward scroll to child PTY, as mouse reports if it
+                        // tracks the mouse, otherwise as arrow keys
+                        let bytes = if process.buffer.wants_mouse() {
+                            let modes = process.buffer.mouse_modes();
+                            mouse_to_child(&event, modes, visible_height, panel_cols)
+                        } else {
+                            let up = matches!(event.kind, MouseEventKind::ScrollUp);
+                            let app_cursor = process.buffer.keyboard_modes().application_cursor;
+                            Some(wheel_to_arrow(up, app_cursor).repeat(SCROLL_AMOUNT))
+                        };
+                        if let Some(bytes) = bytes {
                             let _ = pm.write_to_process(name, &bytes);
                         }
                     } else if matches!(event.kind, MouseEventKind::ScrollUp) {
@@ -164,7 +347,8 @@ pub fn handle_mouse(
                 app.selection.clear();
                 app.pending_click = None;
                 if let Some(name) = &selected_name {
-                    if let Some(process) = pm.get_process(name) {
+                    if let Some(process) = pm.get_process_mut(name) {
+                        process.sync_wrap(viewport_width);
                         let pos = if process.wrap_enabled {
                             screen_to_buffer_wrapped(
                                 opx,
@@ -172,7 +356,7 @@ pub fn handle_mouse(
                                 opx,
                                 process.scroll_offset,
                                 process.buffer.get_all_lines(),
-                                viewport_width,
+                                &process.wrap_index,
                             )
                         } else {
                             screen_to_buffer(
@@ -193,8 +377,10 @@ pub fn handle_mouse(
                 app.selection.clear();
 
                 let alt = event.modifiers.contains(KeyModifiers::ALT);
+                let ctrl = event.modifiers.contains(KeyModifiers::CONTROL);
                 if let Some(name) = &selected_name {
-                    if let Some(process) = pm.get_process(name) {
+                    if let Some(process) = pm.get_process_mut(name) {
+                        process.sync_wrap(viewport_width);
                         let raw_pos = if process.wrap_enabled {
                             screen_to_buffer_wrapped(
                                 event.column.max(opx),
@@ -202,7 +388,7 @@ pub fn handle_mouse(
                                 opx,
                                 process.scroll_offset,
                                 process.buffer.get_all_lines(),
-                                viewport_width,
+                                &process.wrap_index,
                             )
                         } else {
                             screen_to_buffer(
@@ -213,6 +399,18 @@ pub fn handle_mouse(
                                 viewport_width,
                             )
                         };
+                        // Ctrl-click on a file:line reference opens it in the editor
+                        if ctrl {
+                            let lines = process.buffer.get_all_lines();
+                            let file_ref = lines
+                                .get(raw_pos.row)
+                                .and_then(|line| file_ref_at(&line.text(), raw_pos.col));
+                            if let Some(file_ref) = file_ref {
+                                app.pending_click = None;
+                                open_file_ref(app, process, &file_ref);
+                                return;
+                            }
+                        }
                         // Box selection keeps raw column; char/line selection clamps
                         let pos = if alt {
                             raw_pos
@@ -313,7 +511,8 @@ pub fn handle_mouse(
                     app.last_drag_row = Some((event.row, Instant::now()));
                 }
 
-                if let Some(process) = pm.get_process(name) {
+                if let Some(process) = pm.get_process_mut(name) {
+                    process.sync_wrap(viewport_width);
                     let last_row = visible_height.saturating_sub(1);
                     let clamped_row = row.min(last_row) as u16;
 
@@ -325,7 +524,7 @@ pub fn handle_mouse(
                                 visual_row,
                                 0,
                                 process.buffer.get_all_lines(),
-                                viewport_width,
+                                &process.wrap_index,
                             );
                             p.col = usize::MAX;
                             p
@@ -341,7 +540,7 @@ pub fn handle_mouse(
                                 visual_row,
                                 0,
                                 process.buffer.get_all_lines(),
-                                viewport_width,
+                                &process.wrap_index,
                             );
                             if p.row > 0 || p.col > 0 {
                                 // Go to end of previous buffer row
@@ -372,7 +571,7 @@ pub fn handle_mouse(
                                 opx,
                                 process.scroll_offset,
                                 process.buffer.get_all_lines(),
-                                viewport_width,
+                                &process.wrap_index,
                             )
                         } else {
                             screen_to_buffer(
@@ -469,14 +668,15 @@ pub fn tick_edge_scroll(
     app.last_edge_scroll = Some(Instant::now());
 
     // Update selection cursor to track the scroll
-    if let Some(process) = pm.get_process(&name) {
+    if let Some(process) = pm.get_process_mut(&name) {
+        process.sync_wrap(viewport_width);
         let pos = if process.wrap_enabled {
             match edge {
                 DragEdge::Top => visual_to_buffer(
                     process.scroll_offset,
                     0,
                     process.buffer.get_all_lines(),
-                    viewport_width,
+                    &process.wrap_index,
                 ),
                 DragEdge::Bottom => {
                     let visual_row = visible_height.saturating_sub(1) + process.scroll_offset;
@@ -484,7 +684,7 @@ pub fn tick_edge_scroll(
                         visual_row,
                         0,
                         process.buffer.get_all_lines(),
-                        viewport_width,
+                        &process.wrap_index,
                     );
                     p.col = usize::MAX;
                     p
@@ -502,3 +702,181 @@ pub fn tick_edge_scroll(
         app.selection.update_mouse_drag(pos);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ev(kind: MouseEventKind, modifiers: KeyModifiers) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column: 0,
+            row: 0,
+            modifiers,
+        }
+    }
+
+    fn modes(tracking: MouseTracking, encoding: MouseEncoding) -> MouseModes {
+        MouseModes { tracking, encoding }
+    }
+
+    fn encode(kind: MouseEventKind, m: MouseModes) -> Option<Vec<u8>> {
+        encode_mouse(&ev(kind, KeyModifiers::NONE), m, 5, 3, (10, 20))
+    }
+
+    #[test]
+    fn off_forwards_nothing() {
+        let m = modes(MouseTracking::Off, MouseEncoding::Sgr);
+        assert_eq!(encode(MouseEventKind::Down(MouseButton::Left), m), None);
+    }
+
+    #[test]
+    fn x10_encoding_press_and_release() {
+        let m = modes(MouseTracking::Normal, MouseEncoding::X10);
+        assert_eq!(
+            encode(MouseEventKind::Down(MouseButton::Left), m),
+            Some(vec![0x1b, b'[', b'M', 32, 37, 35])
+        );
+        // Release doesn't identify the button in legacy encodings
+        assert_eq!(
+            encode(MouseEventKind::Up(MouseButton::Right), m),
+            Some(vec![0x1b, b'[', b'M', 35, 37, 35])
+        );
+    }
+
+    #[test]
+    fn x10_encoding_drops_out_of_range_coordinates() {
+        let m = modes(MouseTracking::Normal, MouseEncoding::X10);
+        let e = ev(MouseEventKind::Down(MouseButton::Left), KeyModifiers::NONE);
+        assert_eq!(encode_mouse(&e, m, 224, 1, (1, 1)), None);
+        assert!(encode_mouse(&e, m, 223, 1, (1, 1)).is_some());
+    }
+
+    #[test]
+    fn utf8_encoding_extends_coordinates() {
+        let m = modes(MouseTracking::Normal, MouseEncoding::Utf8);
+        let e = ev(MouseEventKind::Down(MouseButton::Left), KeyModifiers::NONE);
+        let mut expected = b"\x1b[M ".to_vec();
+        expected.extend("\u{12c}".as_bytes()); // 300 + 32
+        expected.push(33);
+        assert_eq!(encode_mouse(&e, m, 268, 1, (1, 1)), Some(expected));
+    }
+
+    #[test]
+    fn sgr_encoding_keeps_released_button() {
+        let m = modes(MouseTracking::Normal, MouseEncoding::Sgr);
+        assert_eq!(
+            encode(MouseEventKind::Up(MouseButton::Right), m),
+            Some(b"\x1b[<2;5;3m".to_vec())
+        );
+    }
+
+    #[test]
+    fn urxvt_encoding() {
+        let m = modes(MouseTracking::Normal, MouseEncoding::Urxvt);
+        assert_eq!(
+            encode(MouseEventKind::Down(MouseButton::Middle), m),
+            Some(b"\x1b[33;5;3M".to_vec())
+        );
+    }
+
+    #[test]
+    fn sgr_pixels_reports_pixel_coordinates() {
+        let m = modes(MouseTracking::Normal, MouseEncoding::SgrPixels);
+        assert_eq!(
+            encode(MouseEventKind::Down(MouseButton::Left), m),
+            Some(b"\x1b[<0;41;41M".to_vec())
+        );
+    }
+
+    #[test]
+    fn modifiers_add_bits() {
+        let m = modes(MouseTracking::Normal, MouseEncoding::Sgr);
+        let e = ev(
+            MouseEventKind::Down(MouseButton::Left),
+            KeyModifiers::SHIFT | KeyModifiers::ALT | KeyModifiers::CONTROL,
+        );
+        assert_eq!(
+            encode_mouse(&e, m, 1, 1, (1, 1)),
+            Some(b"\x1b[<28;1;1M".to_vec())
+        );
+    }
+
+    #[test]
+    fn x10_mode_reports_presses_without_modifiers() {
+        let m = modes(MouseTracking::X10, MouseEncoding::Sgr);
+        let e = ev(
+            MouseEventKind::Down(MouseButton::Left),
+            KeyModifiers::CONTROL,
+        );
+        assert_eq!(
+            encode_mouse(&e, m, 1, 1, (1, 1)),
+            Some(b"\x1b[<0;1;1M".to_vec())
+        );
+        assert_eq!(encode(MouseEventKind::Up(MouseButton::Left), m), None);
+    }
+
+    #[test]
+    fn tracking_modes_filter_motion() {
+        let drag = MouseEventKind::Drag(MouseButton::Left);
+        let moved = MouseEventKind::Moved;
+        let normal = modes(MouseTracking::Normal, MouseEncoding::Sgr);
+        assert_eq!(encode(drag, normal), None);
+        assert_eq!(encode(moved, normal), None);
+
+        let button = modes(MouseTracking::ButtonEvent, MouseEncoding::Sgr);
+        assert_eq!(encode(drag, button), Some(b"\x1b[<32;5;3M".to_vec()));
+        assert_eq!(encode(moved, button), None);
+
+        let any = modes(MouseTracking::AnyEvent, MouseEncoding::Sgr);
+        assert_eq!(encode(moved, any), Some(b"\x1b[<35;5;3M".to_vec()));
+    }
+
+    #[test]
+    fn wheel_encodes_as_buttons_64_and_65() {
+        let m = modes(MouseTracking::Normal, MouseEncoding::Sgr);
+        assert_eq!(
+            encode(MouseEventKind::ScrollUp, m),
+            Some(b"\x1b[<64;5;3M".to_vec())
+        );
+        assert_eq!(
+            encode(MouseEventKind::ScrollDown, m),
+            Some(b"\x1b[<65;5;3M".to_vec())
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn ctrl_click_on_file_ref_queues_editor() {
+        let (event_tx, _event_rx) = tokio::sync::mpsc::channel(crate::event::EVENT_QUEUE_CAPACITY);
+        let mut pm = ProcessManager::new(event_tx, 59, 24, 500, 10_000, false);
+        pm.add_process(crate::config::ProcessConfig {
+            name: "one".to_string(),
+            command: "true".to_string(),
+            ..Default::default()
+        });
+        let gen = pm.get_process("one").unwrap().generation;
+        pm.write_output("one", gen, b"  --> Cargo.toml:3:5\r\nmissing.rs:1\r\n");
+        let mut app = App::new(false, true);
+        app.editor = Some("edit {file}:{line}:{col}".to_string());
+        let opx = output_panel_x(20);
+        let click = |column: u16, row: u16| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: opx + column,
+            row,
+            modifiers: KeyModifiers::CONTROL,
+        };
+
+        handle_mouse(click(8, 0), &mut app, &mut pm, 24, 59, 20);
+        assert_eq!(app.pending_editor.as_deref(), Some("edit 'Cargo.toml':3:5"));
+
+        // Outside a reference it is a plain click
+        app.pending_editor = None;
+        handle_mouse(click(1, 0), &mut app, &mut pm, 24, 59, 20);
+        assert_eq!(app.pending_editor, None);
+
+        handle_mouse(click(3, 1), &mut app, &mut pm, 24, 59, 20);
+        assert_eq!(app.pending_editor, None);
+        assert_eq!(app.active_status(), Some("No such file: missing.rs"));
+    }
+}
@@ -0,0 +1,298 @@
+use crate::process::pty::shell_invocation;
+use crate::ui::App;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Lines of command output kept for the popup.
+const MAX_OUTPUT_LINES: usize = 10_000;
+
+/// How long a pipe command may run before it is killed.
+const PIPE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long to keep reading after the command exits. A background child
+/// can hold its stdout open indefinitely (`xclip` does).
+const OUTPUT_GRACE: Duration = Duration::from_millis(200);
+
+/// A command the selection or scrollback is being piped to, running on a
+/// thread of its own. Dropping the job kills the command if it still runs.
+pub struct PipeJob {
+    pub command: String,
+    result: Receiver<Result<String, String>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl PipeJob {
+    /// Run `command` through the shell in `cwd`, with `input` on its stdin.
+    pub fn spawn(command: &str, input: String, cwd: Option<&Path>) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let shell_command = command.to_string();
+        let cwd = cwd.map(Path::to_path_buf);
+        let cancelled = Arc::clone(&cancel);
+        thread::spawn(move || {
+            let result = run(
+                &shell_command,
+                input,
+                cwd.as_deref(),
+                PIPE_TIMEOUT,
+                &cancelled,
+            );
+            let _ = tx.send(result);
+        });
+        Self {
+            command: command.to_string(),
+            result: rx,
+            cancel,
+        }
+    }
+
+    /// The command's output once it succeeded, or why it failed; `None`
+    /// while it runs.
+    fn poll(&self) -> Option<Result<String, String>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err("stopped unexpectedly".to_string())),
+        }
+    }
+}
+
+impl Drop for PipeJob {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// What a pipe command printed, shown read-only until dismissed.
+pub struct PipeOutput {
+    pub command: String,
+    pub text: String,
+    pub scroll: u16,
+}
+
+fn run(
+    command: &str,
+    input: String,
+    cwd: Option<&Path>,
+    timeout: Duration,
+    cancel: &AtomicBool,
+) -> Result<String, String> {
+    let (program, args) = shell_invocation(command);
+    let mut cmd = Command::new(program);
+    cmd.args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(cwd) = cwd {
+        cmd.current_dir(cwd);
+    }
+    // A session of its own: no controlling terminal to read while panex
+    // has it in raw mode, and one process group to kill
+    #[cfg(unix)]
+    unsafe {
+        use std::os::unix::process::CommandExt;
+        cmd.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    let mut child = cmd.spawn().map_err(|e| e.to_string())?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    // Written from another thread, so a command that prints before it has
+    // read everything can't deadlock against us
+    thread::spawn(move || {
+        let _ = stdin.write_all(input.as_bytes());
+    });
+    let stdout = read_chunks(child.stdout.take().expect("stdout is piped"));
+    let stderr = read_chunks(child.stderr.take().expect("stderr is piped"));
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            break status;
+        }
+        if cancel.load(Ordering::Relaxed) {
+            kill(&mut child);
+            return Err("cancelled".to_string());
+        }
+        if Instant::now() >= deadline {
+            kill(&mut child);
+            return Err(format!("timed out after {timeout:?}"));
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    let until = Instant::now() + OUTPUT_GRACE;
+    let stdout = drain(&stdout, until);
+    if status.success() {
+        return Ok(clean_output(&String::from_utf8_lossy(&stdout)));
+    }
+    let stderr = String::from_utf8_lossy(&drain(&stderr, until)).into_owned();
+    Err(
+        match stderr.lines().map(str::trim).find(|l| !l.is_empty()) {
+            Some(line) => format!("{status}: {line}"),
+            None => status.to_string(),
+        },
+    )
+}
+
+/// Read `source` to its end on a thread, sending what arrives.
+fn read_chunks(mut source: impl Read + Send + 'static) -> Receiver<Vec<u8>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buf = [0; 8192];
+        while let Ok(n @ 1..) = source.read(&mut buf) {
+            if tx.send(buf[..n].to_vec()).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+/// Everything sent on `chunks` until it closes or `until` passes.
+fn drain(chunks: &Receiver<Vec<u8>>, until: Instant) -> Vec<u8> {
+    let mut out = Vec::new();
+    while let Ok(chunk) = chunks.recv_timeout(until.saturating_duration_since(Instant::now())) {
+        out.extend(chunk);
+    }
+    out
+}
+
+/// Kill the command with everything it started.
+fn kill(child: &mut Child) {
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(-(child.id() as i32), libc::SIGKILL);
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Command output as plain lines: escape sequences and other control
+/// characters dropped, tabs expanded, and at most `MAX_OUTPUT_LINES` lines.
+fn clean_output(text: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.lines() {
+        let mut line = String::with_capacity(raw.len());
+        let mut chars = raw.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\x1b' => {
+                    // CSI runs to its final byte; other escapes are one char
+                    if chars.next_if_eq(&'[').is_some() {
+                        while chars.next().is_some_and(|c| !('@'..='~').contains(&c)) {}
+                    } else {
+                        chars.next();
+                    }
+                }
+                '\t' => {
+                    let width = line.chars().count();
+                    line.extend(std::iter::repeat_n(' ', 8 - width % 8));
+                }
+                c if c.is_control() => {}
+                c => line.push(c),
+            }
+        }
+        lines.push(line);
+    }
+    if lines.len() > MAX_OUTPUT_LINES {
+        let more = lines.len() - MAX_OUTPUT_LINES;
+        lines.truncate(MAX_OUTPUT_LINES);
+        lines.push(format!("… {more} more lines"));
+    }
+    lines.join("\n")
+}
+
+/// Show what a finished pipe command printed, or why it failed. Returns
+/// true if there is something new to draw.
+pub fn poll(app: &mut App) -> bool {
+    let Some(result) = app.pipe_job.as_ref().and_then(PipeJob::poll) else {
+        return false;
+    };
+    let Some(job) = app.pipe_job.take() else {
+        return false;
+    };
+    match result {
+        Ok(text) if text.trim().is_empty() => app.set_status(&format!("Piped to {}", job.command)),
+        Ok(text) => {
+            app.pipe_output = Some(PipeOutput {
+                command: job.command.clone(),
+                text,
+                scroll: 0,
+            })
+        }
+        Err(why) => app.set_status(&format!("{} failed: {why}", job.command)),
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn wait(job: &PipeJob) -> Result<String, String> {
+        let start = Instant::now();
+        loop {
+            if let Some(result) = job.poll() {
+                return result;
+            }
+            assert!(start.elapsed() < Duration::from_secs(5), "pipe timed out");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn output_is_cleaned_for_display() {
+        assert_eq!(
+            clean_output("\x1b[1;32mok\x1b[0m\r\na\tb\x07\n"),
+            "ok\na       b"
+        );
+        let long = "x\n".repeat(MAX_OUTPUT_LINES + 2);
+        assert!(clean_output(&long).ends_with("x\n… 2 more lines"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn pipes_input_and_reports_failures() {
+        let job = PipeJob::spawn("sort -r", "a\nc\nb\n".to_string(), None);
+        assert_eq!(wait(&job), Ok("c\nb\na".to_string()));
+
+        let job = PipeJob::spawn("echo nope >&2; exit 3", String::new(), None);
+        let err = wait(&job).unwrap_err();
+        assert!(err.contains('3') && err.ends_with(": nope"), "{err}");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn background_children_and_hung_commands_dont_block() {
+        // Like xclip: exits while a child keeps stdout open
+        let start = Instant::now();
+        let result = run(
+            "echo done; sleep 5 &",
+            String::new(),
+            None,
+            PIPE_TIMEOUT,
+            &AtomicBool::new(false),
+        );
+        assert_eq!(result, Ok("done".to_string()));
+        assert!(start.elapsed() < Duration::from_secs(2));
+
+        let result = run(
+            "sleep 5",
+            String::new(),
+            None,
+            Duration::from_millis(100),
+            &AtomicBool::new(false),
+        );
+        assert_eq!(result, Err("timed out after 100ms".to_string()));
+    }
+}
@@ -1,3 +1,6 @@
+use crate::process::buffer::LineView;
+use crate::process::WrapIndex;
+use std::ops::Range;
 use std::time::Instant;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -65,6 +68,14 @@ impl SelectionState {
         }
     }
 
+    /// A line-wise selection of the first `line_count` buffer lines.
+    pub fn all_lines(line_count: usize) -> Self {
+        let mut selection = Self::new();
+        selection.start_visual(BufferPos::new(0, 0), true);
+        selection.cursor = BufferPos::new(line_count.saturating_sub(1), 0);
+        selection
+    }
+
     pub fn is_active(&self) -> bool {
         self.phase != SelectionPhase::Idle
     }
@@ -228,41 +239,30 @@ pub fn screen_to_buffer(
 }
 
 /// Map a visual (wrapped) line index + column to a buffer position.
-/// Walks buffer lines, splitting each into ceil(len / viewport_width) visual lines.
+/// Rows past the end of content clamp to the last content line.
 pub fn visual_to_buffer(
     visual_row: usize,
     visual_col: usize,
-    buffer: &std::collections::VecDeque<crate::process::buffer::Line>,
-    viewport_width: usize,
+    buffer: LineView<'_>,
+    wrap: &WrapIndex,
 ) -> BufferPos {
-    if viewport_width == 0 {
-        return BufferPos::new(0, visual_col);
-    }
     let content_count = content_line_count(buffer);
-    let mut visual = 0usize;
-    for (row_idx, line) in buffer.iter().enumerate().take(content_count) {
-        let n_visual = if line.cells.is_empty() {
-            1
-        } else {
-            line.cells.len().div_ceil(viewport_width)
-        };
-        if visual_row < visual + n_visual {
-            let chunk_index = visual_row - visual;
-            let col = chunk_index * viewport_width + visual_col;
+    if visual_row < wrap.row_start(content_count) {
+        let (row_idx, start_col) = wrap.locate(visual_row);
+        if let Some(line) = buffer.get(row_idx) {
             // Clamp column to actual line length
-            let col = if line.cells.is_empty() {
+            let col = if line.is_empty() {
                 0
             } else {
-                col.min(line.cells.len() - 1)
+                (start_col + visual_col).min(line.len() - 1)
             };
             return BufferPos::new(row_idx, col);
         }
-        visual += n_visual;
     }
     // Clamp to last buffer line
     let last = content_count.saturating_sub(1);
-    let col = if last < buffer.len() && !buffer[last].cells.is_empty() {
-        visual_col.min(buffer[last].cells.len() - 1)
+    let col = if last < buffer.len() && !buffer[last].is_empty() {
+        visual_col.min(buffer[last].len() - 1)
     } else {
         0
     };
@@ -275,62 +275,82 @@ pub fn screen_to_buffer_wrapped(
     screen_row: u16,
     panel_x: u16,
     scroll_offset: usize,
-    buffer: &std::collections::VecDeque<crate::process::buffer::Line>,
-    viewport_width: usize,
+    buffer: LineView<'_>,
+    wrap: &WrapIndex,
 ) -> BufferPos {
     let col = screen_col.saturating_sub(panel_x) as usize;
     let visual_row = screen_row as usize + scroll_offset;
-    visual_to_buffer(visual_row, col, buffer, viewport_width)
+    visual_to_buffer(visual_row, col, buffer, wrap)
 }
 
 /// Map a visual row (scroll_offset-based) to the corresponding buffer row.
 /// Useful for edge-scroll and keyboard visual select where column is handled separately.
 pub fn visual_row_to_buffer_row(
     visual_row: usize,
-    buffer: &std::collections::VecDeque<crate::process::buffer::Line>,
-    viewport_width: usize,
+    buffer: LineView<'_>,
+    wrap: &WrapIndex,
 ) -> BufferPos {
-    visual_to_buffer(visual_row, 0, buffer, viewport_width)
+    visual_to_buffer(visual_row, 0, buffer, wrap)
 }
 
 /// Clamp a buffer position so row is within content and col within line cells.
-pub fn clamp_pos(
-    pos: BufferPos,
-    buffer: &std::collections::VecDeque<crate::process::buffer::Line>,
-) -> BufferPos {
+pub fn clamp_pos(pos: BufferPos, buffer: LineView<'_>) -> BufferPos {
     if buffer.is_empty() {
         return BufferPos::new(0, 0);
     }
     let content_count = content_line_count(buffer);
     let row = pos.row.min(content_count.saturating_sub(1));
-    let col = if buffer[row].cells.is_empty() {
+    let col = if buffer[row].is_empty() {
         0
     } else {
-        pos.col.min(buffer[row].cells.len() - 1)
+        pos.col.min(buffer[row].len() - 1)
     };
     BufferPos::new(row, col)
 }
 
 /// Count buffer lines excluding trailing empty ones (mirrors output_panel logic).
-fn content_line_count(buffer: &std::collections::VecDeque<crate::process::buffer::Line>) -> usize {
+fn content_line_count(buffer: LineView<'_>) -> usize {
     let mut count = buffer.len();
-    while count > 0 && buffer[count - 1].cells.is_empty() {
+    while count > 0 && buffer[count - 1].is_empty() {
         count -= 1;
     }
     count.max(1)
 }
 
 /// Extract selected text from buffer
-pub fn extract_selected_text(
+pub fn extract_selected_text(selection: &SelectionState, buffer: LineView<'_>) -> String {
+    let (_, end) = selection.normalized_range();
+    let mut result = String::new();
+
+    for (row, cols) in selected_spans(selection, buffer) {
+        let line = &buffer[row];
+        for col in cols {
+            result.push(line.char_at(col).unwrap_or(' '));
+        }
+
+        // Trim trailing spaces from each line
+        let trimmed = result.trim_end_matches(' ').len();
+        result.truncate(trimmed);
+
+        if row < end.row {
+            result.push('\n');
+        }
+    }
+
+    result
+}
+
+/// The columns the selection covers on each of its rows in the buffer.
+pub fn selected_spans(
     selection: &SelectionState,
-    buffer: &std::collections::VecDeque<crate::process::buffer::Line>,
-) -> String {
+    buffer: LineView<'_>,
+) -> Vec<(usize, Range<usize>)> {
     if !selection.is_active() {
-        return String::new();
+        return Vec::new();
     }
 
     let (start, end) = selection.normalized_range();
-    let mut result = String::new();
+    let mut spans = Vec::new();
 
     for row in start.row..=end.row {
         if row >= buffer.len() {
@@ -344,7 +364,7 @@ pub fn extract_selected_text(
                 let ce = if row == end.row {
                     end.col.saturating_add(1)
                 } else {
-                    line.cells.len()
+                    line.len()
                 };
                 (cs, ce)
             }
@@ -356,50 +376,36 @@ pub fn extract_selected_text(
                 };
                 (min_col, max_col.saturating_add(1))
             }
-            SelectionMode::Line => (0, line.cells.len()),
+            SelectionMode::Line => (0, line.len()),
         };
 
-        let col_end = col_end.min(line.cells.len());
-
-        for col in col_start..col_end {
-            result.push(line.cells[col].c);
-        }
-
-        // Trim trailing spaces from each line
-        let trimmed = result.trim_end_matches(' ').len();
-        result.truncate(trimmed);
-
-        if row < end.row {
-            result.push('\n');
-        }
+        let col_end = col_end.min(line.len());
+        spans.push((row, col_start..col_end.max(col_start)));
     }
 
-    result
+    spans
 }
 
 /// Expand a position to word boundaries
-pub fn expand_to_word(
-    pos: BufferPos,
-    buffer: &std::collections::VecDeque<crate::process::buffer::Line>,
-) -> (BufferPos, BufferPos) {
+pub fn expand_to_word(pos: BufferPos, buffer: LineView<'_>) -> (BufferPos, BufferPos) {
     if pos.row >= buffer.len() {
         return (pos, pos);
     }
     let line = &buffer[pos.row];
-    if pos.col >= line.cells.len() {
+    if pos.col >= line.len() {
         return (pos, pos);
     }
 
     let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
-    let ch = line.cells[pos.col].c;
+    let ch = line.char_at(pos.col).unwrap_or(' ');
 
     if is_word_char(ch) {
         let mut start = pos.col;
-        while start > 0 && is_word_char(line.cells[start - 1].c) {
+        while start > 0 && is_word_char(line.char_at(start - 1).unwrap_or(' ')) {
             start -= 1;
         }
         let mut end = pos.col;
-        while end + 1 < line.cells.len() && is_word_char(line.cells[end + 1].c) {
+        while end + 1 < line.len() && is_word_char(line.char_at(end + 1).unwrap_or(' ')) {
             end += 1;
         }
         (BufferPos::new(pos.row, start), BufferPos::new(pos.row, end))
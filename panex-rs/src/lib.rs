@@ -1,10 +1,15 @@
 /// Library crate for integration testing.
-/// Exposes buffer and config modules; the full app is in main.rs.
+/// Exposes buffer, grid, line, config, dotenv, procfile and scripts modules; the full app is in main.rs.
 pub mod process {
     pub mod buffer;
+    pub mod grid;
+    pub mod line;
 }
 
 pub mod config;
+pub mod dotenv;
+pub mod procfile;
+pub mod scripts;
 
 /// Search types and logic for testing.
 /// Uses `include!` to share the source with the binary crate's `ui::search`.
@@ -1,19 +1,29 @@
 mod config;
+mod dotenv;
 mod event;
 mod input;
 mod process;
+mod procfile;
+mod scripts;
 mod ui;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
 use config::PanexConfig;
 use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture, Event, EventStream},
+    cursor::SetCursorStyle,
+    event::{
+        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, EventStream, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags,
+        PushKeyboardEnhancementFlags,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use event::AppEvent;
+use event::{AppEvent, EVENT_QUEUE_CAPACITY};
 use futures::StreamExt;
+use process::buffer::CursorShape;
+use process::export::{export, file_stem, ExportFormat};
 use process::ProcessManager;
 use ratatui::{
     backend::CrosstermBackend,
@@ -21,17 +31,27 @@ use ratatui::{
     widgets::Block,
     Terminal,
 };
+use std::collections::HashSet;
 use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
 const RESIZE_DEBOUNCE: Duration = Duration::from_millis(50);
+/// Wake-up interval when idle, so quit/shutdown/lifecycle checks still run.
+const IDLE_TICK: Duration = Duration::from_millis(100);
+/// Longest time spent draining queued process events before yielding to input.
+const DRAIN_BUDGET: Duration = Duration::from_millis(8);
 use ui::{
-    help_popup::{HelpPopup, RestartPopup, ShutdownPopup},
-    output_panel::OutputPanel,
+    help_popup::{
+        EscapedQuitPopup, HelpPopup, LinksPopup, PastePopup, PipePopup, ProcessInfoPopup,
+        ProcessTreePopup, RestartPopup, ShutdownPopup, SignalPopup,
+    },
+    hints::HintPatterns,
+    output_panel::{cursor_screen_position, OutputPanel},
     process_list::ProcessList,
     status_bar::StatusBar,
-    App,
+    App, InputMode,
 };
 
 #[derive(Parser, Debug)]
@@ -40,9 +60,34 @@ use ui::{
 #[command(version)]
 struct Cli {
     /// Commands to run
-    #[arg(required = true)]
     commands: Vec<String>,
 
+    /// Config file with `[processes.<name>]` tables. Without commands,
+    /// ./panex.toml is used if it exists.
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+
+    /// Run the `name: command` entries of a Procfile (default: ./Procfile),
+    /// with .env from its directory and a PORT for each process
+    #[arg(long, num_args = 0..=1, default_missing_value = procfile::DEFAULT_PROCFILE)]
+    procfile: Option<PathBuf>,
+
+    /// Procfile instances per process, e.g. web=2,worker=1 (all=N for the rest)
+    #[arg(short = 'f', long, requires = "procfile", value_parser = procfile::parse_formation)]
+    formation: Option<procfile::Formation>,
+
+    /// Run the package.json scripts matching a glob, e.g. 'dev:*'
+    #[arg(long, value_name = "GLOB")]
+    npm: Option<String>,
+
+    /// Run package.json scripts by name (comma-separated, globs allowed)
+    #[arg(long, value_name = "NAMES", value_delimiter = ',')]
+    scripts: Vec<String>,
+
+    /// Run a script in every npm workspace package that has it
+    #[arg(long, value_name = "SCRIPT")]
+    workspace_scripts: Option<String>,
+
     /// Process names (comma-separated)
     #[arg(short, long)]
     names: Option<String>,
@@ -71,18 +116,59 @@ struct Cli {
     /// Preserve scrollback and show a restart marker instead of clearing output.
     #[arg(long)]
     show_restart_marker: bool,
+
+    /// Cap total scrollback memory across all processes (e.g. 256M, 1G).
+    /// When exceeded, the oldest lines of the largest buffers are dropped first.
+    #[arg(long, value_parser = config::parse_memory_limit)]
+    memory_limit: Option<usize>,
+
+    /// Read a .env file into every process's environment (repeatable)
+    #[arg(long, value_name = "PATH")]
+    env_file: Vec<PathBuf>,
+
+    /// Command that opens file:line references on Ctrl-click, e.g.
+    /// 'code -g {file}:{line}:{col}' (default: from $VISUAL or $EDITOR)
+    #[arg(long, value_name = "TEMPLATE")]
+    editor: Option<String>,
+
+    /// Shell command that `|` pipes the selection or scrollback to,
+    /// e.g. 'jq .'
+    #[arg(long, value_name = "COMMAND")]
+    pipe_command: Option<String>,
+
+    /// On quit, write every process's scrollback to DIR as NAME.txt (or
+    /// .ansi/.html with --dump-format)
+    #[arg(long, value_name = "DIR")]
+    dump_on_exit: Option<PathBuf>,
+
+    /// Format for --dump-on-exit: text, ansi or html
+    #[arg(long, default_value = "text", value_parser = ExportFormat::parse)]
+    dump_format: ExportFormat,
+
+    /// Print the resolved processes and their environment, with
+    /// secret-looking values masked, and exit
+    #[arg(long)]
+    print_config: bool,
+
+    /// Maximum redraws per second (1–240). Output arriving faster is batched per frame.
+    #[arg(long, default_value_t = config::DEFAULT_FPS, value_parser = clap::value_parser!(u16).range(1..=240))]
+    fps: u16,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
-    if cli.commands.is_empty() {
-        eprintln!("Error: At least one command is required");
-        std::process::exit(1);
-    }
-
-    let config = PanexConfig::from_args(
+    let config_file = cli.config.or_else(|| {
+        let default = Path::new(config::DEFAULT_CONFIG_FILE);
+        let implicit = cli.commands.is_empty()
+            && cli.procfile.is_none()
+            && cli.npm.is_none()
+            && cli.scripts.is_empty()
+            && cli.workspace_scripts.is_none();
+        (implicit && default.exists()).then(|| default.to_path_buf())
+    });
+    let mut config = PanexConfig::from_args(
         cli.commands,
         cli.names,
         cli.no_shift_tab,
@@ -91,23 +177,101 @@ async fn main() -> Result<()> {
         cli.panel_width,
         cli.show_restart_marker,
     );
+    let patterns: Vec<String> = cli.npm.into_iter().chain(cli.scripts).collect();
+    if let Some(ref script) = cli.workspace_scripts {
+        if let Err(e) = config.load_workspace_scripts(script) {
+            eprintln!("Error: {e:#}");
+            std::process::exit(1);
+        }
+    }
+    if !patterns.is_empty() {
+        if let Err(e) = config.load_scripts(&patterns) {
+            eprintln!("Error: {e:#}");
+            std::process::exit(1);
+        }
+    }
+    if let Some(path) = cli.procfile {
+        let formation = cli.formation.unwrap_or_default();
+        if let Err(e) = config.load_procfile(&path, &formation) {
+            eprintln!("Error: {e:#}");
+            std::process::exit(1);
+        }
+    }
+    if let Some(path) = config_file {
+        if let Err(e) = config.load_file(&path) {
+            eprintln!("Error: {e:#}");
+            std::process::exit(1);
+        }
+    }
+    if config.processes.is_empty() {
+        eprintln!(
+            "Error: At least one command is required (or a {})",
+            config::DEFAULT_CONFIG_FILE
+        );
+        std::process::exit(1);
+    }
+    config.env_files.extend(cli.env_file);
+    if cli.editor.is_some() {
+        config.editor = cli.editor;
+    }
+    if cli.pipe_command.is_some() {
+        config.pipe_command = cli.pipe_command;
+    }
+    config.memory_limit = cli.memory_limit;
+    config.fps = cli.fps;
+    if cli.print_config {
+        match config.format_resolved() {
+            Ok(text) => print!("{text}"),
+            Err(e) => {
+                eprintln!("Error: {e:#}");
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
     let auto_copy = !cli.no_auto_copy;
+    let dump = cli.dump_on_exit.map(|dir| (dir, cli.dump_format));
 
-    run(config, auto_copy).await
+    run(config, auto_copy, dump).await
 }
 
-async fn run(config: PanexConfig, auto_copy: bool) -> Result<()> {
+async fn run(
+    config: PanexConfig,
+    auto_copy: bool,
+    dump: Option<(PathBuf, ExportFormat)>,
+) -> Result<()> {
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
+    // Ask the host terminal for unambiguous key reports (Ctrl-Enter, keypad, …)
+    // so chords can be forwarded to children that opt into richer encodings.
+    let keyboard_enhanced = crossterm::terminal::supports_keyboard_enhancement().unwrap_or(false);
+    if keyboard_enhanced {
+        execute!(
+            stdout,
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+        )?;
+    }
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let result = run_app(&mut terminal, config, auto_copy).await;
+    let result = run_app(&mut terminal, config, auto_copy, keyboard_enhanced, dump).await;
 
     // Disable mouse capture first to stop new mouse events
-    execute!(terminal.backend_mut(), DisableMouseCapture)?;
+    execute!(
+        terminal.backend_mut(),
+        DisableMouseCapture,
+        DisableBracketedPaste
+    )?;
+    if keyboard_enhanced {
+        execute!(terminal.backend_mut(), PopKeyboardEnhancementFlags)?;
+    }
 
     // Drain any pending input events to prevent leakage
     while crossterm::event::poll(std::time::Duration::from_millis(10))? {
@@ -118,6 +282,7 @@ async fn run(config: PanexConfig, auto_copy: bool) -> Result<()> {
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
+        SetCursorStyle::DefaultUserShape,
         LeaveAlternateScreen,
         crossterm::style::ResetColor,
         crossterm::style::SetAttribute(crossterm::style::Attribute::Reset),
@@ -132,8 +297,10 @@ async fn run_app(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     config: PanexConfig,
     auto_copy: bool,
+    keyboard_enhanced: bool,
+    dump: Option<(PathBuf, ExportFormat)>,
 ) -> Result<()> {
-    let (event_tx, mut event_rx) = mpsc::unbounded_channel::<AppEvent>();
+    let (event_tx, mut event_rx) = mpsc::channel::<AppEvent>(EVENT_QUEUE_CAPACITY);
 
     let size = terminal.size()?;
     let panel_cols = config.compute_panel_columns(size.width);
@@ -148,6 +315,11 @@ async fn run_app(
         config.buffer_size,
         config.show_restart_marker,
     );
+    pm.set_memory_limit(config.memory_limit);
+    pm.set_env_files(config.env_files.clone());
+
+    // Daemonizing descendants are re-parented to us, not init, so they can be tracked
+    process::tree::become_subreaper();
 
     // Add processes
     for proc_config in &config.processes {
@@ -158,11 +330,19 @@ async fn run_app(
     pm.start_all()?;
 
     let mut app = App::new(config.no_shift_tab, auto_copy);
+    app.config_path = config.config_path.clone();
+    app.editor = config.editor.clone();
+    app.pipe_command = config.pipe_command.clone();
+    app.hint_patterns = HintPatterns::new(&config.hint_patterns)?;
     let mut event_stream = EventStream::new();
     let mut last_size: Option<(u16, u16)> = Some((size.width, size.height));
     let mut pending_resize: Option<(u16, u16)> = None;
     let mut resize_deadline: Option<Instant> = None;
     let mut last_selected: usize = 0;
+    let mut cursor_shape = CursorShape::Default;
+    let frame_interval = config.frame_interval();
+    let mut needs_draw = true;
+    let mut last_draw: Option<Instant> = None;
 
     loop {
         // Nudge resize when selected process changes (triggers SIGWINCH)
@@ -173,76 +353,189 @@ async fn run_app(
             }
         }
 
-        // Draw
-        terminal.draw(|f| {
-            let size = f.area();
-
-            let main_chunks =
-                Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).split(size);
-
-            let panel_cols = config.compute_panel_columns(size.width);
-            let content_chunks = Layout::horizontal([
-                Constraint::Length(panel_cols),
-                Constraint::Length(1), // delimiter
-                Constraint::Min(0),
-            ])
-            .split(main_chunks[0]);
-
-            // Process list
-            let process_list = ProcessList::new(&pm, app.selected_index);
-            f.render_widget(process_list, content_chunks[0]);
-
-            // Delimiter (clear the column so no artifacts bleed through)
-            f.render_widget(Block::default(), content_chunks[1]);
-
-            // Output panel
-            let selected_name = pm.process_names().get(app.selected_index).cloned();
-            let selected_process = selected_name.as_ref().and_then(|n| pm.get_process(n));
-            let output_panel =
-                OutputPanel::new(selected_process, app.mode, &app.selection, &app.search);
-            f.render_widget(output_panel, content_chunks[2]);
-
-            // Status bar
-            let proc_no_shift_tab = selected_process
-                .map(|p| p.config.no_shift_tab)
-                .unwrap_or(false);
-            let status_bar = StatusBar::new(
-                app.mode,
-                app.no_shift_tab,
-                proc_no_shift_tab,
-                app.active_status(),
-                &app.search,
-            );
-            f.render_widget(status_bar, main_chunks[1]);
-
-            // Help popup
-            if app.show_help {
-                f.render_widget(HelpPopup::new(app.help_scroll), size);
+        // Draw at most once per frame interval, and only when something changed
+        let drew = needs_draw && last_draw.is_none_or(|t| t.elapsed() >= frame_interval);
+        if drew {
+            // Rendering only borrows the wrap index, so catch it up to any
+            // writes made outside flush_output (hooks, key handling) first
+            let size = terminal.size()?;
+            let output_width = size
+                .width
+                .saturating_sub(config.compute_panel_columns(size.width) + 1);
+            if let Some(process) = pm
+                .process_names()
+                .get(app.selected_index)
+                .cloned()
+                .and_then(|name| pm.get_process_mut(&name))
+            {
+                process.sync_wrap(output_width as usize);
             }
 
-            // Shutdown popup
-            if app.shutting_down {
-                let stopped = pm.stopped_count();
-                let total = pm.process_count();
-                let remaining_ms = app
-                    .shutdown_start
-                    .map(|start| {
-                        let elapsed = start.elapsed().as_millis() as u64;
-                        config.timeout.saturating_sub(elapsed)
-                    })
-                    .unwrap_or(config.timeout);
-                f.render_widget(ShutdownPopup::new(stopped, total, remaining_ms), size);
+            let mut wanted_shape = CursorShape::Default;
+            terminal.draw(|f| {
+                let size = f.area();
+
+                let main_chunks =
+                    Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).split(size);
+
+                let panel_cols = config.compute_panel_columns(size.width);
+                let content_chunks = Layout::horizontal([
+                    Constraint::Length(panel_cols),
+                    Constraint::Length(1), // delimiter
+                    Constraint::Min(0),
+                ])
+                .split(main_chunks[0]);
+
+                // Process list
+                let process_list = ProcessList::new(&pm, app.selected_index, app.show_metrics);
+                f.render_widget(process_list, content_chunks[0]);
+
+                // Delimiter (clear the column so no artifacts bleed through)
+                f.render_widget(Block::default(), content_chunks[1]);
+
+                // Output panel
+                let selected_name = pm.process_names().get(app.selected_index).cloned();
+                let selected_process = selected_name.as_ref().and_then(|n| pm.get_process(n));
+                let output_panel = OutputPanel::new(
+                    selected_process,
+                    app.mode,
+                    &app.selection,
+                    &app.search,
+                    app.hints.as_ref(),
+                );
+                f.render_widget(output_panel, content_chunks[2]);
+
+                // Status bar
+                let proc_no_shift_tab = selected_process
+                    .map(|p| p.config.no_shift_tab)
+                    .unwrap_or(false);
+                let status_bar = StatusBar::new(
+                    app.mode,
+                    app.no_shift_tab,
+                    proc_no_shift_tab,
+                    app.active_status(),
+                    &app.search,
+                    app.command_prompt.as_deref(),
+                    app.hints.as_ref(),
+                );
+                f.render_widget(status_bar, main_chunks[1]);
+
+                // Process detail popup
+                if app.show_info {
+                    if let Some(process) = selected_process {
+                        f.render_widget(ProcessInfoPopup::new(process), size);
+                    }
+                }
+
+                // Process tree popup
+                if app.show_tree {
+                    f.render_widget(ProcessTreePopup::new(&pm, app.tree_scroll), size);
+                }
+
+                // Signal menu
+                if let (Some(selected), Some(process)) = (app.signal_menu, selected_process) {
+                    f.render_widget(SignalPopup::new(&process.config.name, selected), size);
+                }
+
+                // Links popup
+                if let (Some(selected), Some(process)) = (app.link_menu, selected_process) {
+                    let links = process.quick_links();
+                    f.render_widget(
+                        LinksPopup::new(&process.config.name, &links, selected),
+                        size,
+                    );
+                }
+
+                // Output of a pipe command
+                if let Some(ref output) = app.pipe_output {
+                    f.render_widget(PipePopup::new(output), size);
+                }
+
+                // Quit confirmation for escaped descendants
+                if let Some(ref escaped) = app.pending_quit {
+                    f.render_widget(EscapedQuitPopup::new(escaped), size);
+                }
+
+                // Help popup
+                if app.show_help {
+                    f.render_widget(HelpPopup::new(app.help_scroll), size);
+                }
+
+                // Shutdown popup
+                if app.shutting_down {
+                    let stopped = pm.stopped_count();
+                    let total = pm.process_count();
+                    let remaining_ms = app
+                        .shutdown_start
+                        .map(|start| {
+                            let elapsed = start.elapsed().as_millis() as u64;
+                            config.timeout.saturating_sub(elapsed)
+                        })
+                        .unwrap_or(config.timeout);
+                    f.render_widget(ShutdownPopup::new(stopped, total, remaining_ms), size);
+                }
+
+                // Restart popup
+                if let Some((ref action, _)) = app.restarting {
+                    f.render_widget(RestartPopup::new(action), size);
+                }
+
+                // Paste confirmation popup
+                if let Some(ref paste) = app.pending_paste {
+                    f.render_widget(PastePopup::new(paste), size);
+                }
+
+                // Show the focused child's cursor (hidden by ratatui otherwise)
+                let popup_open = app.show_help
+                    || app.show_info
+                    || app.show_tree
+                    || app.signal_menu.is_some()
+                    || app.link_menu.is_some()
+                    || app.pipe_output.is_some()
+                    || app.pending_quit.is_some()
+                    || app.shutting_down
+                    || app.restarting.is_some()
+                    || app.pending_paste.is_some();
+                if app.mode == InputMode::Focus && !popup_open {
+                    if let Some(process) = selected_process {
+                        if let Some((x, y)) = cursor_screen_position(process, content_chunks[2]) {
+                            f.set_cursor_position((x, y));
+                            wanted_shape = process.buffer.cursor_shape();
+                        }
+                    }
+                }
+            })?;
+
+            // Forward DECSCUSR shape changes to the host terminal
+            if wanted_shape != cursor_shape {
+                cursor_shape = wanted_shape;
+                execute!(terminal.backend_mut(), cursor_style(cursor_shape))?;
             }
+            needs_draw = false;
+            last_draw = Some(Instant::now());
+        }
 
-            // Restart popup
-            if let Some((ref action, _)) = app.restarting {
-                f.render_widget(RestartPopup::new(action), size);
+        // Hand the terminal to an editor opened from a file reference
+        if let Some(command) = app.pending_editor.take() {
+            // A fresh stream stops the old one's reader, so the editor gets every key
+            event_stream = EventStream::new();
+            match run_editor(terminal, keyboard_enhanced, &command) {
+                Ok(status) if !status.success() => app.set_status(&format!("Editor: {status}")),
+                Ok(_) => {}
+                Err(e) => app.set_status(&format!("Editor failed: {e}")),
             }
-        })?;
+            cursor_shape = CursorShape::Default;
+            needs_draw = true;
+        }
+
+        // Reap exited children, escalate overdue stops, start pending restarts
+        needs_draw |= pm.tick();
 
         // Handle restart phases
         if let Some((ref action, ref phase)) = app.restarting.clone() {
             match phase {
+                // Wait until the popup has actually been drawn
+                ui::app::RestartPhase::Pending if !drew => {}
                 ui::app::RestartPhase::Pending => {
                     // Popup was just rendered — now execute the restart
                     match action {
@@ -262,6 +555,7 @@ async fn run_app(
                 ui::app::RestartPhase::Active(start) => {
                     if start.elapsed() >= Duration::from_millis(500) {
                         app.restarting = None;
+                        needs_draw = true;
                     }
                 }
             }
@@ -269,6 +563,8 @@ async fn run_app(
 
         // Handle shutdown progression
         if app.shutting_down {
+            // The popup shows a live countdown
+            needs_draw = true;
             if let Some(start) = app.shutdown_start {
                 let elapsed = start.elapsed().as_millis() as u64;
                 if !pm.any_running() {
@@ -289,6 +585,28 @@ async fn run_app(
             break;
         }
 
+        needs_draw |= app.expire_status();
+
+        // Sample CPU/memory while the columns or the detail popup are visible
+        if app.show_metrics || app.show_info {
+            let detail = app
+                .show_info
+                .then(|| pm.process_names().get(app.selected_index).cloned())
+                .flatten();
+            needs_draw |= pm.sample_metrics(detail.as_deref());
+        }
+
+        // Show what a pipe command printed, or why it failed
+        needs_draw |= input::pipe::poll(&mut app);
+
+        // Keep the port badges and links popup current
+        needs_draw |= pm.scan_ports();
+
+        // Keep descendant trees current so orphans stay attributed
+        if pm.track_tree() && app.show_tree {
+            needs_draw = true;
+        }
+
         let term_size = terminal.size()?;
         let current_size = (term_size.width, term_size.height);
 
@@ -313,6 +631,7 @@ async fn run_app(
                 );
                 pending_resize = None;
                 resize_deadline = None;
+                needs_draw = true;
             }
         }
 
@@ -321,13 +640,26 @@ async fn run_app(
 
         // Edge-scroll during drag selection (runs every iteration)
         input::mouse::tick_edge_scroll(&mut app, &mut pm, visible_height, viewport_width);
+        if app.drag_edge.is_some() {
+            needs_draw = true;
+        }
+
+        // Wake for the next frame if one is owed, otherwise idle
+        let wait = if needs_draw {
+            last_draw.map_or(Duration::ZERO, |t| {
+                frame_interval.saturating_sub(t.elapsed())
+            })
+        } else {
+            IDLE_TICK
+        };
 
         // Handle events - biased ensures terminal events (quit) are checked first
         tokio::select! {
             biased;
 
             Some(Ok(event)) = event_stream.next() => {
-                if let Event::Key(_) | Event::Mouse(_) | Event::Resize(_, _) = event {
+                if let Event::Key(_) | Event::Mouse(_) | Event::Resize(_, _) | Event::Paste(_) = event {
+                    needs_draw = true;
                     if let Some((cols, rows)) = input::handle_event(event, &mut app, &mut pm, visible_height, viewport_width, panel_cols) {
                         // Schedule debounced resize (only push deadline if size changed)
                         let new_size = Some((cols, rows));
@@ -338,40 +670,143 @@ async fn run_app(
                     }
                 }
             }
-            Some(event) = event_rx.recv() => {
-                match event {
-                    AppEvent::ProcessOutput(name, gen, data) => {
-                        pm.handle_output(&name, gen, &data);
-                    }
-                    AppEvent::ProcessStarted(_name) => {
-                        // Could show notification
-                    }
-                    AppEvent::ProcessExited(name, gen, code) => {
-                        pm.handle_exit(&name, gen, code);
-                    }
-                    AppEvent::ProcessError(name, gen, error) => {
-                        pm.handle_error(&name, gen, &error);
-                    }
-                    AppEvent::Input(e) => {
-                        if let Some((cols, rows)) = input::handle_event(e, &mut app, &mut pm, visible_height, viewport_width, panel_cols) {
-                            let new_size = Some((cols, rows));
-                            if pending_resize != new_size {
-                                pending_resize = new_size;
-                                resize_deadline = Some(Instant::now() + RESIZE_DEBOUNCE);
+            Some(first) = event_rx.recv() => {
+                // Drain whatever else is queued so a burst costs one flush and one frame
+                let selected = pm.process_names().get(app.selected_index).cloned();
+                let drain_until = Instant::now() + DRAIN_BUDGET;
+                let mut next = Some(first);
+                while let Some(event) = next {
+                    match event {
+                        AppEvent::ProcessOutput(name, gen, data) => {
+                            let list_changed = pm.write_output(&name, gen, &data);
+                            let name = pm.current_name(&name, gen);
+                            needs_draw |= list_changed || selected.as_deref() == Some(name);
+                        }
+                        AppEvent::ProcessStarted(name, gen) => {
+                            pm.handle_started(&name, gen);
+                            needs_draw = true;
+                        }
+                        AppEvent::ProcessExited(name, gen, code) => {
+                            pm.handle_exit(&name, gen, code);
+                            needs_draw = true;
+                        }
+                        AppEvent::ProcessError(name, gen, error) => {
+                            pm.handle_error(&name, gen, &error);
+                            needs_draw = true;
+                        }
+                        AppEvent::Input(e) => {
+                            needs_draw = true;
+                            if let Some((cols, rows)) = input::handle_event(e, &mut app, &mut pm, visible_height, viewport_width, panel_cols) {
+                                let new_size = Some((cols, rows));
+                                if pending_resize != new_size {
+                                    pending_resize = new_size;
+                                    resize_deadline = Some(Instant::now() + RESIZE_DEBOUNCE);
+                                }
                             }
                         }
+                        AppEvent::Tick => {}
                     }
-                    AppEvent::Tick => {}
+                    next = if Instant::now() < drain_until {
+                        event_rx.try_recv().ok()
+                    } else {
+                        None
+                    };
                 }
+                pm.flush_output();
             }
-            // Periodic timeout to ensure quit/redraw runs even when blocked
-            _ = tokio::time::sleep(Duration::from_millis(100)) => {}
+            // Next frame, or a periodic wake-up so quit/shutdown checks still run
+            _ = tokio::time::sleep(wait) => {}
         }
     }
 
+    if let Some((dir, format)) = dump {
+        pm.flush_output();
+        dump_buffers(&pm, &dir, format)?;
+    }
+
     Ok(())
 }
 
+/// Write every process's whole scrollback to `dir`, one file each.
+fn dump_buffers(pm: &ProcessManager, dir: &Path, format: ExportFormat) -> Result<()> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let mut taken = HashSet::new();
+    for name in pm.process_names() {
+        let Some(process) = pm.get_process(name) else {
+            continue;
+        };
+        let lines = process.buffer.get_all_lines();
+        let spans: Vec<_> = (0..process.buffer.content_line_count())
+            .map(|row| (row, 0..lines.get(row).map_or(0, |line| line.len())))
+            .collect();
+        // `api/v1` and `api_v1` share a stem: number the later ones
+        let stem = file_stem(name);
+        let mut file = format!("{stem}.{}", format.extension());
+        let mut n = 1;
+        while !taken.insert(file.clone()) {
+            n += 1;
+            file = format!("{stem}-{n}.{}", format.extension());
+        }
+        let path = dir.join(file);
+        std::fs::write(&path, export(lines, &spans, format, name))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Leave the TUI, run an editor `command` in the foreground, then take the
+/// terminal back.
+fn run_editor(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    keyboard_enhanced: bool,
+    command: &str,
+) -> io::Result<std::process::ExitStatus> {
+    if keyboard_enhanced {
+        execute!(terminal.backend_mut(), PopKeyboardEnhancementFlags)?;
+    }
+    execute!(
+        terminal.backend_mut(),
+        DisableMouseCapture,
+        DisableBracketedPaste,
+        SetCursorStyle::DefaultUserShape,
+        LeaveAlternateScreen,
+    )?;
+    terminal.show_cursor()?;
+    disable_raw_mode()?;
+
+    let status = input::editor::run_in_foreground(command);
+
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
+    if keyboard_enhanced {
+        execute!(
+            terminal.backend_mut(),
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+        )?;
+    }
+    // Redraw everything, not just what changed since the last frame
+    terminal.clear()?;
+    status
+}
+
+/// Host terminal cursor style for a child's DECSCUSR shape.
+fn cursor_style(shape: CursorShape) -> SetCursorStyle {
+    match shape {
+        CursorShape::Default => SetCursorStyle::DefaultUserShape,
+        CursorShape::BlinkingBlock => SetCursorStyle::BlinkingBlock,
+        CursorShape::SteadyBlock => SetCursorStyle::SteadyBlock,
+        CursorShape::BlinkingUnderline => SetCursorStyle::BlinkingUnderScore,
+        CursorShape::SteadyUnderline => SetCursorStyle::SteadyUnderScore,
+        CursorShape::BlinkingBar => SetCursorStyle::BlinkingBar,
+        CursorShape::SteadyBar => SetCursorStyle::SteadyBar,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -390,4 +825,41 @@ mod tests {
 
         assert!(cli.show_restart_marker);
     }
+
+    #[test]
+    fn cli_fps_defaults_and_rejects_out_of_range() {
+        let cli = Cli::parse_from(["panex", "echo test"]);
+        assert_eq!(cli.fps, config::DEFAULT_FPS);
+
+        let cli = Cli::parse_from(["panex", "--fps", "30", "echo test"]);
+        assert_eq!(cli.fps, 30);
+
+        assert!(Cli::try_parse_from(["panex", "--fps", "0", "echo test"]).is_err());
+    }
+
+    #[test]
+    fn dump_buffers_writes_a_file_per_process() {
+        let (tx, _rx) = mpsc::channel(EVENT_QUEUE_CAPACITY);
+        let mut pm = ProcessManager::new(tx, 40, 10, 500, 1000, false);
+        for name in ["web", "api/v1", "api_v1"] {
+            pm.add_process(config::ProcessConfig {
+                name: name.to_string(),
+                command: "true".to_string(),
+                ..config::ProcessConfig::default()
+            });
+        }
+        pm.get_process_mut("web")
+            .unwrap()
+            .buffer
+            .write(b"\x1b[32mready\x1b[0m\r\n");
+        let dir = std::env::temp_dir().join(format!("panex-dump-{}", std::process::id()));
+
+        dump_buffers(&pm, &dir, ExportFormat::Ansi).unwrap();
+        let web = std::fs::read_to_string(dir.join("web.ansi")).unwrap();
+        assert_eq!(web, "\x1b[0;32mready\x1b[0m\n");
+        // Names that map to the same file get numbered instead of overwritten
+        assert!(dir.join("api_v1.ansi").exists());
+        assert!(dir.join("api_v1-2.ansi").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
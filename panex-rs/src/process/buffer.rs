@@ -1,31 +1,88 @@
+use super::grid::Grid;
+pub use super::grid::LineView;
+use super::line::StyleId;
+pub use super::line::{Cell, Line};
 use ratatui::style::{Color, Modifier, Style};
-use std::collections::VecDeque;
 use vte::{Params, Perform};
 
 pub const DEFAULT_MAX_SCROLLBACK: usize = 10_000;
 const MAX_LINE_WIDTH: usize = 2000; // Max column to prevent runaway memory allocation
+const MAX_KITTY_STACK: usize = 16; // Kitty keyboard flag stack depth (per spec)
 
-#[derive(Debug, Clone, Default)]
-pub struct Cell {
-    pub c: char,
-    pub style: Style,
+/// Keyboard encoding modes requested by the child, used when forwarding keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyboardModes {
+    /// DECCKM — cursor keys send SS3 (`ESC O A`) instead of CSI (`ESC [ A`)
+    pub application_cursor: bool,
+    /// DECKPAM — keypad keys send application sequences (`ESC O p`…)
+    pub application_keypad: bool,
+    /// xterm modifyOtherKeys level set via `CSI > 4 ; n m` (0 = off)
+    pub modify_other_keys: u8,
+    /// Active kitty keyboard protocol flags (0 = legacy encoding)
+    pub kitty_flags: u16,
 }
 
-#[derive(Debug, Clone)]
-pub struct Line {
-    pub cells: Vec<Cell>,
+/// Which mouse events the child asked to receive (DECSET 9/1000/1002/1003).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MouseTracking {
+    #[default]
+    Off,
+    /// Mode 9 — button presses only, no modifiers
+    X10,
+    /// Mode 1000 — presses, releases and wheel
+    Normal,
+    /// Mode 1002 — also motion while a button is held
+    ButtonEvent,
+    /// Mode 1003 — all motion, with or without buttons
+    AnyEvent,
 }
 
-impl Line {
-    pub fn new() -> Self {
-        Self { cells: Vec::new() }
-    }
+/// How mouse reports are encoded (DECSET 1005/1006/1015/1016).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MouseEncoding {
+    /// `ESC [ M Cb Cx Cy` with single-byte values (coordinates up to 223)
+    #[default]
+    X10,
+    /// Mode 1005 — like X10 but values are UTF-8 encoded (up to 2015)
+    Utf8,
+    /// Mode 1006 — `ESC [ < b ; x ; y M/m`
+    Sgr,
+    /// Mode 1015 — `ESC [ b ; x ; y M` with decimal values
+    Urxvt,
+    /// Mode 1016 — SGR format with pixel coordinates
+    SgrPixels,
 }
 
-impl Default for Line {
-    fn default() -> Self {
-        Self::new()
-    }
+/// Mouse reporting requested by the child, used when forwarding mouse events.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MouseModes {
+    pub tracking: MouseTracking,
+    pub encoding: MouseEncoding,
+}
+
+/// Cursor shape requested via DECSCUSR (`CSI Ps SP q`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CursorShape {
+    /// Ps = 0 — the host terminal's configured shape
+    #[default]
+    Default,
+    BlinkingBlock,
+    SteadyBlock,
+    BlinkingUnderline,
+    SteadyUnderline,
+    BlinkingBar,
+    SteadyBar,
+}
+
+/// Line changes since the last `take_line_changes`, for consumers that keep
+/// per-line data in sync incrementally (the wrap index).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LineChanges {
+    /// Lines dropped from the front (scrollback trimming, eviction)
+    pub popped_front: usize,
+    /// Lowest row, after the pops, whose length changed or that was
+    /// inserted or removed. Rows before it are unchanged.
+    pub first_changed: Option<usize>,
 }
 
 pub struct TerminalBuffer {
@@ -34,13 +91,27 @@ pub struct TerminalBuffer {
 }
 
 struct TerminalState {
-    lines: VecDeque<Line>,
+    /// Scrollback followed by the screen, addressed as one sequence of rows.
+    lines: Grid,
     cursor_row: usize,
     cursor_col: usize,
     cols: usize,
     rows: usize,
     max_scrollback: usize,
+    /// Lines dropped from the front so far, which shifts every row address
+    dropped_lines: usize,
     current_style: Style,
+    /// Interned id of `current_style`, refreshed after each SGR.
+    current_style_id: StyleId,
+    /// Whether DIM is forced onto everything written (hook output).
+    dimmed: bool,
+    /// Style of the other writer, swapped with `current_style` by `write_dimmed`
+    /// so hook and process colors don't leak into each other.
+    other_style: Style,
+    /// Heap bytes held by `lines`, maintained incrementally.
+    line_bytes: usize,
+    /// Line changes not yet taken by `take_line_changes`.
+    line_changes: LineChanges,
     saved_cursor: Option<(usize, usize)>,
     pending_responses: Vec<Vec<u8>>,
     /// Scroll region: (top, bottom) 0-indexed, inclusive. None = full screen.
@@ -50,11 +121,23 @@ struct TerminalState {
     alternate_screen: bool,
     /// Active mouse tracking mode (0 = none, 9/1000/1002/1003 = enabled).
     mouse_mode: u16,
+    /// Active mouse encoding mode (0 = X10 default, 1005/1006/1015/1016).
+    mouse_encoding: u16,
     /// Whether DECCKM (application cursor keys) is enabled.
     decckm: bool,
+    /// Whether DECKPAM (application keypad, ESC =) is enabled.
+    keypad_application: bool,
+    /// xterm modifyOtherKeys level (0 = off, 1 or 2).
+    modify_other_keys: u8,
+    /// Kitty keyboard protocol flag stack (CSI > flags u pushes, CSI < n u pops).
+    kitty_keyboard: Vec<u16>,
     /// Whether the cursor is hidden via DECTCEM (CSI ?25l).
     /// Interactive line-mode apps hide the cursor during redraws.
     cursor_hidden: bool,
+    /// Cursor shape set via DECSCUSR.
+    cursor_shape: CursorShape,
+    /// Whether bracketed paste (CSI ?2004h) is enabled by the child.
+    bracketed_paste: bool,
 }
 
 impl TerminalBuffer {
@@ -73,12 +156,13 @@ impl TerminalBuffer {
     pub fn resize(&mut self, cols: usize, rows: usize) {
         self.state.cols = cols;
         self.state.rows = rows;
+        self.state.lines.set_screen_rows(rows);
         self.state.scroll_region = None;
         // In alternate screen mode, truncate buffer to new screen size
         // and clamp cursor so no stale lines remain past the screen bottom.
         if self.state.alternate_screen && rows > 0 {
             while self.state.lines.len() > rows {
-                self.state.lines.pop_back();
+                self.state.pop_back_line();
             }
             if self.state.cursor_row >= rows {
                 self.state.cursor_row = rows - 1;
@@ -95,6 +179,14 @@ impl TerminalBuffer {
     }
 
     pub fn write(&mut self, data: &[u8]) {
+        self.state.set_dimmed(false);
+        self.parser.advance(&mut self.state, data);
+    }
+
+    /// Write output that isn't the process's own (hook output) in a dim style.
+    /// It keeps its own colors, separate from the process's.
+    pub fn write_dimmed(&mut self, data: &[u8]) {
+        self.state.set_dimmed(true);
         self.parser.advance(&mut self.state, data);
     }
 
@@ -102,14 +194,56 @@ impl TerminalBuffer {
     /// Avoids showing empty cursor line after newline.
     pub fn content_line_count(&self) -> usize {
         let mut count = self.state.lines.len();
-        while count > 0 && self.state.lines[count - 1].cells.is_empty() {
+        while count > 0 && self.state.lines[count - 1].is_empty() {
             count -= 1;
         }
         count.max(1)
     }
 
-    pub fn get_all_lines(&self) -> &VecDeque<Line> {
-        &self.state.lines
+    /// Approximate heap bytes held by this buffer's lines and their styles.
+    pub fn memory_usage(&self) -> usize {
+        self.state.line_bytes
+            + self.state.lines.len() * std::mem::size_of::<Line>()
+            + self.state.lines.view().styles().heap_bytes()
+    }
+
+    /// Drop up to `max_lines` of the oldest scrollback lines, never touching the
+    /// cursor line or the visible screen. Returns how many lines were removed.
+    pub fn evict_oldest(&mut self, max_lines: usize) -> usize {
+        let state = &mut self.state;
+        if state.alternate_screen {
+            return 0;
+        }
+        let removable = state
+            .lines
+            .len()
+            .saturating_sub(state.rows.max(1))
+            .min(state.cursor_row);
+        let n = max_lines.min(removable);
+        for _ in 0..n {
+            state.pop_front_line();
+        }
+        state.cursor_row -= n;
+        if let Some((row, col)) = state.saved_cursor {
+            state.saved_cursor = Some((row.saturating_sub(n), col));
+        }
+        n
+    }
+
+    /// Lines dropped from the front since the buffer was created or
+    /// cleared for a restart. A row noted before a write moves up by however
+    /// much this grows during it.
+    pub fn dropped_lines(&self) -> usize {
+        self.state.dropped_lines
+    }
+
+    pub fn get_all_lines(&self) -> LineView<'_> {
+        self.state.lines.view()
+    }
+
+    /// Take the line changes accumulated since the previous call.
+    pub fn take_line_changes(&mut self) -> LineChanges {
+        std::mem::take(&mut self.state.line_changes)
     }
 
     pub fn is_alternate_screen(&self) -> bool {
@@ -120,6 +254,40 @@ impl TerminalBuffer {
         self.state.mouse_mode != 0
     }
 
+    /// Cursor position as (buffer row, column).
+    pub fn cursor_position(&self) -> (usize, usize) {
+        (self.state.cursor_row, self.state.cursor_col)
+    }
+
+    /// Returns false while the child hides the cursor (DECTCEM, CSI ?25l).
+    pub fn cursor_visible(&self) -> bool {
+        !self.state.cursor_hidden
+    }
+
+    /// Cursor shape the child requested via DECSCUSR.
+    pub fn cursor_shape(&self) -> CursorShape {
+        self.state.cursor_shape
+    }
+
+    /// Mouse tracking and encoding modes the child requested.
+    pub fn mouse_modes(&self) -> MouseModes {
+        let tracking = match self.state.mouse_mode {
+            9 => MouseTracking::X10,
+            1000 => MouseTracking::Normal,
+            1002 => MouseTracking::ButtonEvent,
+            1003 => MouseTracking::AnyEvent,
+            _ => MouseTracking::Off,
+        };
+        let encoding = match self.state.mouse_encoding {
+            1005 => MouseEncoding::Utf8,
+            1006 => MouseEncoding::Sgr,
+            1015 => MouseEncoding::Urxvt,
+            1016 => MouseEncoding::SgrPixels,
+            _ => MouseEncoding::X10,
+        };
+        MouseModes { tracking, encoding }
+    }
+
     /// Returns true if the child process has indicated it handles special keys
     /// (arrow, function, Home/End, etc.) — i.e. DECCKM, alternate screen,
     /// mouse tracking, or cursor hidden (DECTCEM) is active.
@@ -128,6 +296,24 @@ impl TerminalBuffer {
             || self.state.decckm
             || self.state.mouse_mode != 0
             || self.state.cursor_hidden
+            || self.state.modify_other_keys != 0
+            || self.state.kitty_flags() != 0
+    }
+
+    /// Keyboard modes the child requested (cursor/keypad modes, modifyOtherKeys, kitty).
+    pub fn keyboard_modes(&self) -> KeyboardModes {
+        KeyboardModes {
+            application_cursor: self.state.decckm,
+            application_keypad: self.state.keypad_application,
+            modify_other_keys: self.state.modify_other_keys,
+            kitty_flags: self.state.kitty_flags(),
+        }
+    }
+
+    /// Returns true if the child enabled bracketed paste (mode 2004),
+    /// so pasted text should be wrapped in `ESC[200~ … ESC[201~`.
+    pub fn wants_bracketed_paste(&self) -> bool {
+        self.state.bracketed_paste
     }
 
     pub fn take_pending_responses(&mut self) -> Vec<Vec<u8>> {
@@ -143,7 +329,7 @@ impl TerminalBuffer {
         let mut result: Vec<String> = Vec::with_capacity(count);
         for i in 0..count {
             let line = &self.state.lines[i];
-            let s: String = line.cells.iter().map(|cell| cell.c).collect();
+            let s = line.text();
             result.push(s.trim_end().to_string());
         }
         // Remove trailing empty lines
@@ -156,7 +342,7 @@ impl TerminalBuffer {
 
 impl TerminalState {
     fn new(cols: usize, rows: usize, max_scrollback: usize) -> Self {
-        let mut lines = VecDeque::with_capacity(max_scrollback.min(1024));
+        let mut lines = Grid::new(rows, max_scrollback.min(1024));
         lines.push_back(Line::new());
         Self {
             lines,
@@ -165,14 +351,29 @@ impl TerminalState {
             cols,
             rows,
             max_scrollback,
+            dropped_lines: 0,
             current_style: Style::default(),
+            current_style_id: StyleId::DEFAULT,
+            dimmed: false,
+            other_style: Style::default(),
+            line_bytes: 0,
+            line_changes: LineChanges {
+                popped_front: 0,
+                first_changed: Some(0),
+            },
             saved_cursor: None,
             pending_responses: Vec::new(),
             scroll_region: None,
             alternate_screen: false,
             mouse_mode: 0,
+            mouse_encoding: 0,
             decckm: false,
+            keypad_application: false,
+            modify_other_keys: 0,
+            kitty_keyboard: Vec::new(),
             cursor_hidden: false,
+            cursor_shape: CursorShape::Default,
+            bracketed_paste: false,
         }
     }
 
@@ -185,35 +386,75 @@ impl TerminalState {
         };
         let target = row.min(max);
         while self.lines.len() <= target {
+            self.mark_changed(self.lines.len());
             self.lines.push_back(Line::new());
         }
-        // Trim if over max scrollback (only relevant in normal mode)
-        if !self.alternate_screen {
-            while self.lines.len() > self.max_scrollback {
-                self.lines.pop_front();
-                if self.cursor_row > 0 {
-                    self.cursor_row -= 1;
+        self.trim_scrollback();
+    }
+
+    /// Drop the oldest lines beyond `max_scrollback` (normal mode only),
+    /// keeping the cursor and scroll region on the same screen rows.
+    fn trim_scrollback(&mut self) {
+        if self.alternate_screen {
+            return;
+        }
+        while self.lines.len() > self.max_scrollback {
+            self.pop_front_line();
+            if self.cursor_row > 0 {
+                self.cursor_row -= 1;
+            }
+            if let Some((top, bottom)) = self.scroll_region {
+                if top > 0 {
+                    self.scroll_region = Some((top - 1, bottom - 1));
                 }
             }
         }
     }
 
-    fn ensure_col(&mut self, col: usize) {
-        self.ensure_row(self.cursor_row);
-        let line = &mut self.lines[self.cursor_row];
-        while line.cells.len() <= col {
-            line.cells.push(Cell {
-                c: ' ',
-                style: Style::default(),
-            });
+    /// Record that `row` (and everything after it) may have moved or resized.
+    fn mark_changed(&mut self, row: usize) {
+        let first = &mut self.line_changes.first_changed;
+        *first = Some(first.map_or(row, |r| r.min(row)));
+    }
+
+    /// Apply `f` to a line, keeping the memory accounting in sync.
+    fn edit_line<R>(&mut self, row: usize, f: impl FnOnce(&mut Line) -> R) -> R {
+        let line = &mut self.lines[row];
+        let (bytes, len) = (line.heap_bytes(), line.len());
+        let result = f(line);
+        self.line_bytes = self.line_bytes - bytes + line.heap_bytes();
+        if line.len() != len {
+            self.mark_changed(row);
+        }
+        result
+    }
+
+    fn pop_front_line(&mut self) {
+        if let Some(line) = self.lines.pop_front() {
+            self.line_bytes -= line.heap_bytes();
+            self.dropped_lines += 1;
+            let changes = &mut self.line_changes;
+            changes.popped_front += 1;
+            changes.first_changed = changes.first_changed.map(|r| r.saturating_sub(1));
+        }
+    }
+
+    fn pop_back_line(&mut self) {
+        if let Some(line) = self.lines.pop_back() {
+            self.line_bytes -= line.heap_bytes();
+            self.mark_changed(self.lines.len());
         }
     }
 
     fn newline(&mut self) {
+        // The line being left is usually finished; drop its growth slack
+        if self.cursor_row < self.lines.len() {
+            self.edit_line(self.cursor_row, Line::shrink_to_fit);
+        }
         if let Some((top, bottom)) = self.scroll_region {
             if self.cursor_row >= top && self.cursor_row <= bottom {
                 if self.cursor_row == bottom {
-                    self.scroll_region_up(top, bottom, 1);
+                    self.scroll_or_save(top, bottom, 1);
                 } else {
                     self.cursor_row += 1;
                     self.ensure_row(self.cursor_row);
@@ -241,31 +482,47 @@ impl TerminalState {
     /// Scroll content up within [top, bottom] by `n` lines.
     fn scroll_region_up(&mut self, top: usize, bottom: usize, n: usize) {
         self.ensure_row(bottom);
-        for _ in 0..n {
-            if top <= bottom && bottom < self.lines.len() {
-                self.lines.remove(top);
-                self.lines.insert(bottom, Line::new());
-            }
+        if top > bottom || bottom >= self.lines.len() {
+            return;
+        }
+        self.line_bytes -= self.lines.scroll_up(top, bottom, n);
+        self.mark_changed(top);
+    }
+
+    /// Scroll up within [top, bottom] by `n` lines on behalf of a line feed
+    /// or `SU`. On the normal screen, a region covering the whole screen
+    /// keeps its lines as scrollback; the screen, and with it the cursor
+    /// and region, moves down by `n` rows.
+    fn scroll_or_save(&mut self, top: usize, bottom: usize, n: usize) {
+        self.ensure_row(bottom);
+        if self.alternate_screen || !self.lines.spans_screen(top, bottom) {
+            self.scroll_region_up(top, bottom, n);
+            return;
         }
+        let n = n.min(bottom - top + 1);
+        self.mark_changed(bottom + 1);
+        self.lines.scroll_into_scrollback(n);
+        self.cursor_row += n;
+        self.scroll_region = self
+            .scroll_region
+            .map(|(top, bottom)| (top + n, bottom + n));
+        self.trim_scrollback();
     }
 
     /// Scroll content down within [top, bottom] by `n` lines.
     fn scroll_region_down(&mut self, top: usize, bottom: usize, n: usize) {
         self.ensure_row(bottom);
-        for _ in 0..n {
-            if top <= bottom && bottom < self.lines.len() {
-                self.lines.remove(bottom);
-                self.lines.insert(top, Line::new());
-            }
+        if top > bottom || bottom >= self.lines.len() {
+            return;
         }
+        self.line_bytes -= self.lines.scroll_down(top, bottom, n);
+        self.mark_changed(top);
     }
 
     fn put_char(&mut self, c: char) {
-        self.ensure_col(self.cursor_col);
-        self.lines[self.cursor_row].cells[self.cursor_col] = Cell {
-            c,
-            style: self.current_style,
-        };
+        self.ensure_row(self.cursor_row);
+        let (col, style) = (self.cursor_col, self.current_style_id);
+        self.edit_line(self.cursor_row, |line| line.set(col, c, style));
         self.cursor_col += 1;
         // Don't auto-wrap: let lines grow as needed, truncate at render time.
         // This prevents content corruption when terminal is resized narrower.
@@ -273,29 +530,92 @@ impl TerminalState {
 
     fn clear_line_from(&mut self, col: usize) {
         self.ensure_row(self.cursor_row);
-        let line = &mut self.lines[self.cursor_row];
-        if col < line.cells.len() {
-            line.cells.truncate(col);
-        }
+        self.edit_line(self.cursor_row, |line| line.truncate(col));
     }
 
     fn clear_screen_from_cursor(&mut self) {
         self.clear_line_from(self.cursor_col);
         // Clear all lines below
         while self.lines.len() > self.cursor_row + 1 {
-            self.lines.pop_back();
+            self.pop_back_line();
         }
     }
 
     fn clear_screen(&mut self) {
         self.lines.clear();
         self.lines.push_back(Line::new());
+        self.line_bytes = 0;
+        self.mark_changed(0);
         self.cursor_row = 0;
         self.cursor_col = 0;
         self.scroll_region = None;
     }
 
+    fn kitty_flags(&self) -> u16 {
+        self.kitty_keyboard.last().copied().unwrap_or(0)
+    }
+
+    /// Kitty keyboard protocol: `CSI > flags u` push, `CSI < n u` pop,
+    /// `CSI = flags ; mode u` set, `CSI ? u` query.
+    fn kitty_keyboard_dispatch(&mut self, marker: u8, params: &[u16]) {
+        match marker {
+            b'>' => {
+                if self.kitty_keyboard.len() >= MAX_KITTY_STACK {
+                    self.kitty_keyboard.remove(0);
+                }
+                self.kitty_keyboard
+                    .push(params.first().copied().unwrap_or(0));
+            }
+            b'<' => {
+                let n = params.first().copied().filter(|&n| n != 0).unwrap_or(1) as usize;
+                let keep = self.kitty_keyboard.len().saturating_sub(n);
+                self.kitty_keyboard.truncate(keep);
+            }
+            b'=' => {
+                let flags = params.first().copied().unwrap_or(0);
+                let current = self.kitty_flags();
+                let updated = match params.get(1).copied().unwrap_or(1) {
+                    2 => current | flags,
+                    3 => current & !flags,
+                    _ => flags,
+                };
+                match self.kitty_keyboard.last_mut() {
+                    Some(top) => *top = updated,
+                    None => self.kitty_keyboard.push(updated),
+                }
+            }
+            b'?' => {
+                let response = format!("\x1b[?{}u", self.kitty_flags());
+                self.pending_responses.push(response.into_bytes());
+            }
+            _ => {}
+        }
+    }
+
     fn parse_sgr(&mut self, params: &Params) {
+        self.apply_sgr(params);
+        self.refresh_style_id();
+    }
+
+    fn refresh_style_id(&mut self) {
+        let style = if self.dimmed {
+            self.current_style.add_modifier(Modifier::DIM)
+        } else {
+            self.current_style
+        };
+        self.current_style_id = self.lines.intern_style(style);
+    }
+
+    /// Switch between process output and dimmed hook output.
+    fn set_dimmed(&mut self, dimmed: bool) {
+        if self.dimmed != dimmed {
+            self.dimmed = dimmed;
+            std::mem::swap(&mut self.current_style, &mut self.other_style);
+            self.refresh_style_id();
+        }
+    }
+
+    fn apply_sgr(&mut self, params: &Params) {
         let params: Vec<u16> = params.iter().flat_map(|p| p.iter().copied()).collect();
 
         if params.is_empty() {
@@ -439,7 +759,17 @@ impl Perform for TerminalState {
     fn put(&mut self, _byte: u8) {}
     fn unhook(&mut self) {}
     fn osc_dispatch(&mut self, _params: &[&[u8]], _bell_terminated: bool) {}
-    fn esc_dispatch(&mut self, _intermediates: &[u8], _ignore: bool, _byte: u8) {}
+    fn esc_dispatch(&mut self, intermediates: &[u8], _ignore: bool, byte: u8) {
+        if !intermediates.is_empty() {
+            return;
+        }
+        match byte {
+            // DECKPAM / DECKPNM — application / numeric keypad
+            b'=' => self.keypad_application = true,
+            b'>' => self.keypad_application = false,
+            _ => {}
+        }
+    }
 
     fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], _ignore: bool, action: char) {
         let params_vec: Vec<u16> = params.iter().flat_map(|p| p.iter().copied()).collect();
@@ -546,19 +876,17 @@ impl Perform for TerminalState {
                     1 => {
                         // Clear from start to cursor
                         self.ensure_row(self.cursor_row);
-                        for i in 0..=self.cursor_col {
-                            if i < self.lines[self.cursor_row].cells.len() {
-                                self.lines[self.cursor_row].cells[i] = Cell {
-                                    c: ' ',
-                                    style: Style::default(),
-                                };
+                        let end = self.cursor_col;
+                        self.edit_line(self.cursor_row, |line| {
+                            for i in 0..line.len().min(end + 1) {
+                                line.set(i, ' ', StyleId::DEFAULT);
                             }
-                        }
+                        });
                     }
                     2 => {
                         // Clear entire line
                         self.ensure_row(self.cursor_row);
-                        self.lines[self.cursor_row].cells.clear();
+                        self.edit_line(self.cursor_row, |line| line.truncate(0));
                     }
                     _ => {}
                 }
@@ -584,7 +912,7 @@ impl Perform for TerminalState {
                 // Scroll Up (SU) - scroll content up within scroll region
                 if let Some((top, bottom)) = self.scroll_region {
                     let n = get_param(0, 1) as usize;
-                    self.scroll_region_up(top, bottom, n);
+                    self.scroll_or_save(top, bottom, n);
                 }
             }
             'T' => {
@@ -598,28 +926,43 @@ impl Perform for TerminalState {
                 // Insert Lines (IL) - insert blank lines at cursor, push down within region
                 if let Some((_top, bottom)) = self.scroll_region {
                     let n = get_param(0, 1) as usize;
-                    self.ensure_row(bottom);
-                    for _ in 0..n {
-                        if self.cursor_row <= bottom && bottom < self.lines.len() {
-                            self.lines.remove(bottom);
-                            self.lines.insert(self.cursor_row, Line::new());
-                        }
-                    }
+                    self.scroll_region_down(self.cursor_row, bottom, n);
                 }
             }
             'M' => {
                 // Delete Lines (DL) - delete lines at cursor, pull up within region
                 if let Some((_top, bottom)) = self.scroll_region {
                     let n = get_param(0, 1) as usize;
-                    self.ensure_row(bottom);
-                    for _ in 0..n {
-                        if self.cursor_row <= bottom && self.cursor_row < self.lines.len() {
-                            self.lines.remove(self.cursor_row);
-                            self.lines.insert(bottom, Line::new());
-                        }
-                    }
+                    self.scroll_region_up(self.cursor_row, bottom, n);
                 }
             }
+            'm' if intermediates == b">" && get_param(0, 0) == 4 => {
+                // XTMODKEYS - CSI > 4 ; level m sets modifyOtherKeys
+                self.modify_other_keys = params_vec.get(1).copied().unwrap_or(0).min(2) as u8;
+            }
+            'n' if intermediates == b">" && get_param(0, 0) == 4 => {
+                // CSI > 4 n resets modifyOtherKeys
+                self.modify_other_keys = 0;
+            }
+            'm' | 'n' if intermediates == b">" => {
+                // Other xterm key modifier resources are not supported
+            }
+            'q' if intermediates == b" " => {
+                // DECSCUSR - set cursor style
+                self.cursor_shape = match params_vec.first().copied().unwrap_or(0) {
+                    0 => CursorShape::Default,
+                    1 => CursorShape::BlinkingBlock,
+                    2 => CursorShape::SteadyBlock,
+                    3 => CursorShape::BlinkingUnderline,
+                    4 => CursorShape::SteadyUnderline,
+                    5 => CursorShape::BlinkingBar,
+                    6 => CursorShape::SteadyBar,
+                    _ => self.cursor_shape,
+                };
+            }
+            'u' if !intermediates.is_empty() => {
+                self.kitty_keyboard_dispatch(intermediates[0], &params_vec);
+            }
             'm' => {
                 // SGR - Select Graphic Rendition
                 self.parse_sgr(params);
@@ -668,37 +1011,51 @@ impl Perform for TerminalState {
                 }
             }
             'h' | 'l' if intermediates == b"?" => {
-                // DEC Private Mode Set/Reset (CSI ? ... h/l)
-                let mode = get_param(0, 0);
-                match mode {
-                    1049 | 1047 | 47 => {
-                        // Alternate screen buffer
-                        if action == 'h' {
-                            self.alternate_screen = true;
-                            self.clear_screen();
-                        } else {
-                            self.alternate_screen = false;
-                            self.scroll_region = None;
+                // DEC Private Mode Set/Reset (CSI ? ... h/l); apps often
+                // combine several modes in one sequence (CSI ?1000;1006h)
+                for &mode in &params_vec {
+                    match mode {
+                        1049 | 1047 | 47 => {
+                            // Alternate screen buffer
+                            if action == 'h' {
+                                self.alternate_screen = true;
+                                self.clear_screen();
+                            } else {
+                                self.alternate_screen = false;
+                                self.scroll_region = None;
+                            }
                         }
-                    }
-                    1 => {
-                        // DECCKM — application cursor keys
-                        self.decckm = action == 'h';
-                    }
-                    25 => {
-                        // DECTCEM — cursor visibility
-                        // 'h' = show cursor (enable), 'l' = hide cursor (disable)
-                        self.cursor_hidden = action == 'l';
-                    }
-                    9 | 1000 | 1002 | 1003 => {
-                        // Mouse tracking modes
-                        if action == 'h' {
-                            self.mouse_mode = mode;
-                        } else if self.mouse_mode == mode {
-                            self.mouse_mode = 0;
+                        1 => {
+                            // DECCKM — application cursor keys
+                            self.decckm = action == 'h';
                         }
+                        25 => {
+                            // DECTCEM — cursor visibility
+                            // 'h' = show cursor (enable), 'l' = hide cursor (disable)
+                            self.cursor_hidden = action == 'l';
+                        }
+                        2004 => {
+                            // Bracketed paste mode
+                            self.bracketed_paste = action == 'h';
+                        }
+                        9 | 1000 | 1002 | 1003 => {
+                            // Mouse tracking modes
+                            if action == 'h' {
+                                self.mouse_mode = mode;
+                            } else if self.mouse_mode == mode {
+                                self.mouse_mode = 0;
+                            }
+                        }
+                        1005 | 1006 | 1015 | 1016 => {
+                            // Mouse encoding modes
+                            if action == 'h' {
+                                self.mouse_encoding = mode;
+                            } else if self.mouse_encoding == mode {
+                                self.mouse_encoding = 0;
+                            }
+                        }
+                        _ => {}
                     }
-                    _ => {}
                 }
             }
             _ => {}
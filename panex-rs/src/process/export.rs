@@ -0,0 +1,375 @@
+use super::buffer::LineView;
+use super::line::Cell;
+use ratatui::style::{Color, Modifier, Style};
+use std::fmt::Write;
+use std::ops::Range;
+use std::path::Path;
+
+/// Page colors of an HTML export, for cells without their own.
+const HTML_FG: &str = "#d4d4d4";
+const HTML_BG: &str = "#1e1e1e";
+
+/// The 16 basic colors, as xterm shows them.
+const PALETTE: [&str; 16] = [
+    "#000000", "#cd0000", "#00cd00", "#cdcd00", "#0000ee", "#cd00cd", "#00cdcd", "#e5e5e5",
+    "#7f7f7f", "#ff0000", "#00ff00", "#ffff00", "#5c5cff", "#ff00ff", "#00ffff", "#ffffff",
+];
+
+/// How a buffer is written out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Plain text
+    Text,
+    /// Text with its colors and attributes as SGR escape sequences
+    Ansi,
+    /// A self-contained HTML page with inline styles
+    Html,
+}
+
+impl ExportFormat {
+    /// Parse `text`, `ansi` or `html`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "text" | "txt" => Ok(Self::Text),
+            "ansi" => Ok(Self::Ansi),
+            "html" => Ok(Self::Html),
+            other => Err(format!(
+                "unknown export format: {other} (expected text, ansi or html)"
+            )),
+        }
+    }
+
+    /// The format a file name's extension asks for, if any.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "html" | "htm" => Some(Self::Html),
+            "ansi" | "ans" => Some(Self::Ansi),
+            "txt" => Some(Self::Text),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Text => "txt",
+            Self::Ansi => "ansi",
+            Self::Html => "html",
+        }
+    }
+}
+
+/// A process name usable as a file name.
+pub fn file_stem(name: &str) -> String {
+    name.replace(['/', '\\'], "_")
+}
+
+/// Write the `(row, columns)` spans of `lines` in `format`, one line each.
+/// Trailing blanks are dropped; `title` names an HTML page.
+pub fn export(
+    lines: LineView<'_>,
+    spans: &[(usize, Range<usize>)],
+    format: ExportFormat,
+    title: &str,
+) -> String {
+    let rows = spans.iter().map(|(row, cols)| {
+        let mut cells: Vec<Cell> = lines
+            .get(*row)
+            .map(|line| {
+                line.cells(lines.styles())
+                    .skip(cols.start)
+                    .take(cols.len())
+                    .collect()
+            })
+            .unwrap_or_default();
+        while cells.last().is_some_and(|cell| {
+            cell.c == ' ' && (format == ExportFormat::Text || cell.style == Style::default())
+        }) {
+            cells.pop();
+        }
+        cells
+    });
+    let mut out = String::new();
+    match format {
+        ExportFormat::Text => {
+            for cells in rows {
+                out.extend(cells.iter().map(|cell| cell.c));
+                out.push('\n');
+            }
+        }
+        ExportFormat::Ansi => {
+            for cells in rows {
+                let mut current = Style::default();
+                for cell in cells {
+                    if cell.style != current {
+                        current = cell.style;
+                        out.push_str(&sgr(current));
+                    }
+                    out.push(cell.c);
+                }
+                if current != Style::default() {
+                    out.push_str("\x1b[0m");
+                }
+                out.push('\n');
+            }
+        }
+        ExportFormat::Html => {
+            let _ = write!(
+                out,
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+                 <title>{}</title>\n<style>\n\
+                 body {{ margin: 0; background: {HTML_BG}; color: {HTML_FG}; }}\n\
+                 pre {{ margin: 0; padding: 1em; font: 13px/1.35 ui-monospace, Menlo, Consolas, monospace; }}\n\
+                 </style>\n</head>\n<body>\n<pre>",
+                escape_html(title)
+            );
+            for cells in rows {
+                // Runs of cells in the same style share a span
+                for run in cells.chunk_by(|a, b| a.style == b.style) {
+                    let text: String = run.iter().map(|cell| cell.c).collect();
+                    match css(run[0].style) {
+                        Some(css) => {
+                            let _ =
+                                write!(out, "<span style=\"{css}\">{}</span>", escape_html(&text));
+                        }
+                        None => out.push_str(&escape_html(&text)),
+                    }
+                }
+                out.push('\n');
+            }
+            out.push_str("</pre>\n</body>\n</html>\n");
+        }
+    }
+    out
+}
+
+/// An SGR sequence that resets, then sets `style`.
+fn sgr(style: Style) -> String {
+    const ATTRIBUTES: [(Modifier, u8); 9] = [
+        (Modifier::BOLD, 1),
+        (Modifier::DIM, 2),
+        (Modifier::ITALIC, 3),
+        (Modifier::UNDERLINED, 4),
+        (Modifier::SLOW_BLINK, 5),
+        (Modifier::RAPID_BLINK, 6),
+        (Modifier::REVERSED, 7),
+        (Modifier::HIDDEN, 8),
+        (Modifier::CROSSED_OUT, 9),
+    ];
+    let mut codes = vec!["0".to_string()];
+    for (modifier, code) in ATTRIBUTES {
+        if style.add_modifier.contains(modifier) {
+            codes.push(code.to_string());
+        }
+    }
+    codes.extend(style.fg.and_then(|color| color_code(color, 30)));
+    codes.extend(style.bg.and_then(|color| color_code(color, 40)));
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+/// SGR parameters for `color`, with `base` 30 for foreground or 40 for
+/// background. `None` for the terminal's default.
+fn color_code(color: Color, base: u8) -> Option<String> {
+    let code = match color {
+        Color::Reset => return None,
+        Color::Indexed(n) => return Some(format!("{};5;{n}", base + 8)),
+        Color::Rgb(r, g, b) => return Some(format!("{};2;{r};{g};{b}", base + 8)),
+        // The parser reads both 37 and 97 as White
+        Color::Gray | Color::White => base + 7,
+        Color::DarkGray => base + 60,
+        color => {
+            let index = basic_index(color)?;
+            if index < 8 {
+                base + index
+            } else {
+                base + 60 + index - 8
+            }
+        }
+    };
+    Some(code.to_string())
+}
+
+/// Palette index of a named color.
+fn basic_index(color: Color) -> Option<u8> {
+    Some(match color {
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray | Color::White => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        _ => return None,
+    })
+}
+
+/// CSS color for `color`; `None` for the default.
+fn css_color(color: Color) -> Option<String> {
+    match color {
+        Color::Reset => None,
+        Color::Rgb(r, g, b) => Some(format!("#{r:02x}{g:02x}{b:02x}")),
+        Color::Indexed(n) if n < 16 => Some(PALETTE[n as usize].to_string()),
+        Color::Indexed(n) if n < 232 => {
+            // 6x6x6 color cube
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let n = n - 16;
+            let (r, g, b) = (level(n / 36), level(n / 6 % 6), level(n % 6));
+            Some(format!("#{r:02x}{g:02x}{b:02x}"))
+        }
+        Color::Indexed(n) => {
+            let v = 8 + (n - 232) * 10;
+            Some(format!("#{v:02x}{v:02x}{v:02x}"))
+        }
+        color => basic_index(color).map(|i| PALETTE[i as usize].to_string()),
+    }
+}
+
+/// Inline CSS for `style`; `None` for the default style.
+fn css(style: Style) -> Option<String> {
+    let modifiers = style.add_modifier;
+    let mut fg = style.fg.and_then(css_color);
+    let mut bg = style.bg.and_then(css_color);
+    if modifiers.contains(Modifier::REVERSED) {
+        (fg, bg) = (
+            Some(bg.unwrap_or_else(|| HTML_BG.to_string())),
+            Some(fg.unwrap_or_else(|| HTML_FG.to_string())),
+        );
+    }
+    let mut css = Vec::new();
+    css.extend(fg.map(|c| format!("color:{c}")));
+    css.extend(bg.map(|c| format!("background:{c}")));
+    if modifiers.contains(Modifier::BOLD) {
+        css.push("font-weight:bold".to_string());
+    }
+    if modifiers.contains(Modifier::DIM) {
+        css.push("opacity:0.6".to_string());
+    }
+    if modifiers.contains(Modifier::ITALIC) {
+        css.push("font-style:italic".to_string());
+    }
+    let decorations: Vec<&str> = [
+        (Modifier::UNDERLINED, "underline"),
+        (Modifier::CROSSED_OUT, "line-through"),
+    ]
+    .iter()
+    .filter(|(modifier, _)| modifiers.contains(*modifier))
+    .map(|(_, decoration)| *decoration)
+    .collect();
+    if !decorations.is_empty() {
+        css.push(format!("text-decoration:{}", decorations.join(" ")));
+    }
+    if modifiers.contains(Modifier::HIDDEN) {
+        css.push("visibility:hidden".to_string());
+    }
+    (!css.is_empty()).then(|| css.join(";"))
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::buffer::TerminalBuffer;
+
+    fn buffer(text: &str) -> TerminalBuffer {
+        let mut buffer = TerminalBuffer::new(40, 10);
+        buffer.write(text.as_bytes());
+        buffer
+    }
+
+    fn all(buffer: &TerminalBuffer) -> Vec<(usize, Range<usize>)> {
+        let lines = buffer.get_all_lines();
+        (0..buffer.content_line_count())
+            .map(|row| (row, 0..lines[row].len()))
+            .collect()
+    }
+
+    const OUTPUT: &str = "\x1b[1;31merror\x1b[0m: <bad> & \x1b[38;5;208mwarn\x1b[0m  \r\nplain\r\n";
+
+    #[test]
+    fn text_drops_styles_and_trailing_blanks() {
+        let buffer = buffer(OUTPUT);
+        let text = export(
+            buffer.get_all_lines(),
+            &all(&buffer),
+            ExportFormat::Text,
+            "",
+        );
+        assert_eq!(text, "error: <bad> & warn\nplain\n");
+        // Spans pick out columns
+        let text = export(
+            buffer.get_all_lines(),
+            &[(0, 7..12)],
+            ExportFormat::Text,
+            "",
+        );
+        assert_eq!(text, "<bad>\n");
+    }
+
+    #[test]
+    fn ansi_round_trips_through_the_parser() {
+        let buffer = buffer(OUTPUT);
+        let ansi = export(
+            buffer.get_all_lines(),
+            &all(&buffer),
+            ExportFormat::Ansi,
+            "",
+        );
+        assert!(ansi.starts_with("\x1b[0;1;31merror\x1b[0m: <bad>"));
+        let again = self::buffer(&ansi.replace('\n', "\r\n"));
+        // Same cells, less the trailing blanks
+        let (lines, again) = (buffer.get_all_lines(), again.get_all_lines());
+        for row in 0..2 {
+            let mut expected: Vec<Cell> = lines[row].cells(lines.styles()).collect();
+            expected.truncate(again[row].len());
+            let actual: Vec<Cell> = again[row].cells(again.styles()).collect();
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn html_is_escaped_with_inline_styles() {
+        let buffer = buffer(OUTPUT);
+        let html = export(
+            buffer.get_all_lines(),
+            &all(&buffer),
+            ExportFormat::Html,
+            "web <1>",
+        );
+        assert!(html.contains("<title>web &lt;1&gt;</title>"));
+        assert!(html.contains(
+            "<span style=\"color:#cd0000;font-weight:bold\">error</span>: &lt;bad&gt; &amp; \
+             <span style=\"color:#ff8700\">warn</span>\nplain\n</pre>"
+        ));
+    }
+
+    #[test]
+    fn formats_from_names_and_extensions() {
+        assert_eq!(ExportFormat::parse("html"), Ok(ExportFormat::Html));
+        assert!(ExportFormat::parse("pdf").is_err());
+        assert_eq!(
+            ExportFormat::from_path(Path::new("out/web.htm")),
+            Some(ExportFormat::Html)
+        );
+        assert_eq!(ExportFormat::from_path(Path::new("web.log")), None);
+    }
+}
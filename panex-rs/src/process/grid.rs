@@ -0,0 +1,376 @@
+use super::line::{Line, StyleId, StyleTable};
+use ratatui::style::Style;
+use std::collections::{HashSet, VecDeque};
+use std::ops::{Index, IndexMut};
+
+/// Terminal lines split into scrollback and the screen.
+///
+/// Rows are addressed as one sequence: scrollback first, then the screen.
+/// The screen is a ring holding at most `screen_rows` lines, so scrolling the
+/// whole screen is a pop and a push per line, and a scroll region inside it
+/// is rotated in place (moving the region's `Line`s, O(region) per scroll).
+/// A line only moves to scrollback when it leaves the top of the screen.
+#[derive(Debug, Clone)]
+pub struct Grid {
+    scrollback: VecDeque<Line>,
+    screen: VecDeque<Line>,
+    screen_rows: usize,
+    /// Styles the lines' style ids refer to
+    styles: StyleTable,
+}
+
+impl Grid {
+    pub fn new(screen_rows: usize, scrollback_capacity: usize) -> Self {
+        let screen_rows = screen_rows.max(1);
+        Self {
+            scrollback: VecDeque::with_capacity(scrollback_capacity),
+            screen: VecDeque::with_capacity(screen_rows),
+            screen_rows,
+            styles: StyleTable::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.scrollback.len() + self.screen.len()
+    }
+
+    #[allow(dead_code)] // Pairs with len(); unused by the binary
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn view(&self) -> LineView<'_> {
+        LineView {
+            scrollback: &self.scrollback,
+            screen: &self.screen,
+            styles: &self.styles,
+        }
+    }
+
+    /// Intern a style for these lines. A full table is first compacted to
+    /// the styles the lines still use, renumbering their ids.
+    pub fn intern_style(&mut self, style: Style) -> StyleId {
+        if let Some(id) = self.styles.intern(style) {
+            return id;
+        }
+        let live: HashSet<StyleId> = self
+            .scrollback
+            .iter()
+            .chain(&self.screen)
+            .flat_map(Line::style_ids)
+            .collect();
+        let remap = self.styles.compact(live);
+        for line in self.scrollback.iter_mut().chain(self.screen.iter_mut()) {
+            line.remap_styles(&remap);
+        }
+        self.styles.intern(style).unwrap_or_default()
+    }
+
+    /// Change the screen height, moving lines across the scrollback boundary.
+    /// Row addresses are unaffected.
+    pub fn set_screen_rows(&mut self, rows: usize) {
+        self.screen_rows = rows.max(1);
+        while self.screen.len() > self.screen_rows {
+            if let Some(line) = self.screen.pop_front() {
+                self.scrollback.push_back(line);
+            }
+        }
+        while self.screen.len() < self.screen_rows {
+            let Some(line) = self.scrollback.pop_back() else {
+                break;
+            };
+            self.screen.push_front(line);
+        }
+    }
+
+    /// Append a line at the bottom; a full screen pushes its top line to scrollback.
+    pub fn push_back(&mut self, line: Line) {
+        if self.screen.len() >= self.screen_rows {
+            if let Some(top) = self.screen.pop_front() {
+                self.scrollback.push_back(top);
+            }
+        }
+        self.screen.push_back(line);
+    }
+
+    pub fn pop_front(&mut self) -> Option<Line> {
+        self.scrollback
+            .pop_front()
+            .or_else(|| self.screen.pop_front())
+    }
+
+    pub fn pop_back(&mut self) -> Option<Line> {
+        self.screen
+            .pop_back()
+            .or_else(|| self.scrollback.pop_back())
+    }
+
+    pub fn clear(&mut self) {
+        self.scrollback.clear();
+        self.screen.clear();
+    }
+
+    /// Whether rows `top..=bottom` are exactly the screen.
+    pub fn spans_screen(&self, top: usize, bottom: usize) -> bool {
+        top == self.scrollback.len() && bottom + 1 == self.len()
+    }
+
+    /// Scroll the whole screen up by `n`: its top lines move to scrollback
+    /// and blank rows appear at the bottom, so the grid grows by `n` rows.
+    pub fn scroll_into_scrollback(&mut self, n: usize) {
+        for _ in 0..n.min(self.screen.len()) {
+            if let Some(top) = self.screen.pop_front() {
+                self.scrollback.push_back(top);
+            }
+            self.screen.push_back(Line::new());
+        }
+    }
+
+    /// Scroll rows `top..=bottom` up by `n` (at most the region height):
+    /// the top lines are dropped and blank rows appear at `bottom`. Returns
+    /// the heap bytes dropped. Callers ensure `bottom < len()`.
+    pub fn scroll_up(&mut self, top: usize, bottom: usize, n: usize) -> usize {
+        let n = n.min(bottom - top + 1);
+        let mut dropped = 0;
+        if let Some((top, bottom)) = self.screen_range(top, bottom) {
+            if top == 0 && bottom + 1 == self.screen.len() {
+                // The whole ring: a pop and a push per line
+                for _ in 0..n {
+                    dropped += self.screen.pop_front().map_or(0, |l| l.heap_bytes());
+                    self.screen.push_back(Line::new());
+                }
+            } else {
+                let region = &mut self.screen.make_contiguous()[top..=bottom];
+                region.rotate_left(n);
+                let len = region.len();
+                for line in &mut region[len - n..] {
+                    dropped += std::mem::take(line).heap_bytes();
+                }
+            }
+            return dropped;
+        }
+        self.rotate_left(top, bottom, n);
+        for row in bottom + 1 - n..=bottom {
+            dropped += std::mem::take(&mut self[row]).heap_bytes();
+        }
+        dropped
+    }
+
+    /// Scroll rows `top..=bottom` down by `n` (at most the region height):
+    /// the bottom lines are dropped and blank rows appear at `top`. Returns
+    /// the heap bytes dropped. Callers ensure `bottom < len()`.
+    pub fn scroll_down(&mut self, top: usize, bottom: usize, n: usize) -> usize {
+        let n = n.min(bottom - top + 1);
+        let mut dropped = 0;
+        if let Some((top, bottom)) = self.screen_range(top, bottom) {
+            if top == 0 && bottom + 1 == self.screen.len() {
+                for _ in 0..n {
+                    dropped += self.screen.pop_back().map_or(0, |l| l.heap_bytes());
+                    self.screen.push_front(Line::new());
+                }
+            } else {
+                let region = &mut self.screen.make_contiguous()[top..=bottom];
+                region.rotate_right(n);
+                for line in &mut region[..n] {
+                    dropped += std::mem::take(line).heap_bytes();
+                }
+            }
+            return dropped;
+        }
+        self.rotate_left(top, bottom, bottom - top + 1 - n);
+        for row in top..top + n {
+            dropped += std::mem::take(&mut self[row]).heap_bytes();
+        }
+        dropped
+    }
+
+    /// Rotate rows `top..=bottom` left by `n` across the scrollback
+    /// boundary, by three reversals.
+    fn rotate_left(&mut self, top: usize, bottom: usize, n: usize) {
+        self.reverse(top, top + n);
+        self.reverse(top + n, bottom + 1);
+        self.reverse(top, bottom + 1);
+    }
+
+    /// Reverse rows `start..end`.
+    fn reverse(&mut self, mut start: usize, mut end: usize) {
+        while start + 1 < end {
+            end -= 1;
+            self.swap(start, end);
+            start += 1;
+        }
+    }
+
+    /// `top..=bottom` as screen rows, if the range lies within the screen.
+    fn screen_range(&self, top: usize, bottom: usize) -> Option<(usize, usize)> {
+        let top = top.checked_sub(self.scrollback.len())?;
+        let bottom = bottom - self.scrollback.len();
+        (bottom < self.screen.len()).then_some((top, bottom))
+    }
+
+    /// Swap two rows, on either side of the scrollback boundary.
+    fn swap(&mut self, a: usize, b: usize) {
+        let split = self.scrollback.len();
+        match (a < split, b < split) {
+            (true, true) => self.scrollback.swap(a, b),
+            (false, false) => self.screen.swap(a - split, b - split),
+            (true, false) => std::mem::swap(&mut self.scrollback[a], &mut self.screen[b - split]),
+            (false, true) => std::mem::swap(&mut self.screen[a - split], &mut self.scrollback[b]),
+        }
+    }
+}
+
+impl Index<usize> for Grid {
+    type Output = Line;
+
+    fn index(&self, row: usize) -> &Line {
+        match row.checked_sub(self.scrollback.len()) {
+            Some(screen_row) => &self.screen[screen_row],
+            None => &self.scrollback[row],
+        }
+    }
+}
+
+impl IndexMut<usize> for Grid {
+    fn index_mut(&mut self, row: usize) -> &mut Line {
+        match row.checked_sub(self.scrollback.len()) {
+            Some(screen_row) => &mut self.screen[screen_row],
+            None => &mut self.scrollback[row],
+        }
+    }
+}
+
+/// Read-only view of all buffer lines, scrollback first.
+#[derive(Debug, Clone, Copy)]
+pub struct LineView<'a> {
+    scrollback: &'a VecDeque<Line>,
+    screen: &'a VecDeque<Line>,
+    styles: &'a StyleTable,
+}
+
+impl<'a> LineView<'a> {
+    pub fn len(&self) -> usize {
+        self.scrollback.len() + self.screen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, row: usize) -> Option<&'a Line> {
+        match row.checked_sub(self.scrollback.len()) {
+            Some(screen_row) => self.screen.get(screen_row),
+            None => self.scrollback.get(row),
+        }
+    }
+
+    /// Styles for resolving the lines' cells.
+    pub fn styles(&self) -> &'a StyleTable {
+        self.styles
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &'a Line> + 'a {
+        self.scrollback.iter().chain(self.screen.iter())
+    }
+}
+
+impl Index<usize> for LineView<'_> {
+    type Output = Line;
+
+    fn index(&self, row: usize) -> &Line {
+        self.get(row).expect("line index out of bounds")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(s: &str) -> Line {
+        let mut line = Line::new();
+        for (i, c) in s.chars().enumerate() {
+            line.set(i, c, StyleId::DEFAULT);
+        }
+        line
+    }
+
+    fn texts(grid: &Grid) -> Vec<String> {
+        grid.view().iter().map(Line::text).collect()
+    }
+
+    fn grid_of(rows: usize, lines: &[&str]) -> Grid {
+        let mut grid = Grid::new(rows, 0);
+        for s in lines {
+            grid.push_back(line(s));
+        }
+        grid
+    }
+
+    #[test]
+    fn full_screen_push_moves_top_line_to_scrollback() {
+        let grid = grid_of(2, &["a", "b", "c"]);
+        assert_eq!(grid.scrollback.len(), 1);
+        assert_eq!(grid.screen.len(), 2);
+        assert_eq!(texts(&grid), ["a", "b", "c"]);
+        assert_eq!(grid[0].text(), "a");
+        assert_eq!(grid.view().get(2).map(Line::text).as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn scrolling_the_screen_rotates_the_ring() {
+        let mut grid = grid_of(3, &["x", "a", "b", "c"]);
+        grid.scroll_up(1, 3, 1);
+        assert_eq!(texts(&grid), ["x", "b", "c", ""]);
+        grid.scroll_down(1, 3, 1);
+        assert_eq!(texts(&grid), ["x", "", "b", "c"]);
+        // Scrollback is untouched by screen scrolling
+        assert_eq!(grid.scrollback.len(), 1);
+    }
+
+    #[test]
+    fn partial_regions_rotate_in_place() {
+        let mut grid = grid_of(5, &["h", "a", "b", "c", "f"]);
+        grid.scroll_up(1, 3, 2);
+        assert_eq!(texts(&grid), ["h", "c", "", "", "f"]);
+        grid.scroll_down(1, 3, 1);
+        assert_eq!(texts(&grid), ["h", "", "c", "", "f"]);
+        // More than the region height blanks it
+        grid.scroll_up(1, 3, 10);
+        assert_eq!(texts(&grid), ["h", "", "", "", "f"]);
+    }
+
+    #[test]
+    fn partial_regions_scroll_across_the_boundary() {
+        let mut grid = grid_of(2, &["a", "b", "c", "d"]);
+        grid.scroll_up(1, 2, 1);
+        assert_eq!(texts(&grid), ["a", "c", "", "d"]);
+        grid.scroll_down(0, 2, 1);
+        assert_eq!(texts(&grid), ["", "a", "c", "d"]);
+        let mut grid = grid_of(2, &["a", "b", "c", "d"]);
+        grid.scroll_up(0, 3, 3);
+        assert_eq!(texts(&grid), ["d", "", "", ""]);
+        let mut grid = grid_of(2, &["a", "b", "c", "d"]);
+        grid.scroll_down(0, 2, 2);
+        assert_eq!(texts(&grid), ["", "", "a", "d"]);
+    }
+
+    #[test]
+    fn whole_screen_scrolls_into_scrollback() {
+        let mut grid = grid_of(2, &["a", "b", "c"]);
+        assert!(grid.spans_screen(1, 2));
+        assert!(!grid.spans_screen(0, 2));
+        grid.scroll_into_scrollback(2);
+        assert_eq!(texts(&grid), ["a", "b", "c", "", ""]);
+        assert_eq!(grid.scrollback.len(), 3);
+    }
+
+    #[test]
+    fn resizing_the_screen_keeps_row_order() {
+        let mut grid = grid_of(2, &["a", "b", "c", "d"]);
+        grid.set_screen_rows(3);
+        assert_eq!(grid.screen.len(), 3);
+        grid.set_screen_rows(1);
+        assert_eq!(grid.screen.len(), 1);
+        assert_eq!(texts(&grid), ["a", "b", "c", "d"]);
+    }
+}
@@ -0,0 +1,477 @@
+use ratatui::style::Style;
+use std::collections::HashMap;
+
+/// Index into a buffer's style table. Id 0 is always `Style::default()`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct StyleId(u32);
+
+impl StyleId {
+    pub const DEFAULT: StyleId = StyleId(0);
+}
+
+/// Distinct styles a table holds before it is compacted.
+const STYLE_TABLE_LIMIT: usize = 1 << 16;
+
+/// Styles interned per buffer. Lines store 4-byte ids, which fit in the
+/// padding of a style run, instead of full 16-byte `Style`s. Once the table
+/// reaches its limit, the buffer compacts it to the styles its lines still
+/// use, so a truecolor animation doesn't grow it forever.
+#[derive(Debug, Clone)]
+pub struct StyleTable {
+    styles: Vec<Style>,
+    ids: HashMap<Style, StyleId>,
+    limit: usize,
+}
+
+impl StyleTable {
+    pub fn new() -> Self {
+        Self::with_limit(STYLE_TABLE_LIMIT)
+    }
+
+    fn with_limit(limit: usize) -> Self {
+        Self {
+            styles: vec![Style::default()],
+            ids: HashMap::from([(Style::default(), StyleId::DEFAULT)]),
+            limit,
+        }
+    }
+
+    /// Intern a style, returning its id; `None` once the table is full.
+    pub fn intern(&mut self, style: Style) -> Option<StyleId> {
+        if let Some(&id) = self.ids.get(&style) {
+            return Some(id);
+        }
+        if self.styles.len() >= self.limit {
+            return None;
+        }
+        let id = StyleId(self.styles.len() as u32);
+        self.styles.push(style);
+        self.ids.insert(style, id);
+        Some(id)
+    }
+
+    /// Look up an interned style.
+    pub fn resolve(&self, id: StyleId) -> Style {
+        self.styles.get(id.0 as usize).copied().unwrap_or_default()
+    }
+
+    /// Approximate heap bytes: the style list plus its lookup map.
+    pub fn heap_bytes(&self) -> usize {
+        self.styles.len() * (2 * std::mem::size_of::<Style>() + std::mem::size_of::<StyleId>())
+    }
+
+    /// Keep only the `live` styles (and the default), renumbered. Returns
+    /// each kept id's new id. The limit grows when most styles are still
+    /// live, so the next intern succeeds.
+    pub fn compact(
+        &mut self,
+        live: impl IntoIterator<Item = StyleId>,
+    ) -> HashMap<StyleId, StyleId> {
+        let mut kept = Self::with_limit(self.limit);
+        let mut remap = HashMap::from([(StyleId::DEFAULT, StyleId::DEFAULT)]);
+        for id in live {
+            if let std::collections::hash_map::Entry::Vacant(entry) = remap.entry(id) {
+                let style = self.resolve(id);
+                kept.styles.push(style);
+                let new_id = StyleId(kept.styles.len() as u32 - 1);
+                kept.ids.insert(style, new_id);
+                entry.insert(new_id);
+            }
+        }
+        kept.limit = self.limit.max(kept.styles.len() * 2);
+        *self = kept;
+        remap
+    }
+}
+
+impl Default for StyleTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A character with its resolved style, as seen by readers of a `Line`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Cell {
+    pub c: char,
+    pub style: Style,
+}
+
+/// Line characters: pure-ASCII lines (the common case for logs) take one
+/// byte per cell and are promoted to `char`s on the first non-ASCII write.
+#[derive(Debug, Clone)]
+enum LineText {
+    Ascii(Vec<u8>),
+    Wide(Vec<char>),
+}
+
+/// Cells from `start` up to the next run (or end of line) share `style`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct StyleRun {
+    start: u32,
+    style: StyleId,
+}
+
+/// One row of terminal output, stored as text plus style runs.
+///
+/// Invariant: `runs` is empty (all cells default-styled) or starts at column 0,
+/// is sorted, has no run at or past `len()`, and no two adjacent runs share a style.
+#[derive(Debug, Clone)]
+pub struct Line {
+    text: LineText,
+    runs: Vec<StyleRun>,
+}
+
+impl Line {
+    pub fn new() -> Self {
+        Self {
+            text: LineText::Ascii(Vec::new()),
+            runs: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.text {
+            LineText::Ascii(b) => b.len(),
+            LineText::Wide(c) => c.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn char_at(&self, col: usize) -> Option<char> {
+        match &self.text {
+            LineText::Ascii(b) => b.get(col).map(|&b| b as char),
+            LineText::Wide(c) => c.get(col).copied(),
+        }
+    }
+
+    #[allow(dead_code)] // Used by tests
+    pub fn style_at(&self, col: usize, styles: &StyleTable) -> Style {
+        styles.resolve(self.style_id_at(col))
+    }
+
+    #[allow(dead_code)] // Used by tests
+    pub fn cell(&self, col: usize, styles: &StyleTable) -> Option<Cell> {
+        self.char_at(col).map(|c| Cell {
+            c,
+            style: self.style_at(col, styles),
+        })
+    }
+
+    /// Iterate characters without resolving styles.
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        (0..self.len()).map(|col| self.char_at(col).unwrap_or(' '))
+    }
+
+    /// Iterate cells, resolving each style run once in the buffer's `styles`.
+    pub fn cells<'a>(&'a self, styles: &'a StyleTable) -> impl Iterator<Item = Cell> + 'a {
+        let mut run = 0;
+        let mut style = Style::default();
+        let mut run_end = 0;
+        (0..self.len()).map(move |col| {
+            if col >= run_end {
+                // Advance to the run containing `col`
+                while run < self.runs.len() && self.runs[run].start as usize <= col {
+                    style = styles.resolve(self.runs[run].style);
+                    run += 1;
+                }
+                run_end = self.runs.get(run).map_or(usize::MAX, |r| r.start as usize);
+            }
+            Cell {
+                c: self.char_at(col).unwrap_or(' '),
+                style,
+            }
+        })
+    }
+
+    /// Plain text of the line.
+    pub fn text(&self) -> String {
+        self.chars().collect()
+    }
+
+    /// Write `c` at `col`, padding with default-styled spaces if needed.
+    pub fn set(&mut self, col: usize, c: char, style: StyleId) {
+        let len = self.len();
+        // Fast path: appending in the current style (typical log output)
+        if col == len && self.runs.last().map_or(StyleId::DEFAULT, |r| r.style) == style {
+            match &mut self.text {
+                LineText::Ascii(b) if c.is_ascii() => {
+                    b.push(c as u8);
+                    return;
+                }
+                LineText::Wide(chars) => {
+                    chars.push(c);
+                    return;
+                }
+                LineText::Ascii(_) => {}
+            }
+        }
+        if col >= len {
+            if col > len {
+                self.push_chars(' ', col - len, StyleId::DEFAULT);
+            }
+            self.push_chars(c, 1, style);
+            return;
+        }
+        self.put_text(col, c);
+        if self.style_id_at(col) != style {
+            self.set_style_range(col, col + 1, style);
+        }
+    }
+
+    /// Shorten the line to `len` cells.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len() {
+            return;
+        }
+        if len == 0 {
+            *self = Line::new();
+            return;
+        }
+        match &mut self.text {
+            LineText::Ascii(b) => b.truncate(len),
+            LineText::Wide(c) => c.truncate(len),
+        }
+        let keep = self.runs.partition_point(|r| (r.start as usize) < len);
+        self.runs.truncate(keep);
+        if self.runs.len() == 1 && self.runs[0].style == StyleId::DEFAULT {
+            self.runs.clear();
+        }
+    }
+
+    /// Release spare capacity, e.g. once the cursor has left the line.
+    pub fn shrink_to_fit(&mut self) {
+        match &mut self.text {
+            LineText::Ascii(b) => b.shrink_to_fit(),
+            LineText::Wide(c) => c.shrink_to_fit(),
+        }
+        self.runs.shrink_to_fit();
+    }
+
+    /// Heap bytes owned by this line (allocated capacity, not just length).
+    pub fn heap_bytes(&self) -> usize {
+        let text = match &self.text {
+            LineText::Ascii(b) => b.capacity(),
+            LineText::Wide(c) => c.capacity() * std::mem::size_of::<char>(),
+        };
+        text + self.runs.capacity() * std::mem::size_of::<StyleRun>()
+    }
+
+    /// Style ids used by the line, for compacting its table.
+    pub fn style_ids(&self) -> impl Iterator<Item = StyleId> + '_ {
+        self.runs.iter().map(|run| run.style)
+    }
+
+    /// Renumber style ids after the table was compacted.
+    pub fn remap_styles(&mut self, remap: &HashMap<StyleId, StyleId>) {
+        for run in &mut self.runs {
+            run.style = remap.get(&run.style).copied().unwrap_or_default();
+        }
+    }
+
+    fn style_id_at(&self, col: usize) -> StyleId {
+        match self.runs.partition_point(|r| r.start as usize <= col) {
+            0 => StyleId::DEFAULT,
+            i => self.runs[i - 1].style,
+        }
+    }
+
+    fn push_chars(&mut self, c: char, n: usize, style: StyleId) {
+        let start = self.len();
+        if !c.is_ascii() {
+            self.promote();
+        }
+        match &mut self.text {
+            LineText::Ascii(b) => b.extend(std::iter::repeat_n(c as u8, n)),
+            LineText::Wide(chars) => chars.extend(std::iter::repeat_n(c, n)),
+        }
+        let last = self.runs.last().map_or(StyleId::DEFAULT, |r| r.style);
+        if last != style {
+            if self.runs.is_empty() && start > 0 {
+                self.runs.push(StyleRun {
+                    start: 0,
+                    style: StyleId::DEFAULT,
+                });
+            }
+            self.runs.push(StyleRun {
+                start: start as u32,
+                style,
+            });
+        }
+    }
+
+    fn put_text(&mut self, col: usize, c: char) {
+        if !c.is_ascii() {
+            self.promote();
+        }
+        match &mut self.text {
+            LineText::Ascii(b) => b[col] = c as u8,
+            LineText::Wide(chars) => chars[col] = c,
+        }
+    }
+
+    fn promote(&mut self) {
+        if let LineText::Ascii(b) = &self.text {
+            self.text = LineText::Wide(b.iter().map(|&b| b as char).collect());
+        }
+    }
+
+    /// Restyle cells `[start, end)` (within the line), keeping runs normalized.
+    fn set_style_range(&mut self, start: usize, end: usize, style: StyleId) {
+        let after = (end < self.len()).then(|| self.style_id_at(end));
+        let from = self.runs.partition_point(|r| (r.start as usize) < start);
+        let to = self.runs.partition_point(|r| (r.start as usize) <= end);
+        let mut replacement = vec![StyleRun {
+            start: start as u32,
+            style,
+        }];
+        if let Some(after) = after {
+            replacement.push(StyleRun {
+                start: end as u32,
+                style: after,
+            });
+        }
+        self.runs.splice(from..to, replacement);
+        if self.runs[0].start != 0 {
+            self.runs.insert(
+                0,
+                StyleRun {
+                    start: 0,
+                    style: StyleId::DEFAULT,
+                },
+            );
+        }
+        self.runs.dedup_by(|next, prev| next.style == prev.style);
+        if self.runs.len() == 1 && self.runs[0].style == StyleId::DEFAULT {
+            self.runs.clear();
+        }
+    }
+}
+
+impl Default for Line {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Color;
+
+    /// A style table with red interned.
+    fn red() -> (StyleTable, StyleId) {
+        let mut styles = StyleTable::new();
+        let red = styles.intern(Style::default().fg(Color::Red)).unwrap();
+        (styles, red)
+    }
+
+    fn line_from(s: &str, style: StyleId) -> Line {
+        let mut line = Line::new();
+        for (i, c) in s.chars().enumerate() {
+            line.set(i, c, style);
+        }
+        line
+    }
+
+    #[test]
+    fn ascii_line_stays_compact() {
+        let line = line_from("hello", StyleId::DEFAULT);
+        assert!(matches!(line.text, LineText::Ascii(_)));
+        assert!(line.runs.is_empty());
+        assert_eq!(line.text(), "hello");
+    }
+
+    #[test]
+    fn non_ascii_promotes_to_chars() {
+        let mut line = line_from("ab", StyleId::DEFAULT);
+        line.set(1, '─', StyleId::DEFAULT);
+        assert!(matches!(line.text, LineText::Wide(_)));
+        assert_eq!(line.text(), "a─");
+    }
+
+    #[test]
+    fn padding_uses_default_style() {
+        let (styles, red) = red();
+        let mut line = Line::new();
+        line.set(3, 'x', red);
+        assert_eq!(line.text(), "   x");
+        assert_eq!(line.style_at(0, &styles), Style::default());
+        assert_eq!(line.style_at(3, &styles).fg, Some(Color::Red));
+    }
+
+    #[test]
+    fn overwrite_splits_and_merges_runs() {
+        let (styles, red) = red();
+        let mut line = line_from("abcde", StyleId::DEFAULT);
+        line.set(2, 'X', red);
+        assert_eq!(line.runs.len(), 3);
+        let fgs: Vec<_> = line.cells(&styles).map(|c| c.style.fg).collect();
+        assert_eq!(fgs, [None, None, Some(Color::Red), None, None]);
+
+        // Restoring the default style merges back to a plain line
+        line.set(2, 'c', StyleId::DEFAULT);
+        assert!(line.runs.is_empty());
+        assert_eq!(line.text(), "abcde");
+    }
+
+    #[test]
+    fn appends_extend_matching_run() {
+        let (styles, red) = red();
+        let mut line = line_from("ab", red);
+        line.set(2, 'c', red);
+        assert_eq!(line.runs.len(), 1);
+        line.set(3, 'd', StyleId::DEFAULT);
+        assert_eq!(line.runs.len(), 2);
+        assert_eq!(line.cell(3, &styles).unwrap().style, Style::default());
+    }
+
+    #[test]
+    fn truncate_drops_runs_past_end() {
+        let (_, red) = red();
+        let mut line = line_from("ab", StyleId::DEFAULT);
+        line.set(2, 'c', red);
+        line.truncate(2);
+        assert!(line.runs.is_empty());
+        assert_eq!(line.text(), "ab");
+    }
+
+    #[test]
+    fn interning_reuses_ids() {
+        let (mut styles, red) = red();
+        assert_eq!(styles.intern(Style::default().fg(Color::Red)), Some(red));
+        assert_eq!(styles.intern(Style::default()), Some(StyleId::DEFAULT));
+        assert_eq!(styles.resolve(red).fg, Some(Color::Red));
+    }
+
+    #[test]
+    fn compacting_keeps_live_styles_and_raises_a_full_limit() {
+        let mut styles = StyleTable::with_limit(4);
+        let ids: Vec<_> = (0..3)
+            .map(|i| styles.intern(Style::default().fg(Color::Indexed(i))))
+            .collect::<Option<_>>()
+            .unwrap();
+        assert_eq!(styles.intern(Style::default().bold()), None);
+
+        let mut line = line_from("ab", ids[2]);
+        line.set(1, 'b', ids[0]);
+        let remap = styles.compact(line.style_ids().collect::<Vec<_>>());
+        line.remap_styles(&remap);
+        let fgs: Vec<_> = line.cells(&styles).map(|c| c.style.fg).collect();
+        assert_eq!(fgs, [Some(Color::Indexed(2)), Some(Color::Indexed(0))]);
+        // Room again for new styles, and the dropped one is gone
+        assert!(styles.intern(Style::default().bold()).is_some());
+        assert!(!styles
+            .ids
+            .contains_key(&Style::default().fg(Color::Indexed(1))));
+
+        // With every style still live, the limit doubles instead
+        let mut styles = StyleTable::with_limit(2);
+        let id = styles.intern(Style::default().italic()).unwrap();
+        styles.compact([id]);
+        assert_eq!(styles.limit, 4);
+    }
+}
@@ -0,0 +1,255 @@
+/// URLs remembered per process; older ones are dropped first.
+pub const MAX_URLS: usize = 16;
+
+/// Characters that can't be part of a URL printed in plain text.
+fn ends_url(c: char) -> bool {
+    c.is_whitespace() || c.is_control() || matches!(c, '"' | '\'' | '<' | '>' | '`' | '|')
+}
+
+/// `http://` and `https://` URLs in a line of output, in order. Trailing
+/// punctuation and unbalanced closing brackets are left out, so
+/// `(see http://localhost:3000).` yields `http://localhost:3000`.
+pub fn find_urls(text: &str) -> Vec<&str> {
+    let mut urls = Vec::new();
+    let mut rest = text;
+    let mut offset = 0;
+    while let Some(start) = ["http://", "https://"]
+        .iter()
+        .filter_map(|scheme| rest.find(scheme))
+        .min()
+    {
+        let from = &rest[start..];
+        let len = from.find(ends_url).unwrap_or(from.len());
+        let url = trim_url(&from[..len]);
+        let scheme_len = if url.starts_with("https") { 8 } else { 7 };
+        if url.len() > scheme_len {
+            urls.push(&text[offset + start..offset + start + url.len()]);
+        }
+        let consumed = start + len.max(1);
+        offset += consumed;
+        rest = &rest[consumed..];
+    }
+    urls
+}
+
+/// Strip trailing punctuation and closing brackets without an opening one.
+pub fn trim_url(mut url: &str) -> &str {
+    loop {
+        let Some(last) = url.chars().last() else {
+            return url;
+        };
+        let unbalanced = |open: char| url.matches(open).count() < url.matches(last).count();
+        let strip = match last {
+            '.' | ',' | ';' | ':' | '!' | '?' => true,
+            ')' => unbalanced('('),
+            ']' => unbalanced('['),
+            '}' => unbalanced('{'),
+            _ => false,
+        };
+        if !strip {
+            return url;
+        }
+        url = &url[..url.len() - last.len_utf8()];
+    }
+}
+
+/// Remember `url`, moving it to the end if it was seen before.
+pub fn remember_url(urls: &mut Vec<String>, url: &str) {
+    urls.retain(|u| u != url);
+    urls.push(url.to_string());
+    if urls.len() > MAX_URLS {
+        urls.remove(0);
+    }
+}
+
+/// Quick links for a process: URLs from its output, newest first, then
+/// `http://localhost:<port>` for listening ports no URL mentions.
+pub fn quick_links(urls: &[String], ports: &[u16]) -> Vec<String> {
+    let mut links: Vec<String> = urls.iter().rev().cloned().collect();
+    for port in ports {
+        let suffix = format!(":{port}");
+        let mentioned = urls.iter().any(|url| {
+            let authority = url.split("://").nth(1).unwrap_or("");
+            let host = authority.split(['/', '?', '#']).next().unwrap_or("");
+            host.ends_with(&suffix)
+        });
+        if !mentioned {
+            links.push(format!("http://localhost{suffix}"));
+        }
+    }
+    links
+}
+
+/// A `path:line[:col]` reference in output, e.g. from a compiler error or
+/// a stack trace. `start..end` are the columns it covers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileRef {
+    pub path: String,
+    pub line: u32,
+    pub col: Option<u32>,
+    pub start: usize,
+    pub end: usize,
+}
+
+fn is_path_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | '\\' | '~' | '@' | '+')
+}
+
+/// Parse a positive number at the start of `chars`, returning it and its length.
+fn number(chars: &[char]) -> Option<(u32, usize)> {
+    let len = chars.iter().take_while(|c| c.is_ascii_digit()).count();
+    let digits: String = chars[..len].iter().collect();
+    digits.parse().ok().filter(|&n| n > 0).map(|n| (n, len))
+}
+
+/// Line and column after a path: `:12`, `:12:5`, `(12)`, `(12,5)` or
+/// Python's `", line 12`. Returns them with the length of the suffix.
+fn position(chars: &[char], quoted: bool) -> Option<(u32, Option<u32>, usize)> {
+    const PYTHON: &str = "\", line ";
+    if quoted && chars.iter().take(PYTHON.len()).copied().eq(PYTHON.chars()) {
+        let (line, len) = number(&chars[PYTHON.len()..])?;
+        return Some((line, None, PYTHON.len() + len));
+    }
+    let (separator, close) = match chars.first()? {
+        ':' => (':', None),
+        '(' => (',', Some(')')),
+        _ => return None,
+    };
+    let (line, line_len) = number(&chars[1..])?;
+    let mut len = 1 + line_len;
+    let mut col = None;
+    if chars.get(len) == Some(&separator) {
+        if let Some((c, col_len)) = number(&chars[len + 1..]) {
+            col = Some(c);
+            len += 1 + col_len;
+        }
+    }
+    if let Some(close) = close {
+        if chars.get(len) != Some(&close) {
+            return None;
+        }
+        len += 1;
+    }
+    Some((line, col, len))
+}
+
+/// File references in a line of output, in order. Paths need a `.` or `/`
+/// to count, and tokens right after a `:` (the host of a URL) are skipped.
+pub fn find_file_refs(text: &str) -> Vec<FileRef> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut refs = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if !is_path_char(chars[i]) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < chars.len() && is_path_char(chars[i]) {
+            i += 1;
+        }
+        let path: String = chars[start..i].iter().collect();
+        let after_colon = start > 0 && chars[start - 1] == ':';
+        let quoted = start > 0 && chars[start - 1] == '"';
+        let looks_like_path = path.contains(['.', '/', '\\'])
+            && path.chars().any(char::is_alphabetic)
+            && !path.starts_with("//");
+        if after_colon || !looks_like_path {
+            continue;
+        }
+        if let Some((line, col, len)) = position(&chars[i..], quoted) {
+            refs.push(FileRef {
+                path,
+                line,
+                col,
+                start,
+                end: i + len,
+            });
+            i += len;
+        }
+    }
+    refs
+}
+
+/// The file reference covering column `col` of `text`, if any.
+pub fn file_ref_at(text: &str, col: usize) -> Option<FileRef> {
+    find_file_refs(text)
+        .into_iter()
+        .find(|r| (r.start..r.end).contains(&col))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls_are_found_without_trailing_punctuation() {
+        assert_eq!(
+            find_urls("  ➜  Local:   http://localhost:5173/"),
+            ["http://localhost:5173/"]
+        );
+        assert_eq!(
+            find_urls("(see http://a.test/x_(y)), then https://b.test."),
+            ["http://a.test/x_(y)", "https://b.test"]
+        );
+        assert_eq!(
+            find_urls("\"https://c.test/?q=1\""),
+            ["https://c.test/?q=1"]
+        );
+        assert!(find_urls("http:// nothing here").is_empty());
+    }
+
+    #[test]
+    fn quick_links_add_ports_no_url_mentions() {
+        let mut urls = Vec::new();
+        remember_url(&mut urls, "http://localhost:5173/");
+        remember_url(&mut urls, "http://192.168.1.2:5173/");
+        remember_url(&mut urls, "http://localhost:5173/");
+        assert_eq!(
+            quick_links(&urls, &[5173, 24678]),
+            [
+                "http://localhost:5173/",
+                "http://192.168.1.2:5173/",
+                "http://localhost:24678"
+            ]
+        );
+    }
+
+    #[test]
+    fn file_refs_from_compilers_and_stack_traces() {
+        let refs = |text| {
+            find_file_refs(text)
+                .into_iter()
+                .map(|r| (r.path, r.line, r.col))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            refs("  --> src/main.rs:42:7"),
+            [("src/main.rs".into(), 42, Some(7))]
+        );
+        assert_eq!(
+            refs("    at handler (/app/server.js:10:15)"),
+            [("/app/server.js".into(), 10, Some(15))]
+        );
+        assert_eq!(
+            refs(r#"  File "app/views.py", line 88, in index"#),
+            [("app/views.py".into(), 88, None)]
+        );
+        assert_eq!(
+            refs("src/App.tsx(12,5): error TS2322"),
+            [("src/App.tsx".into(), 12, Some(5))]
+        );
+        // Hosts, ports and versions aren't files
+        assert!(refs("listening on http://localhost:3000/api").is_empty());
+        assert!(refs("127.0.0.1:8080 and 1.2.3").is_empty());
+    }
+
+    #[test]
+    fn file_ref_at_covers_path_and_position() {
+        let text = "error at lib/a.rs:3:1 and lib/b.rs:9";
+        assert_eq!(file_ref_at(text, 9).unwrap().path, "lib/a.rs");
+        assert_eq!(file_ref_at(text, 20).unwrap().path, "lib/a.rs");
+        assert_eq!(file_ref_at(text, 21), None);
+        assert_eq!(file_ref_at(text, 35).unwrap().line, 9);
+    }
+}
@@ -1,14 +1,67 @@
-use super::{PtyHandle, TerminalBuffer};
-use crate::config::{ProcessConfig, ProcessStatus};
+use super::links;
+use super::metrics::{self, GroupMetrics, MetricsSampler};
+use super::pty::{shell_invocation, ExitInfo};
+use super::tree::{self, ProcessTree, TreeEntry};
+use super::{PtyHandle, TerminalBuffer, WrapIndex};
+use crate::config::{free_port, port_is_free, Hook, Port, ProcessConfig, ProcessStatus, Signal};
 use crate::event::{AppEvent, Generation};
 use anyhow::Result;
 use chrono::Local;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::io::Read;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
+/// How long to wait for the child to be reaped after SIGKILL before giving up on it.
+const KILL_GRACE: Duration = Duration::from_millis(1000);
+
+/// Reader thread buffer size. A busy child fills it in one read, so a flood of
+/// output becomes a few large events instead of thousands of small ones.
+const READ_CHUNK: usize = 64 * 1024;
+
+/// How often listening ports are rescanned for the badge and links popup.
+const PORT_SCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+/// What to do once a stopping process has exited.
+#[derive(Debug, Clone, PartialEq)]
+enum AfterStop {
+    Nothing,
+    /// Restart, with the marker timestamp taken when the restart was requested
+    Restart(Option<String>),
+    /// Drop the process; it already left `process_order`
+    Remove,
+}
+
+/// An in-flight stop: SIGTERM has been sent, SIGKILL follows at `kill_at`.
+#[derive(Debug)]
+struct StopRequest {
+    kill_at: Instant,
+    killed: bool,
+    then: AfterStop,
+}
+
+/// Extra environment for a hook, on top of name, generation and hook name.
+type HookEnv = Vec<(&'static str, String)>;
+
+/// Work queued behind the running hook.
+#[derive(Debug)]
+enum Step {
+    Hook(Hook, HookEnv),
+    /// Spawn the process's own command
+    Start,
+}
+
+/// A lifecycle hook running in its own PTY.
+struct HookRun {
+    hook: Hook,
+    pty: PtyHandle,
+    shutdown: Arc<AtomicBool>,
+}
+
 pub struct ManagedProcess {
     pub config: ProcessConfig,
     pub status: ProcessStatus,
@@ -17,41 +70,245 @@ pub struct ManagedProcess {
     pub scroll_offset: usize,
     pub auto_scroll: bool,
     pub wrap_enabled: bool,
+    /// Display-row index for wrap mode; kept in sync while wrapping is on
+    pub wrap_index: WrapIndex,
     pub generation: Generation,
+    /// Latest CPU/memory sample of the process group (None until sampled)
+    pub metrics: Option<GroupMetrics>,
+    /// Port handed to the process: its fixed `port`, or the free one picked
+    /// for `port = "auto"`
+    pub port: Option<u16>,
+    /// TCP ports the process group listens on, as of the last port scan
+    pub listening: Vec<u16>,
+    /// URLs seen in the output since the last start, oldest first
+    pub urls: Vec<String>,
+    /// Working directory of the last start, with `${VAR}`s expanded
+    pub cwd: Option<PathBuf>,
     shutdown: Arc<AtomicBool>,
+    stop: Option<StopRequest>,
+    /// Output written since the last `flush_output`
+    output_pending: bool,
+    hook: Option<HookRun>,
+    /// Generation of the latest hook; its output is accepted until the next
+    /// hook starts, so the tail read after the hook exited still shows up
+    hook_generation: Generation,
+    /// Hooks and the start waiting for `hook` to finish, in order
+    steps: VecDeque<Step>,
+    /// `on_ready` runs once output matches `ready_pattern`
+    awaiting_ready: bool,
 }
 
 impl ManagedProcess {
+    /// Whether `restart_all` includes this process: everything except
+    /// stopped processes that don't autostart.
+    fn restarts_with_all(&self) -> bool {
+        self.config.autostart || self.status.is_alive()
+    }
+
+    /// Scroll so the last line of content sits at the bottom of the viewport.
+    fn scroll_to_bottom(&mut self, visible: usize) {
+        let content_count = self.buffer.content_line_count();
+        let total_display_lines = if self.wrap_enabled {
+            self.wrap_index.total_rows(content_count)
+        } else {
+            content_count
+        };
+        self.scroll_offset = total_display_lines.saturating_sub(visible);
+    }
+
+    /// Bring the wrap index up to date with buffer changes at `width`.
+    /// Cheap when nothing changed; a new width rebuilds it. Call before
+    /// reading `wrap_index` outside of `flush_output`.
+    pub fn sync_wrap(&mut self, width: usize) {
+        if self.wrap_enabled {
+            let changes = self.buffer.take_line_changes();
+            self.wrap_index
+                .sync(self.buffer.get_all_lines(), changes, width);
+        }
+    }
+
+    /// Turn wrapping on or off. The index is only kept while wrapping.
+    fn set_wrap(&mut self, enabled: bool, width: usize) {
+        self.wrap_enabled = enabled;
+        self.wrap_index = if enabled {
+            self.buffer.take_line_changes();
+            WrapIndex::new(self.buffer.get_all_lines(), width)
+        } else {
+            WrapIndex::default()
+        };
+    }
+
+    /// Drop up to `n` of the oldest buffer lines, keeping the scroll
+    /// position on the same content. Returns how many lines were removed.
+    fn evict_oldest(&mut self, n: usize, viewport_width: usize) -> usize {
+        self.sync_wrap(viewport_width);
+        let removed = self.buffer.evict_oldest(n);
+        // Display rows the removed lines occupied (wrap mode may use several each)
+        let display_rows = if self.wrap_enabled {
+            self.wrap_index.row_start(removed)
+        } else {
+            removed
+        };
+        self.sync_wrap(viewport_width);
+        self.scroll_offset = self.scroll_offset.saturating_sub(display_rows);
+        removed
+    }
+
+    /// Write a dimmed panex message on a line of its own.
+    fn note(&mut self, text: &str) {
+        let newline = if self.buffer.cursor_position().1 > 0 {
+            "\r\n"
+        } else {
+            ""
+        };
+        let line = format!("{newline}\x1b[m[panex] {text}\r\n");
+        self.buffer.write_dimmed(line.as_bytes());
+        self.output_pending = true;
+    }
+
+    /// Queue a hook if the process has it set.
+    fn queue_hook(&mut self, hook: Hook, env: HookEnv) {
+        if self.config.hook(hook).is_some() {
+            self.steps.push_back(Step::Hook(hook, env));
+        }
+    }
+
+    /// Queue `on_failure` (for a failed exit) and `after_exit` once the
+    /// process's own command has exited.
+    fn queue_exit_hooks(&mut self, exit: &ExitInfo, failed: bool) {
+        let env = vec![
+            ("PANEX_EXIT_CODE", exit.code.to_string()),
+            ("PANEX_SIGNAL", exit.signal.clone().unwrap_or_default()),
+        ];
+        if failed {
+            self.queue_hook(Hook::OnFailure, env.clone());
+        }
+        self.queue_hook(Hook::AfterExit, env);
+    }
+
+    fn start_pending(&self) -> bool {
+        self.steps.iter().any(|step| matches!(step, Step::Start))
+    }
+
+    /// Drop a start that is still waiting for hooks, killing `before_start`
+    /// if it is running.
+    fn cancel_start(&mut self) {
+        if !self.start_pending() {
+            return;
+        }
+        self.steps
+            .retain(|step| !matches!(step, Step::Start | Step::Hook(Hook::BeforeStart, _)));
+        if let Some(run) = self.hook.as_ref().filter(|r| r.hook == Hook::BeforeStart) {
+            let _ = run.pty.force_kill();
+        }
+    }
+
+    /// Whether a line from buffer row `from` on matches `ready_pattern`.
+    fn output_ready(&self, from: usize) -> bool {
+        let Some(pattern) = self.config.ready_pattern.as_deref() else {
+            return false;
+        };
+        let lines = self.buffer.get_all_lines();
+        (from.min(lines.len())..lines.len()).any(|row| lines[row].text().contains(pattern))
+    }
+
+    /// Remember URLs on the lines the cursor left, from buffer row `from`
+    /// up to the cursor's row. The cursor's own line may still be growing.
+    fn scan_urls(&mut self, from: usize) {
+        let lines = self.buffer.get_all_lines();
+        let to = self.buffer.cursor_position().0.min(lines.len());
+        for row in from.min(to)..to {
+            for url in links::find_urls(&lines[row].text()) {
+                links::remember_url(&mut self.urls, url);
+            }
+        }
+    }
+
+    /// URLs and listening ports to offer in the links popup.
+    pub fn quick_links(&self) -> Vec<String> {
+        links::quick_links(&self.urls, &self.listening)
+    }
+
+    /// Store a new metrics sample, carrying the peak RSS over. A process
+    /// without a sample (exited) keeps only its peak.
+    fn update_metrics(&mut self, sample: Option<GroupMetrics>) {
+        let peak_rss = self.metrics.as_ref().map_or(0, |m| m.peak_rss);
+        self.metrics = match sample {
+            Some(mut sample) => {
+                sample.peak_rss = peak_rss.max(sample.rss);
+                Some(sample)
+            }
+            None => self.metrics.take().map(|_| GroupMetrics {
+                peak_rss,
+                ..GroupMetrics::default()
+            }),
+        };
+    }
+
     pub fn new(config: ProcessConfig, cols: usize, rows: usize, max_scrollback: usize) -> Self {
         let wrap_enabled = config.wrap_enabled;
-        Self {
+        let mut process = Self {
             config,
             status: ProcessStatus::Stopped,
             buffer: TerminalBuffer::with_max_scrollback(cols, rows, max_scrollback),
             pty: None,
             scroll_offset: 0,
             auto_scroll: true,
-            wrap_enabled,
+            wrap_enabled: false,
+            wrap_index: WrapIndex::default(),
             generation: 0,
+            metrics: None,
+            port: None,
+            listening: Vec::new(),
+            urls: Vec::new(),
+            cwd: None,
             shutdown: Arc::new(AtomicBool::new(false)),
-        }
+            stop: None,
+            output_pending: false,
+            hook: None,
+            hook_generation: 0,
+            steps: VecDeque::new(),
+            awaiting_ready: false,
+        };
+        process.set_wrap(wrap_enabled, cols);
+        process
     }
 }
 
 pub struct ProcessManager {
     processes: HashMap<String, ManagedProcess>,
     process_order: Vec<String>,
-    event_tx: mpsc::UnboundedSender<AppEvent>,
+    event_tx: mpsc::Sender<AppEvent>,
     cols: u16,
     rows: u16,
     timeout: u64,
     buffer_size: usize,
     show_restart_marker: bool,
+    /// Global scrollback memory cap in bytes (None = unlimited).
+    memory_limit: Option<usize>,
+    metrics_sampler: MetricsSampler,
+    tree: ProcessTree,
+    /// Escaped descendants to stop along with everything else on quit
+    escaped_to_kill: Vec<TreeEntry>,
+    /// Running `stop_command`s, reaped from `tick`
+    stop_commands: Vec<Child>,
+    /// Last generation handed out. Generations are unique across processes,
+    /// so events from a removed process never match a new one of the same name.
+    last_generation: Generation,
+    /// Current name of renamed processes by generation; reader threads keep
+    /// sending events under the name the process was started with
+    renamed: HashMap<Generation, String>,
+    /// `.env` files read by every process, before its own `env_file`s
+    env_files: Vec<PathBuf>,
+    last_port_scan: Option<Instant>,
 }
 
+/// Lines dropped per eviction step when over the memory limit.
+const EVICT_CHUNK: usize = 64;
+
 impl ProcessManager {
     pub fn new(
-        event_tx: mpsc::UnboundedSender<AppEvent>,
+        event_tx: mpsc::Sender<AppEvent>,
         cols: u16,
         rows: u16,
         timeout: u64,
@@ -67,10 +324,70 @@ impl ProcessManager {
             timeout,
             buffer_size,
             show_restart_marker,
+            memory_limit: None,
+            metrics_sampler: MetricsSampler::new(),
+            tree: ProcessTree::default(),
+            escaped_to_kill: Vec::new(),
+            stop_commands: Vec::new(),
+            last_generation: 0,
+            renamed: HashMap::new(),
+            env_files: Vec::new(),
+            last_port_scan: None,
+        }
+    }
+
+    pub fn set_memory_limit(&mut self, limit: Option<usize>) {
+        self.memory_limit = limit;
+    }
+
+    pub fn set_env_files(&mut self, env_files: Vec<PathBuf>) {
+        self.env_files = env_files;
+    }
+
+    pub fn env_files(&self) -> &[PathBuf] {
+        &self.env_files
+    }
+
+    /// Total scrollback memory across all processes.
+    pub fn memory_usage(&self) -> usize {
+        self.processes
+            .values()
+            .map(|p| p.buffer.memory_usage())
+            .sum()
+    }
+
+    /// Evict oldest lines until under the memory limit, always taking from the
+    /// largest buffer so a chatty process can't push out a quiet one's history.
+    pub fn enforce_memory_limit(&mut self) {
+        let Some(limit) = self.memory_limit else {
+            return;
+        };
+        let mut total = self.memory_usage();
+        let cols = self.cols as usize;
+        let mut exhausted: Vec<String> = Vec::new();
+        while total > limit {
+            let Some(process) = self
+                .processes
+                .values_mut()
+                .filter(|p| !exhausted.contains(&p.config.name))
+                .max_by_key(|p| p.buffer.memory_usage())
+            else {
+                break;
+            };
+            let before = process.buffer.memory_usage();
+            if process.evict_oldest(EVICT_CHUNK, cols) == 0 {
+                exhausted.push(process.config.name.clone());
+                continue;
+            }
+            total = total - before + process.buffer.memory_usage();
         }
     }
 
     pub fn add_process(&mut self, config: ProcessConfig) {
+        self.place_process(config, self.process_order.len());
+    }
+
+    fn place_process(&mut self, config: ProcessConfig, index: usize) {
         let name = config.name.clone();
         let process = ManagedProcess::new(
             config,
@@ -79,9 +396,12 @@ impl ProcessManager {
             self.buffer_size,
         );
         self.processes.insert(name.clone(), process);
-        self.process_order.push(name);
+        let index = index.min(self.process_order.len());
+        self.process_order.insert(index, name);
     }
 
+    /// Start a process. With a `before_start` hook, or hooks still running
+    /// from its last exit, it waits as Starting and is spawned once they are done.
     pub fn start_process(&mut self, name: &str) -> Result<()> {
         let process = self
             .processes
@@ -91,103 +411,253 @@ impl ProcessManager {
         // Stop existing reader thread
         process.shutdown.store(true, Ordering::SeqCst);
 
-        // Increment generation so old events are ignored
-        process.generation += 1;
-        let generation = process.generation;
+        // New generation so old events are ignored
+        self.renamed.remove(&process.generation);
+        self.last_generation += 1;
+        process.generation = self.last_generation;
+        process.metrics = None;
+        process.status = ProcessStatus::Starting;
+        process.stop = None;
+
+        if process.hook.is_some()
+            || !process.steps.is_empty()
+            || process.config.before_start.is_some()
+        {
+            process.queue_hook(Hook::BeforeStart, Vec::new());
+            process.steps.push_back(Step::Start);
+            self.run_steps(name);
+            return Ok(());
+        }
+        self.spawn_process(name)
+            .inspect_err(|e| self.start_failed(name, &e.to_string()))
+    }
 
-        let pty = PtyHandle::spawn(&process.config.command, self.cols, self.rows)?;
-        process.pty = Some(pty);
-        process.status = ProcessStatus::Running;
+    /// Spawn the process's own command under its current generation.
+    fn spawn_process(&mut self, name: &str) -> Result<()> {
+        let process = self
+            .processes
+            .get_mut(name)
+            .ok_or_else(|| anyhow::anyhow!("Process not found: {}", name))?;
+        let generation = process.generation;
+        let config = self.resolve_config(name, true)?;
+        let process = self
+            .processes
+            .get_mut(name)
+            .ok_or_else(|| anyhow::anyhow!("Process not found: {}", name))?;
+        let env = spawn_env(&config);
+        process.urls.clear();
+        process.cwd = config.cwd.clone();
+        let pty = PtyHandle::spawn(
+            &config.command,
+            self.cols,
+            self.rows,
+            &env,
+            config.cwd.as_deref(),
+        )?;
         process.shutdown = Arc::new(AtomicBool::new(false));
+        process.awaiting_ready =
+            process.config.on_ready.is_some() && process.config.ready_pattern.is_some();
+        spawn_reader(
+            self.event_tx.clone(),
+            name.to_string(),
+            generation,
+            pty.get_reader(),
+            Arc::clone(&process.shutdown),
+        );
+        process.pty = Some(pty);
+        Ok(())
+    }
 
-        // Spawn reader thread
-        let reader = process.pty.as_ref().unwrap().get_reader();
-        let tx = self.event_tx.clone();
-        let proc_name = name.to_string();
-        let shutdown = Arc::clone(&process.shutdown);
+    /// A process's config as it is run (`ProcessConfig::resolve`), with the
+    /// ports of all processes for `${<name>.port}`. `starting` is set for
+    /// the process's own command, which gets a new port if its old one has
+    /// been taken.
+    fn resolve_config(&mut self, name: &str, starting: bool) -> Result<ProcessConfig> {
+        self.assign_ports(starting.then_some(name))?;
+        let ports: HashMap<String, u16> = self
+            .processes
+            .iter()
+            .filter_map(|(name, p)| Some((name.clone(), p.port?)))
+            .collect();
+        let process = self
+            .processes
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Process not found: {}", name))?;
+        process.config.resolve(&self.env_files, &ports)
+    }
 
-        std::thread::spawn(move || {
-            let mut buf = [0u8; 4096];
-            loop {
-                if shutdown.load(Ordering::SeqCst) {
-                    break;
-                }
+    /// Give every listed process with a `port` setting its port. Picked
+    /// ports are kept across restarts, unless `starting` finds its own in
+    /// use by something else.
+    fn assign_ports(&mut self, starting: Option<&str>) -> Result<()> {
+        for name in &self.process_order {
+            let Some(process) = self.processes.get(name) else {
+                continue;
+            };
+            let port = match process.config.port {
+                None => None,
+                Some(Port::Fixed(port)) => Some(port),
+                Some(Port::Auto) => match process.port {
+                    Some(port) if starting != Some(name.as_str()) || port_is_free(port) => {
+                        Some(port)
+                    }
+                    _ => {
+                        let taken: Vec<u16> =
+                            self.processes.values().filter_map(|p| p.port).collect();
+                        Some(free_port(&taken)?)
+                    }
+                },
+            };
+            if let Some(process) = self.processes.get_mut(name) {
+                process.port = port;
+            }
+        }
+        Ok(())
+    }
 
-                let mut reader_guard = match reader.lock() {
-                    Ok(g) => g,
-                    Err(_) => break,
-                };
+    /// Mark a process Failed after its start failed, running `on_failure`.
+    fn start_failed(&mut self, name: &str, error: &str) {
+        if let Some(process) = self.processes.get_mut(name) {
+            process.status = ProcessStatus::Failed(error.to_string());
+            process.queue_hook(Hook::OnFailure, Vec::new());
+            self.run_steps(name);
+        }
+    }
 
-                match reader_guard.read(&mut buf) {
-                    Ok(0) => {
-                        drop(reader_guard);
-                        let _ =
-                            tx.send(AppEvent::ProcessExited(proc_name.clone(), generation, None));
-                        break;
-                    }
-                    Ok(n) => {
-                        drop(reader_guard);
-                        let _ = tx.send(AppEvent::ProcessOutput(
-                            proc_name.clone(),
-                            generation,
-                            buf[..n].to_vec(),
-                        ));
+    /// Run queued steps until one is a hook that keeps running (or none are left).
+    fn run_steps(&mut self, name: &str) {
+        loop {
+            let Some(process) = self.processes.get_mut(name) else {
+                return;
+            };
+            if process.hook.is_some() {
+                return;
+            }
+            let Some(step) = process.steps.pop_front() else {
+                return;
+            };
+            let (hook, mut env) = match step {
+                Step::Start => {
+                    if let Err(e) = self.spawn_process(name) {
+                        self.start_failed(name, &e.to_string());
                     }
-                    Err(e) => {
-                        drop(reader_guard);
-                        if !shutdown.load(Ordering::SeqCst) {
-                            let _ = tx.send(AppEvent::ProcessError(
-                                proc_name.clone(),
-                                generation,
-                                e.to_string(),
-                            ));
-                        }
-                        break;
+                    continue;
+                }
+                Step::Hook(hook, env) => (hook, env),
+            };
+            let Some(command) = process.config.hook(hook).map(str::to_string) else {
+                continue;
+            };
+            self.last_generation += 1;
+            let generation = self.last_generation;
+            process.note(&format!("{}: {command}", hook.name()));
+            env.extend([
+                ("PANEX_NAME", name.to_string()),
+                ("PANEX_GENERATION", process.generation.to_string()),
+                ("PANEX_HOOK", hook.name().to_string()),
+            ]);
+            let (cols, rows) = (self.cols, self.rows);
+            let spawned = self.resolve_config(name, false).and_then(|config| {
+                let mut env_with_config = spawn_env(&config);
+                env_with_config.extend(env);
+                let command = config.hook(hook).unwrap_or_default();
+                PtyHandle::spawn(command, cols, rows, &env_with_config, config.cwd.as_deref())
+            });
+            let Some(process) = self.processes.get_mut(name) else {
+                return;
+            };
+            match spawned {
+                Ok(pty) => {
+                    let shutdown = Arc::new(AtomicBool::new(false));
+                    spawn_reader(
+                        self.event_tx.clone(),
+                        name.to_string(),
+                        generation,
+                        pty.get_reader(),
+                        Arc::clone(&shutdown),
+                    );
+                    process.hook_generation = generation;
+                    process.hook = Some(HookRun {
+                        hook,
+                        pty,
+                        shutdown,
+                    });
+                }
+                Err(e) => {
+                    process.note(&format!("{} failed to start: {e}", hook.name()));
+                    if hook == Hook::BeforeStart && process.start_pending() {
+                        process.cancel_start();
+                        self.start_failed(name, &format!("before_start failed: {e}"));
                     }
                 }
             }
-        });
+        }
+    }
 
-        let _ = self
-            .event_tx
-            .send(AppEvent::ProcessStarted(name.to_string()));
-        Ok(())
+    /// A hook exited. A failed `before_start` cancels the start it guarded.
+    fn hook_finished(&mut self, name: &str, hook: Hook, exit: ExitInfo) {
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        if exit.code != 0 {
+            process.note(&format!("{} exited with {}", hook.name(), exit.code));
+            if hook == Hook::BeforeStart && process.start_pending() {
+                process.cancel_start();
+                self.start_failed(name, &format!("before_start exited with {}", exit.code));
+            }
+        }
+        self.run_steps(name);
     }
 
+    /// Start every process with `autostart` set.
     pub fn start_all(&mut self) -> Result<()> {
         let names: Vec<_> = self.process_order.clone();
         for name in names {
-            self.start_process(&name)?;
+            if self.processes[&name].config.autostart {
+                self.start_process(&name)?;
+            }
         }
         Ok(())
     }
 
+    /// Restart a process. A live process is stopped first (non-blocking);
+    /// the new instance starts from `tick` once the old one has exited.
     pub fn restart_process(&mut self, name: &str) -> Result<()> {
         let timestamp = self.show_restart_marker.then(Self::restart_timestamp);
-        self.kill_process(name)?;
-        // Generation counter ensures old events are ignored, minimal delay needed
-        std::thread::sleep(std::time::Duration::from_millis(50));
-        self.apply_restart_output_action(name, timestamp.as_deref())?;
-        self.start_process(name)
+        self.restart_with_timestamp(name, timestamp)
     }
 
+    /// Restart every process, except stopped ones that don't autostart.
     pub fn restart_all(&mut self) -> Result<()> {
-        let names: Vec<_> = self.process_order.clone();
         let timestamp = self.show_restart_marker.then(Self::restart_timestamp);
-        // Kill all first
-        for name in &names {
-            let _ = self.kill_process(name);
+        for name in self.process_order.clone() {
+            if self.processes[&name].restarts_with_all() {
+                self.restart_with_timestamp(&name, timestamp.clone())?;
+            }
         }
-        // Brief delay for cleanup
-        std::thread::sleep(std::time::Duration::from_millis(50));
-        for name in &names {
+        Ok(())
+    }
+
+    /// Number of processes `restart_all` would restart.
+    pub fn restart_all_count(&self) -> usize {
+        self.processes
+            .values()
+            .filter(|p| p.restarts_with_all())
+            .count()
+    }
+
+    fn restart_with_timestamp(&mut self, name: &str, timestamp: Option<String>) -> Result<()> {
+        let process = self
+            .processes
+            .get_mut(name)
+            .ok_or_else(|| anyhow::anyhow!("Process not found: {}", name))?;
+        if process.status.is_alive() {
+            self.stop_process(name, AfterStop::Restart(timestamp));
+            Ok(())
+        } else {
             self.apply_restart_output_action(name, timestamp.as_deref())?;
+            self.start_process(name)
         }
-        // Start all - generation counter ensures old events are ignored
-        for name in names {
-            self.start_process(&name)?;
-        }
-        Ok(())
     }
 
     fn restart_timestamp() -> String {
@@ -207,23 +677,27 @@ impl ProcessManager {
     }
 
     fn clear_restart_output(&mut self, name: &str) -> Result<()> {
+        let cols = self.cols as usize;
         let process = self
             .processes
             .get_mut(name)
             .ok_or_else(|| anyhow::anyhow!("Process not found: {}", name))?;
         process.buffer.clear_for_restart();
+        process.sync_wrap(cols);
         process.scroll_offset = 0;
         process.auto_scroll = true;
         Ok(())
     }
 
     fn append_restart_marker(&mut self, name: &str, timestamp: &str) -> Result<()> {
+        let cols = self.cols as usize;
         let process = self
             .processes
             .get_mut(name)
             .ok_or_else(|| anyhow::anyhow!("Process not found: {}", name))?;
         let marker = Self::restart_marker(timestamp);
         process.buffer.write(marker.as_bytes());
+        process.sync_wrap(cols);
         Ok(())
     }
 
@@ -240,18 +714,314 @@ impl ProcessManager {
         )
     }
 
+    /// Stop a process: its stop signal (or stop command) now, SIGKILL from
+    /// `tick` if it outlives the timeout.
     pub fn kill_process(&mut self, name: &str) -> Result<()> {
-        if let Some(process) = self.processes.get_mut(name) {
-            process.shutdown.store(true, Ordering::SeqCst);
-            if let Some(ref pty) = process.pty {
-                let _ = pty.kill(self.timeout);
+        self.stop_process(name, AfterStop::Nothing);
+        Ok(())
+    }
+
+    fn stop_process(&mut self, name: &str, then: AfterStop) {
+        let timeout = Duration::from_millis(self.timeout);
+        let has_stop_command = self
+            .processes
+            .get(name)
+            .is_some_and(|p| p.pty.is_some() && p.config.stop_command.is_some());
+        let resolved = has_stop_command.then(|| self.resolve_config(name, false));
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        let Some(ref pty) = process.pty else {
+            // Not spawned yet (waiting for hooks): cancel the start instead
+            process.cancel_start();
+            process.status = ProcessStatus::Stopped;
+            match then {
+                AfterStop::Nothing => {}
+                AfterStop::Restart(timestamp) => {
+                    if self
+                        .apply_restart_output_action(name, timestamp.as_deref())
+                        .is_ok()
+                    {
+                        let _ = self.start_process(name);
+                    }
+                }
+                AfterStop::Remove => self.drop_process(name),
             }
+            return;
+        };
+        match process.stop {
+            // Already stopping: keep the original deadline, update the follow-up
+            Some(ref mut stop) => stop.then = then,
+            None => {
+                let spawned = match resolved {
+                    Some(Ok(config)) => config.stop_command.as_deref().map(|command| {
+                        spawn_stop_command(command, &config, pty.pid()).map_err(Into::into)
+                    }),
+                    // A bad env file leaves nothing to run: say so, then signal
+                    Some(Err(e)) => Some(Err(e)),
+                    None => None,
+                };
+                let failed = match spawned {
+                    Some(Ok(child)) => {
+                        self.stop_commands.push(child);
+                        None
+                    }
+                    // No stop command, or it couldn't run
+                    failed => {
+                        let _ = pty.signal(process.config.stop_signal);
+                        failed.and_then(Result::err)
+                    }
+                };
+                // A stopped process can't act on the signal until it is resumed
+                if process.status == ProcessStatus::Paused {
+                    let _ = pty.signal(Signal::Cont);
+                }
+                if let Some(e) = failed {
+                    process.note(&format!("stop command failed: {e}"));
+                }
+                process.status = ProcessStatus::Stopping;
+                process.stop = Some(StopRequest {
+                    kill_at: Instant::now() + timeout,
+                    killed: false,
+                    then,
+                });
+            }
+        }
+    }
+
+    /// Advance process lifecycles: reap exited children, escalate overdue
+    /// stops to SIGKILL and start pending restarts. Called from the event loop.
+    /// Returns whether any process changed status.
+    pub fn tick(&mut self) -> bool {
+        self.stop_commands
+            .retain_mut(|child| matches!(child.try_wait(), Ok(None)));
+        let now = Instant::now();
+        let mut changed = false;
+        let mut restarts = Vec::new();
+        let mut removed = Vec::new();
+        let mut finished_hooks = Vec::new();
+        for (name, process) in self.processes.iter_mut() {
+            if let Some(exit) = process.hook.as_ref().and_then(|run| run.pty.try_wait()) {
+                let run = process.hook.take().unwrap();
+                finished_hooks.push((name.clone(), run.hook, exit));
+            }
+            let Some(ref pty) = process.pty else {
+                continue;
+            };
+            let exit = pty.try_wait();
+            if exit.is_none() {
+                let Some(stop) = process.stop.as_mut() else {
+                    continue;
+                };
+                if !stop.killed {
+                    if now >= stop.kill_at {
+                        let _ = pty.force_kill();
+                        stop.killed = true;
+                    }
+                    continue;
+                }
+                if now < stop.kill_at + KILL_GRACE {
+                    continue;
+                }
+                // Killed but never reaped (e.g. stuck in uninterruptible I/O): give up on it
+            }
+
             process.pty = None;
-            process.status = ProcessStatus::Stopped;
+            process.awaiting_ready = false;
+            changed = true;
+            // Given up on after SIGKILL: report it as killed
+            let exit = exit.unwrap_or(ExitInfo {
+                code: 137,
+                signal: Some("Killed".to_string()),
+            });
+            match process.stop.take() {
+                Some(stop) => {
+                    process.status = ProcessStatus::Stopped;
+                    match stop.then {
+                        AfterStop::Nothing => process.queue_exit_hooks(&exit, false),
+                        AfterStop::Restart(timestamp) => {
+                            process.queue_exit_hooks(&exit, false);
+                            restarts.push((name.clone(), timestamp));
+                        }
+                        // Removed processes have nowhere left to show hook output
+                        AfterStop::Remove => removed.push(name.clone()),
+                    }
+                }
+                None => {
+                    process.status = ProcessStatus::Exited(exit.code);
+                    process.queue_exit_hooks(&exit, exit.code != 0 || exit.signal.is_some());
+                }
+            }
+        }
+        for name in removed {
+            self.drop_process(&name);
+        }
+        changed |= !finished_hooks.is_empty();
+        for (name, hook, exit) in finished_hooks {
+            self.hook_finished(&name, hook, exit);
+        }
+        for (name, timestamp) in restarts {
+            if self
+                .apply_restart_output_action(&name, timestamp.as_deref())
+                .is_ok()
+            {
+                let _ = self.start_process(&name);
+            }
+        }
+        let waiting: Vec<String> = self
+            .processes
+            .iter()
+            .filter(|(_, p)| p.hook.is_none() && !p.steps.is_empty())
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in waiting {
+            self.run_steps(&name);
+        }
+        changed
+    }
+
+    /// Names must be unique and can't be blank or contain whitespace, which
+    /// would break the prompt's argument parsing.
+    fn check_new_name(&self, name: &str) -> Result<()> {
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            anyhow::bail!("invalid process name {name:?}");
+        }
+        if self.processes.contains_key(name) {
+            anyhow::bail!("{name} already exists");
+        }
+        Ok(())
+    }
+
+    /// Add a process at `index` in the list and start it.
+    pub fn insert_process(&mut self, config: ProcessConfig, index: usize) -> Result<()> {
+        self.check_new_name(&config.name)?;
+        let name = config.name.clone();
+        self.place_process(config, index);
+        self.start_process(&name)
+    }
+
+    /// Start a copy of a process right below it, named `<name>-2`, `<name>-3`, …
+    /// Returns the copy's name.
+    pub fn duplicate_process(&mut self, name: &str) -> Result<String> {
+        let process = self
+            .processes
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Process not found: {}", name))?;
+        let mut config = process.config.clone();
+        config.name = (2..)
+            .map(|n| format!("{name}-{n}"))
+            .find(|n| !self.processes.contains_key(n))
+            .unwrap_or_default();
+        config.wrap_enabled = process.wrap_enabled;
+        let index = self.position(name).map_or(usize::MAX, |i| i + 1);
+        let copy = config.name.clone();
+        self.insert_process(config, index)?;
+        Ok(copy)
+    }
+
+    /// Remove a process from the list. A live one is stopped first and
+    /// dropped from `tick` once it has exited.
+    pub fn remove_process(&mut self, name: &str) -> Result<()> {
+        let index = self
+            .position(name)
+            .ok_or_else(|| anyhow::anyhow!("Process not found: {}", name))?;
+        self.process_order.remove(index);
+        if self.processes[name].status.is_alive() {
+            self.stop_process(name, AfterStop::Remove);
+        } else {
+            self.drop_process(name);
         }
         Ok(())
     }
 
+    fn drop_process(&mut self, name: &str) {
+        if let Some(process) = self.processes.remove(name) {
+            process.shutdown.store(true, Ordering::SeqCst);
+            self.renamed.remove(&process.generation);
+            self.renamed.remove(&process.hook_generation);
+            if let Some(run) = process.hook {
+                run.shutdown.store(true, Ordering::SeqCst);
+                let _ = run.pty.force_kill();
+            }
+        }
+    }
+
+    /// Rename a process, live or not. Output from a running instance keeps
+    /// arriving under the old name and is routed through `renamed`.
+    pub fn rename_process(&mut self, name: &str, new_name: &str) -> Result<()> {
+        let index = self
+            .position(name)
+            .ok_or_else(|| anyhow::anyhow!("Process not found: {}", name))?;
+        self.check_new_name(new_name)?;
+        let mut process = self.processes.remove(name).unwrap();
+        process.config.name = new_name.to_string();
+        for generation in [process.generation, process.hook_generation] {
+            if generation != 0 {
+                self.renamed.insert(generation, new_name.to_string());
+            }
+        }
+        self.processes.insert(new_name.to_string(), process);
+        self.process_order[index] = new_name.to_string();
+        Ok(())
+    }
+
+    /// Replace a process's command and restart it.
+    pub fn set_command(&mut self, name: &str, command: &str) -> Result<()> {
+        let process = self
+            .processes
+            .get_mut(name)
+            .ok_or_else(|| anyhow::anyhow!("Process not found: {}", name))?;
+        process.config.command = command.to_string();
+        self.restart_process(name)
+    }
+
+    /// Configs of all listed processes, in list order.
+    pub fn process_configs(&self) -> Vec<ProcessConfig> {
+        self.process_order
+            .iter()
+            .map(|name| {
+                let process = &self.processes[name];
+                ProcessConfig {
+                    wrap_enabled: process.wrap_enabled,
+                    ..process.config.clone()
+                }
+            })
+            .collect()
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.process_order.iter().position(|n| n == name)
+    }
+
+    /// Send `signal` to a live process's group.
+    pub fn send_signal(&self, name: &str, signal: Signal) -> Result<()> {
+        let pty = self
+            .processes
+            .get(name)
+            .and_then(|p| p.pty.as_ref())
+            .ok_or_else(|| anyhow::anyhow!("{name} is not running"))?;
+        pty.signal(signal)
+    }
+
+    /// Pause a running process with SIGSTOP, or resume a paused one with
+    /// SIGCONT. Returns whether it is now paused.
+    pub fn toggle_pause(&mut self, name: &str) -> Result<bool> {
+        let process = self
+            .processes
+            .get_mut(name)
+            .ok_or_else(|| anyhow::anyhow!("Process not found: {}", name))?;
+        let (signal, status) = match (&process.status, &process.pty) {
+            (ProcessStatus::Starting | ProcessStatus::Running, Some(_)) => {
+                (Signal::Stop, ProcessStatus::Paused)
+            }
+            (ProcessStatus::Paused, Some(_)) => (Signal::Cont, ProcessStatus::Running),
+            _ => anyhow::bail!("{name} is not running"),
+        };
+        process.pty.as_ref().unwrap().signal(signal)?;
+        process.status = status;
+        Ok(process.status == ProcessStatus::Paused)
+    }
+
     pub fn write_to_process(&self, name: &str, data: &[u8]) -> Result<()> {
         if let Some(process) = self.processes.get(name) {
             if let Some(ref pty) = process.pty {
@@ -266,12 +1036,22 @@ impl ProcessManager {
         self.rows = rows;
         for process in self.processes.values_mut() {
             process.buffer.resize(cols as usize, rows as usize);
-            if let Some(ref pty) = process.pty {
+            process.sync_wrap(cols as usize);
+            let hook = process.hook.as_ref().map(|run| &run.pty);
+            for pty in process.pty.iter().chain(hook) {
                 let _ = pty.resize(cols, rows);
             }
         }
     }
 
+    /// Toggle line wrapping for a process.
+    pub fn toggle_wrap(&mut self, name: &str) {
+        let cols = self.cols as usize;
+        if let Some(process) = self.processes.get_mut(name) {
+            process.set_wrap(!process.wrap_enabled, cols);
+        }
+    }
+
     /// Re-send PTY resize to a process (triggers SIGWINCH). Used on focus change.
     pub fn nudge_resize(&self, name: &str) {
         if let Some(process) = self.processes.get(name) {
@@ -281,90 +1061,248 @@ impl ProcessManager {
         }
     }
 
+    /// Resample CPU and memory once the sample interval has passed. The
+    /// `detail` process also gets command lines and listening ports.
+    /// Returns whether a sample was taken.
+    pub fn sample_metrics(&mut self, detail: Option<&str>) -> bool {
+        if !self.metrics_sampler.due() {
+            return false;
+        }
+        let leader = |p: &ManagedProcess| p.pty.as_ref().and_then(PtyHandle::pid);
+        let leaders: Vec<u32> = self.processes.values().filter_map(leader).collect();
+        let detail = detail
+            .and_then(|name| self.processes.get(name))
+            .and_then(leader);
+        let mut samples = self.metrics_sampler.sample(&leaders, detail);
+        for process in self.processes.values_mut() {
+            let sample = leader(process).and_then(|pid| samples.remove(&pid));
+            process.update_metrics(sample);
+        }
+        true
+    }
+
+    /// Refresh the listening ports of every process group, at most every
+    /// `PORT_SCAN_INTERVAL`. Returns true if any process's ports changed.
+    pub fn scan_ports(&mut self) -> bool {
+        if !metrics::SUPPORTED
+            || self
+                .last_port_scan
+                .is_some_and(|t| t.elapsed() < PORT_SCAN_INTERVAL)
+        {
+            return false;
+        }
+        self.last_port_scan = Some(Instant::now());
+        let leader = |p: &ManagedProcess| p.pty.as_ref().and_then(PtyHandle::pid);
+        let leaders: Vec<u32> = self.processes.values().filter_map(leader).collect();
+        let mut ports = metrics::group_listening_ports(&leaders);
+        let mut changed = false;
+        for process in self.processes.values_mut() {
+            let listening = leader(process)
+                .and_then(|pid| ports.remove(&pid))
+                .unwrap_or_default();
+            changed |= process.listening != listening;
+            process.listening = listening;
+        }
+        changed
+    }
+
+    /// Pids of running stop commands and hooks; the tree scan leaves them alone.
+    fn helper_pids(&self) -> Vec<u32> {
+        let hooks = self
+            .processes
+            .values()
+            .filter_map(|p| p.hook.as_ref()?.pty.pid());
+        self.stop_commands
+            .iter()
+            .map(Child::id)
+            .chain(hooks)
+            .collect()
+    }
+
+    /// Names and pids of managed processes with a live child.
+    fn leaders(&self) -> Vec<(String, u32)> {
+        self.process_order
+            .iter()
+            .filter_map(|name| {
+                let pid = self.processes.get(name)?.pty.as_ref()?.pid()?;
+                Some((name.clone(), pid))
+            })
+            .collect()
+    }
+
+    /// Rescan descendant trees once the sample interval has passed. Runs
+    /// continuously so processes are attributed before they are orphaned.
+    /// Returns whether a scan was taken.
+    pub fn track_tree(&mut self) -> bool {
+        if !self.tree.due() {
+            return false;
+        }
+        self.tree.scan(&self.leaders(), &self.helper_pids());
+        true
+    }
+
+    pub fn process_tree(&self) -> &ProcessTree {
+        &self.tree
+    }
+
+    /// Descendants that left their managed process's group (e.g. via
+    /// `setsid`), from a fresh scan. Stopping a process won't reach them.
+    pub fn escaped_processes(&mut self) -> Vec<(String, TreeEntry)> {
+        self.tree.scan(&self.leaders(), &self.helper_pids());
+        self.tree.escaped()
+    }
+
+    /// Also stop `entries` during shutdown.
+    pub fn kill_escaped_on_shutdown(&mut self, entries: Vec<TreeEntry>) {
+        self.escaped_to_kill = entries;
+    }
+
+    /// Feed output into a process buffer and apply it immediately.
+    #[allow(dead_code)] // Used by tests
     pub fn handle_output(&mut self, name: &str, gen: Generation, data: &[u8]) {
-        if let Some(process) = self.processes.get_mut(name) {
-            // Ignore events from old process instances
-            if process.generation != gen {
-                return;
-            }
+        self.write_output(name, gen, data);
+        self.flush_output();
+    }
 
+    /// Feed output into a process buffer, deferring the auto-scroll and memory
+    /// limit work to `flush_output` so a burst of events pays for it once.
+    /// Returns whether the process list needs redrawing (alternate screen toggled).
+    pub fn write_output(&mut self, name: &str, gen: Generation, data: &[u8]) -> bool {
+        let Some((process, from_hook)) = self.event_target(name, gen) else {
+            return false;
+        };
+
+        let was_alt = process.buffer.is_alternate_screen();
+        let from_row = process.buffer.cursor_position().0;
+        let dropped = process.buffer.dropped_lines();
+        if from_hook {
+            process.buffer.write_dimmed(data);
+        } else {
             process.buffer.write(data);
-
-            // Send any pending responses (e.g., device attributes queries)
-            let responses = process.buffer.take_pending_responses();
-            if let Some(ref pty) = process.pty {
-                for response in responses {
-                    let _ = pty.write(&response);
-                }
+        }
+        process.output_pending = true;
+        // A full scrollback drops lines from the front, moving rows up
+        let from_row = from_row.saturating_sub(process.buffer.dropped_lines() - dropped);
+
+        // Send any pending responses (e.g., device attributes queries)
+        let responses = process.buffer.take_pending_responses();
+        let pty = if from_hook {
+            process.hook.as_ref().map(|run| &run.pty)
+        } else {
+            process.pty.as_ref()
+        };
+        if let Some(pty) = pty {
+            for response in responses {
+                let _ = pty.write(&response);
             }
+        }
+        let toggled = process.buffer.is_alternate_screen() != was_alt;
+        if !from_hook && !process.buffer.is_alternate_screen() {
+            process.scan_urls(from_row);
+        }
+
+        if !from_hook && process.awaiting_ready && process.output_ready(from_row) {
+            process.awaiting_ready = false;
+            process.queue_hook(Hook::OnReady, Vec::new());
+            let name = process.config.name.clone();
+            self.run_steps(&name);
+        }
+        toggled
+    }
 
+    /// Finish a batch of `write_output` calls: scroll auto-scrolling processes
+    /// to the bottom and enforce the memory limit.
+    pub fn flush_output(&mut self) {
+        let (cols, visible) = (self.cols as usize, self.rows as usize);
+        let mut any = false;
+        for process in self.processes.values_mut() {
+            if !std::mem::take(&mut process.output_pending) {
+                continue;
+            }
+            any = true;
+            process.sync_wrap(cols);
             if process.auto_scroll {
-                let visible = self.rows as usize;
-                let lines = process.buffer.get_all_lines();
-                // Exclude trailing empty lines (consistent with render)
-                let content_count = {
-                    let mut count = lines.len();
-                    while count > 0 && lines[count - 1].cells.is_empty() {
-                        count -= 1;
-                    }
-                    count.max(1)
-                };
-                let total_display_lines = if process.wrap_enabled && self.cols > 0 {
-                    let cols = self.cols as usize;
-                    lines
-                        .iter()
-                        .take(content_count)
-                        .map(|line| {
-                            if line.cells.is_empty() {
-                                1
-                            } else {
-                                line.cells.len().div_ceil(cols)
-                            }
-                        })
-                        .sum::<usize>()
-                        .max(1)
-                } else {
-                    content_count
-                };
-                // Scroll to show bottom of content
-                if total_display_lines > visible {
-                    process.scroll_offset = total_display_lines - visible;
-                } else {
-                    process.scroll_offset = 0;
-                }
+                process.scroll_to_bottom(visible);
             }
         }
+        if any {
+            self.enforce_memory_limit();
+        }
+    }
+
+    /// The reader is up. Without a `ready_pattern`, this is when the process
+    /// counts as ready for `on_ready`.
+    pub fn handle_started(&mut self, name: &str, gen: Generation) {
+        let Some(process) = self.instance_mut(name, gen) else {
+            return;
+        };
+        if process.status == ProcessStatus::Starting {
+            process.status = ProcessStatus::Running;
+        }
+        if process.config.ready_pattern.is_none() {
+            process.queue_hook(Hook::OnReady, Vec::new());
+            let name = process.config.name.clone();
+            self.run_steps(&name);
+        }
     }
 
+    /// Output reached EOF. The exit code comes from reaping the child, which
+    /// `tick` retries until it succeeds.
     pub fn handle_exit(&mut self, name: &str, gen: Generation, code: Option<i32>) {
-        if let Some(process) = self.processes.get_mut(name) {
-            // Ignore events from old process instances
-            if process.generation != gen {
-                return;
+        if let Some(process) = self.instance_mut(name, gen) {
+            process.shutdown.store(true, Ordering::SeqCst);
+            if let Some(code) = code {
+                if process.stop.is_none() {
+                    process.pty = None;
+                    process.status = ProcessStatus::Exited(code);
+                    let exit = ExitInfo { code, signal: None };
+                    process.queue_exit_hooks(&exit, code != 0);
+                    let name = process.config.name.clone();
+                    self.run_steps(&name);
+                    return;
+                }
             }
+        }
+        self.tick();
+    }
 
+    pub fn handle_error(&mut self, name: &str, gen: Generation, error: &str) {
+        if let Some(process) = self.instance_mut(name, gen) {
             process.shutdown.store(true, Ordering::SeqCst);
             process.pty = None;
-            process.status = match code {
-                Some(c) => ProcessStatus::Exited(c),
-                None => ProcessStatus::Exited(0),
-            };
+            process.stop = None;
+            process.awaiting_ready = false;
+            let name = process.config.name.clone();
+            self.start_failed(&name, error);
         }
     }
 
-    pub fn handle_error(&mut self, name: &str, gen: Generation, error: &str) {
-        if let Some(process) = self.processes.get_mut(name) {
-            // Ignore events from old process instances
-            if process.generation != gen {
-                return;
-            }
+    /// The process an event belongs to, or None for events from old
+    /// instances. `name` is the one the instance was started under.
+    fn instance_mut(&mut self, name: &str, gen: Generation) -> Option<&mut ManagedProcess> {
+        let name = self.renamed.get(&gen).map_or(name, String::as_str);
+        self.processes.get_mut(name).filter(|p| p.generation == gen)
+    }
 
-            process.shutdown.store(true, Ordering::SeqCst);
-            process.pty = None;
-            process.status = ProcessStatus::Failed(error.to_string());
+    /// Like `instance_mut`, but also matches output of the process's latest
+    /// hook, flagged by the `bool`.
+    fn event_target(&mut self, name: &str, gen: Generation) -> Option<(&mut ManagedProcess, bool)> {
+        let name = self.renamed.get(&gen).map_or(name, String::as_str);
+        let process = self.processes.get_mut(name)?;
+        if process.generation == gen {
+            Some((process, false))
+        } else if process.hook_generation == gen {
+            Some((process, true))
+        } else {
+            None
         }
     }
 
+    /// Current name of a process whose events carry `name`.
+    pub fn current_name<'a>(&'a self, name: &'a str, gen: Generation) -> &'a str {
+        self.renamed.get(&gen).map_or(name, String::as_str)
+    }
+
     pub fn get_process(&self, name: &str) -> Option<&ManagedProcess> {
         self.processes.get(name)
     }
@@ -381,55 +1319,163 @@ impl ProcessManager {
         self.process_order.len()
     }
 
-    /// Begin graceful shutdown - send SIGTERM to all running processes
+    /// Begin graceful shutdown - send SIGTERM to all live processes
     pub fn begin_shutdown(&mut self) {
+        #[cfg(unix)]
+        for entry in &self.escaped_to_kill {
+            tree::signal(entry, libc::SIGTERM);
+        }
         for name in self.process_order.clone() {
-            if let Some(process) = self.processes.get_mut(&name) {
-                if matches!(process.status, ProcessStatus::Running) {
-                    process.shutdown.store(true, Ordering::SeqCst);
-                    if let Some(ref pty) = process.pty {
-                        let _ = pty.terminate();
-                    }
-                }
+            let alive = self
+                .processes
+                .get(&name)
+                .is_some_and(|p| p.status.is_alive());
+            if alive {
+                self.stop_process(&name, AfterStop::Nothing);
             }
         }
     }
 
-    /// Count processes that are not running
+    /// Count processes that have no live child
     pub fn stopped_count(&self) -> usize {
         self.processes
             .values()
-            .filter(|p| !matches!(p.status, ProcessStatus::Running))
+            .filter(|p| !p.status.is_alive())
             .count()
     }
 
-    /// Check if any process is still running
-    pub fn any_running(&self) -> bool {
+    /// Check if any process still has a live child (or an escaped
+    /// descendant marked for killing is still alive)
+    pub fn any_running(&mut self) -> bool {
+        if !self.escaped_to_kill.is_empty() {
+            // Reap escaped processes that were re-parented to panex
+            self.tree.scan(&self.leaders(), &self.helper_pids());
+        }
+        self.escaped_to_kill.retain(tree::is_alive);
         self.processes
             .values()
-            .any(|p| matches!(p.status, ProcessStatus::Running))
+            .any(|p| p.status.is_alive() || p.hook.is_some() || !p.steps.is_empty())
+            || !self.escaped_to_kill.is_empty()
     }
 
     /// Force kill all remaining processes
     pub fn finish_shutdown(&mut self) {
-        for name in self.process_order.clone() {
-            if let Some(process) = self.processes.get_mut(&name) {
-                if matches!(process.status, ProcessStatus::Running) {
-                    if let Some(ref pty) = process.pty {
-                        let _ = pty.force_kill();
+        #[cfg(unix)]
+        for entry in self.escaped_to_kill.drain(..) {
+            tree::signal(&entry, libc::SIGKILL);
+        }
+        for process in self.processes.values_mut() {
+            process.steps.clear();
+            if let Some(run) = process.hook.take() {
+                let _ = run.pty.force_kill();
+            }
+            if process.status.is_alive() {
+                if let Some(ref pty) = process.pty {
+                    let _ = pty.force_kill();
+                }
+                process.pty = None;
+                process.stop = None;
+                process.status = ProcessStatus::Stopped;
+            }
+        }
+    }
+}
+
+/// Forward a PTY's output to the event loop as events for `name`/`generation`
+/// until EOF, an error or `shutdown`.
+fn spawn_reader(
+    tx: mpsc::Sender<AppEvent>,
+    proc_name: String,
+    generation: Generation,
+    reader: Arc<Mutex<Box<dyn Read + Send>>>,
+    shutdown: Arc<AtomicBool>,
+) {
+    std::thread::spawn(move || {
+        // blocking_send parks this thread while the channel is full, which
+        // stops reading the PTY and in turn blocks the child's writes
+        let _ = tx.blocking_send(AppEvent::ProcessStarted(proc_name.clone(), generation));
+        let mut buf = vec![0u8; READ_CHUNK];
+        loop {
+            if shutdown.load(Ordering::SeqCst) {
+                break;
+            }
+
+            let mut reader_guard = match reader.lock() {
+                Ok(g) => g,
+                Err(_) => break,
+            };
+
+            match reader_guard.read(&mut buf) {
+                Ok(0) => {
+                    drop(reader_guard);
+                    let _ = tx.blocking_send(AppEvent::ProcessExited(
+                        proc_name.clone(),
+                        generation,
+                        None,
+                    ));
+                    break;
+                }
+                Ok(n) => {
+                    drop(reader_guard);
+                    let _ = tx.blocking_send(AppEvent::ProcessOutput(
+                        proc_name.clone(),
+                        generation,
+                        buf[..n].to_vec(),
+                    ));
+                }
+                Err(e) => {
+                    drop(reader_guard);
+                    if !shutdown.load(Ordering::SeqCst) {
+                        let _ = tx.blocking_send(AppEvent::ProcessError(
+                            proc_name.clone(),
+                            generation,
+                            e.to_string(),
+                        ));
                     }
-                    process.pty = None;
-                    process.status = ProcessStatus::Stopped;
+                    break;
                 }
             }
         }
+    });
+}
+
+/// Configured environment of a resolved process, for `PtyHandle::spawn`.
+fn spawn_env(config: &ProcessConfig) -> Vec<(&str, String)> {
+    config
+        .env
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.clone()))
+        .collect()
+}
+
+/// Start a process's `stop_command` through the shell, with the process
+/// group leader's pid in `PANEX_PID`.
+fn spawn_stop_command(
+    command: &str,
+    config: &ProcessConfig,
+    pid: Option<u32>,
+) -> std::io::Result<Child> {
+    let (program, args) = shell_invocation(command);
+    let mut cmd = Command::new(program);
+    cmd.args(args)
+        .envs(config.env.iter().map(|(k, v)| (k, v)))
+        .env("PANEX_NAME", &config.name)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    if let Some(pid) = pid {
+        cmd.env("PANEX_PID", pid.to_string());
     }
+    if let Some(ref cwd) = config.cwd {
+        cmd.current_dir(cwd);
+    }
+    cmd.spawn()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ratatui::style::Color;
+    use ratatui::style::{Color, Modifier};
     use tokio::sync::mpsc;
 
     fn process_config(name: &str) -> ProcessConfig {
@@ -438,6 +1484,7 @@ mod tests {
             command: "true".to_string(),
             no_shift_tab: false,
             wrap_enabled: false,
+            ..ProcessConfig::default()
         }
     }
 
@@ -449,7 +1496,7 @@ mod tests {
         names: &[&str],
         show_restart_marker: bool,
     ) -> ProcessManager {
-        let (event_tx, _event_rx) = mpsc::unbounded_channel();
+        let (event_tx, _event_rx) = mpsc::channel(crate::event::EVENT_QUEUE_CAPACITY);
         let mut pm = ProcessManager::new(event_tx, 80, 24, 500, 10_000, show_restart_marker);
 
         for name in names {
@@ -495,14 +1542,17 @@ mod tests {
             .unwrap();
 
         let lines = pm.get_process("one").unwrap().buffer.get_all_lines();
+        let styles = lines.styles();
         assert!(lines[1]
-            .cells
-            .iter()
+            .cells(styles)
             .all(|cell| cell.style.fg == Some(Color::Blue)));
-        assert_eq!(lines[2].cells[0].style.fg, Some(Color::Blue));
-        assert_eq!(lines[2].cells.last().unwrap().style.fg, Some(Color::Blue));
-        assert_eq!(lines[2].cells[1].style.fg, None);
-        assert_eq!(lines[3].cells[0].style.fg, Some(Color::Blue));
+        assert_eq!(lines[2].style_at(0, styles).fg, Some(Color::Blue));
+        assert_eq!(
+            lines[2].cells(styles).last().unwrap().style.fg,
+            Some(Color::Blue)
+        );
+        assert_eq!(lines[2].style_at(1, styles).fg, None);
+        assert_eq!(lines[3].style_at(0, styles).fg, Some(Color::Blue));
     }
 
     #[test]
@@ -564,6 +1614,65 @@ mod tests {
         assert_eq!(two, "");
     }
 
+    #[test]
+    fn processes_without_autostart_wait_for_an_explicit_start() {
+        let mut pm = test_manager(&["one"]);
+        pm.add_process(ProcessConfig {
+            autostart: false,
+            ..process_config("seed")
+        });
+        assert_eq!(pm.restart_all_count(), 1);
+
+        pm.start_all().unwrap();
+        assert!(pm.get_process("one").unwrap().status.is_alive());
+        assert_eq!(
+            pm.get_process("seed").unwrap().status,
+            ProcessStatus::Stopped
+        );
+
+        // Once started, it is restarted with the others
+        pm.start_process("seed").unwrap();
+        assert_eq!(pm.restart_all_count(), 2);
+    }
+
+    #[test]
+    fn processes_are_added_duplicated_and_renamed_in_place() {
+        let mut pm = test_manager(&["one", "two"]);
+        let config = |name: &str| ProcessConfig {
+            autostart: false,
+            ..process_config(name)
+        };
+        assert!(pm.insert_process(config("two"), 0).is_err());
+        assert!(pm.insert_process(config("has space"), 0).is_err());
+        pm.insert_process(config("zero"), 0).unwrap();
+        assert_eq!(pm.duplicate_process("one").unwrap(), "one-2");
+        assert_eq!(pm.duplicate_process("one").unwrap(), "one-3");
+        assert_eq!(pm.process_names(), ["zero", "one", "one-3", "one-2", "two"]);
+
+        assert!(pm.rename_process("one-3", "two").is_err());
+        pm.rename_process("one-3", "three").unwrap();
+        assert_eq!(pm.process_names()[2], "three");
+        assert_eq!(pm.get_process("three").unwrap().config.name, "three");
+        assert!(pm.get_process("one-3").is_none());
+    }
+
+    #[test]
+    fn removed_names_can_be_reused_without_picking_up_old_events() {
+        let mut pm = test_manager(&["one"]);
+        pm.start_process("one").unwrap();
+        let old_gen = pm.get_process("one").unwrap().generation;
+        pm.get_process_mut("one").unwrap().status = ProcessStatus::Exited(0);
+        pm.get_process_mut("one").unwrap().pty = None;
+
+        pm.remove_process("one").unwrap();
+        assert_eq!(pm.process_count(), 0);
+        pm.insert_process(process_config("one"), 0).unwrap();
+        assert_ne!(pm.get_process("one").unwrap().generation, old_gen);
+
+        pm.handle_output("one", old_gen, b"stale");
+        assert_eq!(pm.get_process("one").unwrap().buffer.to_test_string(), "");
+    }
+
     #[test]
     fn restart_with_marker_keeps_old_output_and_appends_marker() {
         let mut pm = test_manager_with_restart_marker(&["one"], true);
@@ -600,4 +1709,631 @@ mod tests {
         assert!(one.ends_with("┘"));
         assert_eq!(one, two);
     }
+
+    #[test]
+    fn memory_limit_evicts_from_largest_buffer_first() {
+        let mut pm = test_manager(&["chatty", "quiet"]);
+        let lines = |n: usize| -> Vec<u8> {
+            (0..n)
+                .map(|i| format!("{i} {}\r\n", "x".repeat(100)))
+                .collect::<String>()
+                .into_bytes()
+        };
+        pm.get_process_mut("quiet")
+            .unwrap()
+            .buffer
+            .write(&lines(100));
+        let quiet_usage = pm.get_process("quiet").unwrap().buffer.memory_usage();
+
+        let limit = quiet_usage * 3;
+        pm.set_memory_limit(Some(limit));
+        let gen = pm.get_process("chatty").unwrap().generation;
+        pm.handle_output("chatty", gen, &lines(2000));
+
+        assert!(pm.memory_usage() <= limit);
+        // The quiet process keeps all of its history
+        assert_eq!(
+            pm.get_process("quiet").unwrap().buffer.memory_usage(),
+            quiet_usage
+        );
+        let chatty = pm.get_process("chatty").unwrap().buffer.to_test_string();
+        assert!(!chatty.starts_with("0 "));
+        assert!(chatty.contains("1999 "));
+    }
+
+    #[test]
+    fn memory_limit_keeps_manual_scroll_on_same_content() {
+        let mut pm = test_manager(&["one"]);
+        let line = |i: usize| format!("{i} {}\r\n", "x".repeat(100));
+        let gen = pm.get_process("one").unwrap().generation;
+        let first: String = (0..200).map(line).collect();
+        pm.handle_output("one", gen, first.as_bytes());
+        {
+            let process = pm.get_process_mut("one").unwrap();
+            process.auto_scroll = false;
+            process.scroll_offset = 150;
+        }
+
+        pm.set_memory_limit(Some(pm.memory_usage()));
+        let more: String = (200..264).map(line).collect();
+        pm.handle_output("one", gen, more.as_bytes());
+
+        let process = pm.get_process("one").unwrap();
+        let top = &process.buffer.get_all_lines()[process.scroll_offset];
+        assert!(top.text().starts_with("150 "));
+    }
+
+    #[test]
+    fn write_output_defers_scroll_until_flush() {
+        let mut pm = test_manager(&["one"]);
+        let gen = pm.get_process("one").unwrap().generation;
+        for i in 0..100 {
+            pm.write_output("one", gen, format!("line {i}\r\n").as_bytes());
+        }
+        assert_eq!(pm.get_process("one").unwrap().scroll_offset, 0);
+
+        pm.flush_output();
+        let process = pm.get_process("one").unwrap();
+        // 100 lines of content in a 24-row viewport
+        assert_eq!(process.scroll_offset, 100 - 24);
+    }
+
+    #[test]
+    fn wrap_index_tracks_output_incrementally() {
+        let mut pm = test_manager(&["one"]);
+        pm.toggle_wrap("one");
+        let gen = pm.get_process("one").unwrap().generation;
+        // 100-char lines take two rows each at 80 columns
+        for i in 0..30 {
+            let line = format!("{i:03}{}\r\n", "x".repeat(97));
+            pm.write_output("one", gen, line.as_bytes());
+        }
+        pm.flush_output();
+
+        let process = pm.get_process_mut("one").unwrap();
+        assert_eq!(process.wrap_index.width(), 80);
+        assert_eq!(process.wrap_index.total_rows(30), 60);
+        assert_eq!(process.scroll_offset, 60 - 24);
+
+        // Writes outside flush_output are picked up by the next sync
+        process.buffer.write(
+            b"short
+",
+        );
+        process.sync_wrap(80);
+        assert_eq!(process.wrap_index.total_rows(31), 61);
+
+        pm.toggle_wrap("one");
+        assert_eq!(pm.get_process("one").unwrap().wrap_index.width(), 0);
+    }
+
+    #[test]
+    fn write_output_reports_alternate_screen_toggle() {
+        let mut pm = test_manager(&["one"]);
+        let gen = pm.get_process("one").unwrap().generation;
+        assert!(!pm.write_output("one", gen, b"plain"));
+        assert!(pm.write_output("one", gen, b"\x1b[?1049h"));
+        assert!(!pm.write_output("one", gen, b"full screen"));
+        assert!(pm.write_output("one", gen, b"\x1b[?1049l"));
+        // Stale generations are ignored
+        assert!(!pm.write_output("one", gen + 1, b"\x1b[?1049h"));
+    }
+
+    #[test]
+    fn write_output_scans_new_lines_when_scrollback_is_full() {
+        let (event_tx, _event_rx) = mpsc::channel(crate::event::EVENT_QUEUE_CAPACITY);
+        let mut pm = ProcessManager::new(event_tx, 80, 4, 500, 20, false);
+        pm.add_process(ProcessConfig {
+            ready_pattern: Some("listening".to_string()),
+            ..process_config("one")
+        });
+        let process = pm.get_process_mut("one").unwrap();
+        process.awaiting_ready = true;
+        let gen = process.generation;
+        pm.write_output("one", gen, "old\r\n".repeat(30).as_bytes());
+
+        // Ten lines drop off the front while these are written
+        let mut data = "noise\r\n".repeat(8);
+        data.push_str("listening on http://localhost:3000/\r\n");
+        pm.write_output("one", gen, data.as_bytes());
+        let process = pm.get_process("one").unwrap();
+        assert!(!process.awaiting_ready);
+        assert_eq!(process.urls, ["http://localhost:3000/"]);
+    }
+
+    #[test]
+    fn write_output_collects_urls_from_complete_lines() {
+        let mut pm = test_manager(&["one"]);
+        let gen = pm.get_process("one").unwrap().generation;
+        pm.write_output(
+            "one",
+            gen,
+            b"Local: http://localhost:5173/\r\nNetwork: http://10.",
+        );
+        assert_eq!(
+            pm.get_process("one").unwrap().urls,
+            ["http://localhost:5173/"]
+        );
+        // The second URL counts once its line is complete
+        pm.write_output("one", gen, b"0.0.2:5173/\r\n");
+        let process = pm.get_process_mut("one").unwrap();
+        process.listening = vec![5173, 9229];
+        assert_eq!(
+            process.quick_links(),
+            [
+                "http://10.0.0.2:5173/",
+                "http://localhost:5173/",
+                "http://localhost:9229"
+            ]
+        );
+    }
+
+    /// Manager with real processes; events are pumped by `wait_until`.
+    #[cfg(unix)]
+    fn live_manager(command: &str, timeout: u64) -> (ProcessManager, mpsc::Receiver<AppEvent>) {
+        let (event_tx, event_rx) = mpsc::channel(crate::event::EVENT_QUEUE_CAPACITY);
+        let mut pm = ProcessManager::new(event_tx, 80, 24, timeout, 10_000, false);
+        pm.add_process(ProcessConfig {
+            command: command.to_string(),
+            ..process_config("one")
+        });
+        (pm, event_rx)
+    }
+
+    /// Dispatch events and tick like the main loop until `done` holds (or 5s pass).
+    #[cfg(unix)]
+    fn wait_until(
+        pm: &mut ProcessManager,
+        rx: &mut mpsc::Receiver<AppEvent>,
+        done: impl Fn(&ProcessManager) -> bool,
+    ) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            while let Ok(event) = rx.try_recv() {
+                match event {
+                    AppEvent::ProcessOutput(name, gen, data) => {
+                        pm.write_output(&name, gen, &data);
+                    }
+                    AppEvent::ProcessStarted(name, gen) => pm.handle_started(&name, gen),
+                    AppEvent::ProcessExited(name, gen, code) => pm.handle_exit(&name, gen, code),
+                    AppEvent::ProcessError(name, gen, e) => pm.handle_error(&name, gen, &e),
+                    _ => {}
+                }
+            }
+            pm.flush_output();
+            pm.tick();
+            if done(pm) {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        false
+    }
+
+    #[cfg(unix)]
+    fn output_contains(pm: &ProcessManager, text: &str) -> bool {
+        pm.get_process("one")
+            .unwrap()
+            .buffer
+            .to_test_string()
+            .contains(text)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn natural_exit_reports_real_exit_code() {
+        let (mut pm, mut rx) = live_manager("exit 3", 500);
+        pm.start_process("one").unwrap();
+        assert!(wait_until(&mut pm, &mut rx, |pm| {
+            pm.get_process("one").unwrap().status == ProcessStatus::Exited(3)
+        }));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stop_does_not_block_and_escalates_to_sigkill() {
+        let (mut pm, mut rx) = live_manager("trap '' TERM; echo ready; sleep 10", 300);
+        pm.start_process("one").unwrap();
+        assert!(wait_until(&mut pm, &mut rx, |pm| output_contains(
+            pm, "ready"
+        )));
+        assert_eq!(
+            pm.get_process("one").unwrap().status,
+            ProcessStatus::Running
+        );
+
+        let start = Instant::now();
+        pm.kill_process("one").unwrap();
+        assert!(start.elapsed() < Duration::from_millis(100));
+        assert_eq!(
+            pm.get_process("one").unwrap().status,
+            ProcessStatus::Stopping
+        );
+
+        // SIGTERM is ignored, so only the SIGKILL after the timeout ends it
+        assert!(wait_until(&mut pm, &mut rx, |pm| {
+            pm.get_process("one").unwrap().status == ProcessStatus::Stopped
+        }));
+        assert!(start.elapsed() >= Duration::from_millis(300));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn full_event_queue_pauses_reader_until_drained() {
+        let (event_tx, mut rx) = mpsc::channel(2);
+        let mut pm = ProcessManager::new(event_tx, 80, 24, 500, 10_000, false);
+        pm.add_process(ProcessConfig {
+            command: "yes".to_string(),
+            ..process_config("one")
+        });
+        pm.start_process("one").unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+
+        // The reader is parked on the full channel rather than queueing more
+        let mut queued = 0;
+        while rx.try_recv().is_ok() {
+            queued += 1;
+        }
+        assert_eq!(queued, 2);
+
+        // Draining lets it continue
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(rx.try_recv().is_ok());
+        pm.finish_shutdown();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn metrics_cover_the_process_group_and_keep_peak_after_exit() {
+        let (mut pm, mut rx) = live_manager("sleep 10 & echo ready; wait", 500);
+        pm.start_process("one").unwrap();
+        assert!(wait_until(&mut pm, &mut rx, |pm| output_contains(
+            pm, "ready"
+        )));
+
+        assert!(pm.sample_metrics(Some("one")));
+        // Not due again until the sample interval has passed
+        assert!(!pm.sample_metrics(Some("one")));
+        let process = pm.get_process_mut("one").unwrap();
+        let metrics = process.metrics.clone().unwrap();
+        assert!(metrics.rss > 0);
+        assert_eq!(metrics.peak_rss, metrics.rss);
+        // The shell and its background sleep share the group
+        assert!(metrics.children.len() >= 2);
+        assert!(metrics
+            .children
+            .iter()
+            .any(|c| c.command.starts_with("sleep 10")));
+
+        // Once the group is gone only the peak survives
+        process.update_metrics(None);
+        let after = process.metrics.as_ref().unwrap();
+        assert_eq!((after.rss, after.peak_rss), (0, metrics.peak_rss));
+        assert!(after.children.is_empty());
+        pm.finish_shutdown();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn escaped_descendants_are_found_and_killed_on_shutdown() {
+        let (mut pm, mut rx) = live_manager("setsid sleep 30 & echo ready; wait", 500);
+        pm.start_process("one").unwrap();
+        assert!(wait_until(&mut pm, &mut rx, |pm| output_contains(
+            pm, "ready"
+        )));
+
+        let escaped = pm.escaped_processes();
+        let (owner, sleeper) = escaped
+            .iter()
+            .find(|(_, e)| e.command == "sleep 30")
+            .cloned()
+            .expect("setsid child should be outside the group");
+        assert_eq!(owner, "one");
+        let entries = pm.process_tree().entries("one");
+        assert!(!entries[0].escaped);
+        assert!(entries.iter().any(|e| e.pid == sleeper.pid && e.depth == 1));
+
+        pm.kill_escaped_on_shutdown(vec![sleeper.clone()]);
+        pm.begin_shutdown();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while pm.any_running() && Instant::now() < deadline {
+            pm.tick();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(!tree::is_alive(&sleeper));
+    }
+
+    /// Scheduler state letter of a process, from `/proc/<pid>/stat`.
+    #[cfg(target_os = "linux")]
+    fn proc_state(pid: u32) -> Option<char> {
+        let line = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+        Some(super::super::metrics::parse_stat(&line)?.state)
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn pause_stops_the_group_and_kill_resumes_it_first() {
+        // Long timeout, so only SIGCONT + SIGTERM can end it within the wait
+        let (mut pm, mut rx) = live_manager("echo ready; sleep 30", 10_000);
+        assert!(pm.toggle_pause("one").is_err());
+        pm.start_process("one").unwrap();
+        assert!(wait_until(&mut pm, &mut rx, |pm| output_contains(
+            pm, "ready"
+        )));
+        let pid = pm
+            .get_process("one")
+            .unwrap()
+            .pty
+            .as_ref()
+            .unwrap()
+            .pid()
+            .unwrap();
+
+        assert!(pm.toggle_pause("one").unwrap());
+        assert_eq!(pm.get_process("one").unwrap().status, ProcessStatus::Paused);
+        assert!(wait_until(&mut pm, &mut rx, |_| proc_state(pid) == Some('T')));
+
+        assert!(!pm.toggle_pause("one").unwrap());
+        assert_eq!(
+            pm.get_process("one").unwrap().status,
+            ProcessStatus::Running
+        );
+        assert!(wait_until(&mut pm, &mut rx, |_| proc_state(pid) != Some('T')));
+
+        pm.toggle_pause("one").unwrap();
+        pm.kill_process("one").unwrap();
+        assert!(wait_until(&mut pm, &mut rx, |pm| !pm
+            .get_process("one")
+            .unwrap()
+            .status
+            .is_alive()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stop_uses_configured_signal_or_command() {
+        let trap = |sig: &str| {
+            format!(
+                "trap 'echo got {sig}; exit 0' {sig}; echo ready; while true; do sleep 0.1; done"
+            )
+        };
+        let (mut pm, mut rx) = live_manager(&trap("INT"), 10_000);
+        pm.add_process(ProcessConfig {
+            command: trap("USR1"),
+            stop_command: Some("kill -USR1 $PANEX_PID".to_string()),
+            ..process_config("two")
+        });
+        pm.processes.get_mut("one").unwrap().config.stop_signal = Signal::Int;
+        pm.start_process("one").unwrap();
+        pm.start_process("two").unwrap();
+        let contains = |pm: &ProcessManager, name: &str, text: &str| {
+            pm.get_process(name)
+                .unwrap()
+                .buffer
+                .to_test_string()
+                .contains(text)
+        };
+        assert!(wait_until(&mut pm, &mut rx, |pm| contains(
+            pm, "one", "ready"
+        ) && contains(
+            pm, "two", "ready"
+        )));
+
+        pm.kill_process("one").unwrap();
+        pm.kill_process("two").unwrap();
+        assert!(wait_until(&mut pm, &mut rx, |pm| contains(
+            pm, "one", "got INT"
+        ) && contains(
+            pm, "two", "got USR1"
+        )));
+        assert!(wait_until(&mut pm, &mut rx, |pm| pm.stopped_count() == 2));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stop_reports_an_unreadable_env_file_and_signals_instead() {
+        let env_file = std::env::temp_dir().join(format!("panex-stop-{}.env", std::process::id()));
+        std::fs::write(&env_file, "GREETING=hi\n").unwrap();
+        let (mut pm, mut rx) = live_manager(
+            "trap 'echo got TERM; exit 0' TERM; echo ready; while true; do sleep 0.1; done",
+            500,
+        );
+        let config = &mut pm.processes.get_mut("one").unwrap().config;
+        config.env_file = vec![env_file.clone()];
+        config.stop_command = Some("echo never".to_string());
+        pm.start_process("one").unwrap();
+        assert!(wait_until(&mut pm, &mut rx, |pm| output_contains(
+            pm, "ready"
+        )));
+
+        std::fs::remove_file(&env_file).unwrap();
+        pm.kill_process("one").unwrap();
+        let process = pm.get_process("one").unwrap();
+        assert!(process.output_pending);
+        assert!(process
+            .buffer
+            .to_test_string()
+            .lines()
+            .any(|line| line.starts_with("[panex] stop command failed")));
+        assert!(wait_until(&mut pm, &mut rx, |pm| output_contains(
+            pm, "got TERM"
+        )));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn renamed_live_process_keeps_its_output_and_removal_waits_for_exit() {
+        let (mut pm, mut rx) = live_manager("read line; echo got $line; sleep 30", 500);
+        pm.start_process("one").unwrap();
+        assert!(wait_until(&mut pm, &mut rx, |pm| pm
+            .get_process("one")
+            .unwrap()
+            .status
+            == ProcessStatus::Running));
+
+        // The reader thread still reports as "one"
+        pm.rename_process("one", "renamed").unwrap();
+        pm.write_to_process("renamed", b"hello\r").unwrap();
+        assert!(wait_until(&mut pm, &mut rx, |pm| pm
+            .get_process("renamed")
+            .unwrap()
+            .buffer
+            .to_test_string()
+            .contains("got hello")));
+
+        pm.remove_process("renamed").unwrap();
+        assert_eq!(pm.process_count(), 0);
+        assert!(pm.get_process("renamed").is_some(), "kept until it exits");
+        assert!(wait_until(&mut pm, &mut rx, |pm| pm
+            .get_process("renamed")
+            .is_none()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn restart_waits_for_exit_then_starts_new_instance() {
+        let (mut pm, mut rx) = live_manager("echo started; sleep 10", 2000);
+        pm.start_process("one").unwrap();
+        assert!(wait_until(&mut pm, &mut rx, |pm| output_contains(
+            pm, "started"
+        )));
+        let gen = pm.get_process("one").unwrap().generation;
+
+        pm.restart_process("one").unwrap();
+        let process = pm.get_process("one").unwrap();
+        assert_eq!(process.status, ProcessStatus::Stopping);
+        assert_eq!(process.generation, gen);
+
+        assert!(wait_until(&mut pm, &mut rx, |pm| {
+            let p = pm.get_process("one").unwrap();
+            p.generation == gen + 1 && p.status == ProcessStatus::Running
+        }));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hooks_run_in_order_with_exit_details() {
+        let (mut pm, mut rx) = live_manager("echo main; exit 3", 500);
+        let config = &mut pm.processes.get_mut("one").unwrap().config;
+        config.before_start = Some("echo pre $PANEX_HOOK $PANEX_NAME".to_string());
+        config.on_failure = Some("echo failed $PANEX_GENERATION".to_string());
+        config.after_exit = Some("echo post $PANEX_EXIT_CODE".to_string());
+        pm.start_process("one").unwrap();
+        let gen = pm.get_process("one").unwrap().generation;
+        assert!(wait_until(&mut pm, &mut rx, |pm| output_contains(
+            pm, "post 3"
+        )));
+
+        let process = pm.get_process("one").unwrap();
+        assert_eq!(process.status, ProcessStatus::Exited(3));
+        let text = process.buffer.to_test_string();
+        let at = |s: &str| text.find(s).unwrap_or_else(|| panic!("{s:?} in {text}"));
+        assert!(at("pre before_start one") < at("main"));
+        assert!(at("main") < at(&format!("failed {gen}")));
+        assert!(at(&format!("failed {gen}")) < at("post 3"));
+
+        // Hook output is dimmed, the process's own isn't
+        let lines = process.buffer.get_all_lines();
+        let dim = |s: &str| {
+            let line = lines.iter().find(|l| l.text().starts_with(s)).unwrap();
+            line.cell(0, lines.styles())
+                .unwrap()
+                .style
+                .add_modifier
+                .contains(Modifier::DIM)
+        };
+        assert!(dim("pre"));
+        assert!(!dim("main"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn configured_env_reaches_the_process_and_its_hooks() {
+        let (mut pm, mut rx) = live_manager("echo main on $PORT", 500);
+        let config = &mut pm.processes.get_mut("one").unwrap().config;
+        config.env = vec![("PORT".to_string(), "5100".to_string())];
+        config.after_exit = Some("echo hook on $PORT".to_string());
+        pm.start_process("one").unwrap();
+        assert!(wait_until(&mut pm, &mut rx, |pm| output_contains(
+            pm,
+            "hook on 5100"
+        )));
+        assert!(output_contains(&pm, "main on 5100"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn auto_ports_are_injected_and_referable() {
+        let (mut pm, mut rx) = live_manager("echo main on $PORT", 500);
+        pm.processes.get_mut("one").unwrap().config.port = Some(Port::Auto);
+        pm.add_process(ProcessConfig {
+            command: "echo api at ${one.port}, own $WEB_PORT".to_string(),
+            port: Some(Port::Fixed(4321)),
+            port_env: Some("WEB_PORT".to_string()),
+            ..process_config("web")
+        });
+        pm.start_all().unwrap();
+        let port = pm.get_process("one").unwrap().port.unwrap();
+        assert_eq!(pm.get_process("web").unwrap().port, Some(4321));
+        let web_output = |pm: &ProcessManager| {
+            pm.get_process("web")
+                .unwrap()
+                .buffer
+                .to_test_string()
+                .contains(&format!("api at {port}, own 4321"))
+        };
+        assert!(wait_until(&mut pm, &mut rx, |pm| web_output(pm)
+            && output_contains(pm, &format!("main on {port}"))));
+
+        // The port is kept across restarts while it is free
+        pm.restart_process("one").unwrap();
+        assert_eq!(pm.get_process("one").unwrap().port, Some(port));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failed_before_start_skips_the_command() {
+        let (mut pm, mut rx) = live_manager("echo main", 500);
+        let config = &mut pm.processes.get_mut("one").unwrap().config;
+        config.before_start = Some("exit 2".to_string());
+        config.on_failure = Some("echo recovered".to_string());
+        pm.start_process("one").unwrap();
+        assert_eq!(
+            pm.get_process("one").unwrap().status,
+            ProcessStatus::Starting
+        );
+        assert!(wait_until(&mut pm, &mut rx, |pm| output_contains(
+            pm,
+            "recovered"
+        )));
+        assert!(matches!(
+            pm.get_process("one").unwrap().status,
+            ProcessStatus::Failed(_)
+        ));
+        assert!(output_contains(&pm, "before_start exited with 2"));
+        assert!(!output_contains(&pm, "main"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn on_ready_waits_for_ready_pattern() {
+        let (mut pm, mut rx) =
+            live_manager("echo booting; read line; echo listening now; sleep 10", 500);
+        let config = &mut pm.processes.get_mut("one").unwrap().config;
+        config.on_ready = Some("echo seeded".to_string());
+        config.ready_pattern = Some("listening".to_string());
+        pm.start_process("one").unwrap();
+        assert!(wait_until(&mut pm, &mut rx, |pm| output_contains(
+            pm, "booting"
+        )));
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!output_contains(&pm, "seeded"));
+
+        pm.write_to_process("one", b"go\r").unwrap();
+        assert!(wait_until(&mut pm, &mut rx, |pm| output_contains(
+            pm, "seeded"
+        )));
+        pm.kill_process("one").unwrap();
+        assert!(wait_until(&mut pm, &mut rx, |pm| pm.stopped_count() == 1));
+    }
 }
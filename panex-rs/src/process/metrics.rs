@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// How often metrics are resampled while they are shown.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Whether this platform exposes per-process metrics (`/proc`).
+pub const SUPPORTED: bool = cfg!(target_os = "linux");
+
+/// One member of a process group as of the last sample.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChildMetrics {
+    pub pid: u32,
+    /// Full command line for the detail view, otherwise the short `comm` name
+    pub command: String,
+    pub cpu_percent: f32,
+    pub rss: u64,
+}
+
+/// CPU and memory summed over a process group.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GroupMetrics {
+    pub cpu_percent: f32,
+    pub rss: u64,
+    /// Highest `rss` seen since the process was (re)started
+    pub peak_rss: u64,
+    /// Group members, largest RSS first
+    pub children: Vec<ChildMetrics>,
+    /// Listening TCP ports, only collected for the detail view
+    pub ports: Vec<u16>,
+}
+
+/// The fields of `/proc/<pid>/stat` used for sampling.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcStat {
+    pub pid: u32,
+    pub comm: String,
+    /// Single-letter state; `Z` is a zombie awaiting its parent's wait
+    pub state: char,
+    pub ppid: u32,
+    pub pgrp: u32,
+    /// utime + stime, in clock ticks
+    pub cpu_ticks: u64,
+    /// Start time in ticks since boot; distinguishes reused pids
+    pub start_time: u64,
+    pub rss_pages: u64,
+}
+
+/// Parse a `/proc/<pid>/stat` line. The command name is in parentheses and
+/// may itself contain spaces and parentheses, so fields are split after the
+/// last `)`.
+pub fn parse_stat(line: &str) -> Option<ProcStat> {
+    let open = line.find('(')?;
+    let close = line.rfind(')')?;
+    let pid = line[..open].trim().parse().ok()?;
+    let comm = line.get(open + 1..close)?.to_string();
+    // Fields after the command, starting at field 3 (state)
+    let fields: Vec<&str> = line[close + 1..].split_whitespace().collect();
+    let field = |n: usize| -> Option<u64> { fields.get(n - 3)?.parse().ok() };
+    Some(ProcStat {
+        pid,
+        comm,
+        state: fields.first()?.chars().next()?,
+        ppid: field(4)? as u32,
+        pgrp: field(5)? as u32,
+        cpu_ticks: field(14)? + field(15)?,
+        start_time: field(22)?,
+        rss_pages: field(24)?,
+    })
+}
+
+/// Socket inodes and ports of listening sockets in `/proc/net/tcp{,6}`.
+pub fn parse_listening_sockets(table: &str) -> Vec<(u64, u16)> {
+    const LISTEN: &str = "0A";
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.get(3) != Some(&LISTEN) {
+                return None;
+            }
+            let (_, port) = fields.get(1)?.rsplit_once(':')?;
+            let port = u16::from_str_radix(port, 16).ok()?;
+            let inode = fields.get(9)?.parse().ok()?;
+            Some((inode, port))
+        })
+        .collect()
+}
+
+/// Byte count in at most four characters: `512B`, `12K`, `812M`, `8.1G`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+    if bytes < 1000 {
+        return format!("{bytes}B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 999.5 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if value < 9.95 {
+        format!("{value:.1}{}", UNITS[unit])
+    } else {
+        format!("{value:.0}{}", UNITS[unit])
+    }
+}
+
+/// Samples process groups from `/proc`, remembering CPU ticks between calls
+/// so usage is averaged over the sample interval.
+pub struct MetricsSampler {
+    /// CPU ticks per (pid, start time) at the last sample
+    prev_ticks: HashMap<(u32, u64), u64>,
+    last: Option<Instant>,
+    ticks_per_sec: f64,
+    page_size: u64,
+}
+
+impl MetricsSampler {
+    pub fn new() -> Self {
+        #[cfg(unix)]
+        let (ticks_per_sec, page_size) = unsafe {
+            (
+                libc::sysconf(libc::_SC_CLK_TCK),
+                libc::sysconf(libc::_SC_PAGESIZE),
+            )
+        };
+        #[cfg(not(unix))]
+        let (ticks_per_sec, page_size) = (100, 4096);
+        Self {
+            prev_ticks: HashMap::new(),
+            last: None,
+            ticks_per_sec: ticks_per_sec.max(1) as f64,
+            page_size: page_size.max(1) as u64,
+        }
+    }
+
+    /// True once `SAMPLE_INTERVAL` has passed since the last sample.
+    pub fn due(&self) -> bool {
+        self.last.is_none_or(|t| t.elapsed() >= SAMPLE_INTERVAL)
+    }
+
+    /// Sample the process groups led by `leaders`, keyed by leader pid.
+    /// For the `detail` leader's group, full command lines and listening
+    /// ports are collected as well. The first sample reports 0% CPU.
+    pub fn sample(&mut self, leaders: &[u32], detail: Option<u32>) -> HashMap<u32, GroupMetrics> {
+        let now = Instant::now();
+        let elapsed = self.last.map(|t| now.duration_since(t).as_secs_f64());
+        self.last = Some(now);
+
+        let mut groups: HashMap<u32, GroupMetrics> = HashMap::new();
+        let mut ticks = HashMap::new();
+        for stat in read_stats() {
+            if !leaders.contains(&stat.pgrp) {
+                continue;
+            }
+            let key = (stat.pid, stat.start_time);
+            let cpu_percent = match (self.prev_ticks.get(&key), elapsed) {
+                (Some(&prev), Some(secs)) if secs > 0.0 => {
+                    let used = stat.cpu_ticks.saturating_sub(prev) as f64 / self.ticks_per_sec;
+                    (used / secs * 100.0) as f32
+                }
+                _ => 0.0,
+            };
+            ticks.insert(key, stat.cpu_ticks);
+
+            let command = if detail == Some(stat.pgrp) {
+                read_cmdline(stat.pid).unwrap_or(stat.comm)
+            } else {
+                stat.comm
+            };
+            let child = ChildMetrics {
+                pid: stat.pid,
+                command,
+                cpu_percent,
+                rss: stat.rss_pages * self.page_size,
+            };
+            let group = groups.entry(stat.pgrp).or_default();
+            group.cpu_percent += child.cpu_percent;
+            group.rss += child.rss;
+            group.children.push(child);
+        }
+        self.prev_ticks = ticks;
+
+        for group in groups.values_mut() {
+            group.children.sort_by_key(|c| std::cmp::Reverse(c.rss));
+        }
+        if let Some(group) = detail.and_then(|pid| groups.get_mut(&pid)) {
+            let pids: Vec<u32> = group.children.iter().map(|c| c.pid).collect();
+            group.ports = listening_ports(&pids, &listening_sockets());
+        }
+        groups
+    }
+}
+
+impl Default for MetricsSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Stat lines of every process visible in `/proc`.
+pub fn read_stats() -> Vec<ProcStat> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|e| {
+            e.file_name()
+                .to_str()
+                .is_some_and(|n| n.parse::<u32>().is_ok())
+        })
+        .filter_map(|e| std::fs::read_to_string(e.path().join("stat")).ok())
+        .filter_map(|line| parse_stat(&line))
+        .collect()
+}
+
+/// Command line with arguments joined by spaces (None for kernel threads).
+pub fn read_cmdline(pid: u32) -> Option<String> {
+    let raw = std::fs::read(format!("/proc/{pid}/cmdline")).ok()?;
+    let args: Vec<String> = raw
+        .split(|&b| b == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect();
+    (!args.is_empty()).then(|| args.join(" "))
+}
+
+/// Listening TCP sockets of the system, by inode.
+fn listening_sockets() -> HashMap<u64, u16> {
+    ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .flat_map(|table| parse_listening_sockets(&table))
+        .collect()
+}
+
+/// Listening TCP ports of each process group led by `leaders`, keyed by
+/// leader pid. Groups without any are left out.
+pub fn group_listening_ports(leaders: &[u32]) -> HashMap<u32, Vec<u16>> {
+    let mut members: HashMap<u32, Vec<u32>> = HashMap::new();
+    for stat in read_stats() {
+        if leaders.contains(&stat.pgrp) {
+            members.entry(stat.pgrp).or_default().push(stat.pid);
+        }
+    }
+    if members.is_empty() {
+        return HashMap::new();
+    }
+    let sockets = listening_sockets();
+    members
+        .into_iter()
+        .map(|(leader, pids)| (leader, listening_ports(&pids, &sockets)))
+        .filter(|(_, ports)| !ports.is_empty())
+        .collect()
+}
+
+/// TCP ports that any of `pids` is listening on, sorted and deduplicated.
+fn listening_ports(pids: &[u32], sockets: &HashMap<u64, u16>) -> Vec<u16> {
+    let mut ports = Vec::new();
+    for pid in pids {
+        let Ok(fds) = std::fs::read_dir(format!("/proc/{pid}/fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let Ok(target) = std::fs::read_link(fd.path()) else {
+                continue;
+            };
+            let inode = target
+                .to_str()
+                .and_then(|t| t.strip_prefix("socket:["))
+                .and_then(|t| t.strip_suffix(']'))
+                .and_then(|t| t.parse::<u64>().ok());
+            if let Some(port) = inode.and_then(|i| sockets.get(&i)) {
+                ports.push(*port);
+            }
+        }
+    }
+    ports.sort_unstable();
+    ports.dedup();
+    ports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_stat_handles_parentheses_in_command() {
+        let line = "4242 (node (vite)) S 1 4242 4242 0 -1 4194560 500 0 0 0 \
+                    120 30 0 0 20 0 11 0 98765 1234567 2048 18446744073709551615";
+        let stat = parse_stat(line).unwrap();
+        assert_eq!(stat.pid, 4242);
+        assert_eq!(stat.comm, "node (vite)");
+        assert_eq!((stat.state, stat.ppid), ('S', 1));
+        assert_eq!(stat.pgrp, 4242);
+        assert_eq!(stat.cpu_ticks, 150);
+        assert_eq!(stat.start_time, 98765);
+        assert_eq!(stat.rss_pages, 2048);
+        assert!(parse_stat("garbage").is_none());
+    }
+
+    #[test]
+    fn parse_listening_sockets_keeps_only_listen_state() {
+        let table = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:1435 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 31337 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:C350 0100007F:1435 01 00000000:00000000 00:00000000 00000000  1000        0 31338 1 0000000000000000 20 4 30 10 -1";
+        assert_eq!(parse_listening_sockets(table), [(31337, 5173)]);
+    }
+
+    #[test]
+    fn format_bytes_stays_short() {
+        assert_eq!(format_bytes(512), "512B");
+        assert_eq!(format_bytes(12 * 1024), "12K");
+        assert_eq!(format_bytes(812 * 1024 * 1024), "812M");
+        assert_eq!(format_bytes(8_700_000_000), "8.1G");
+        assert_eq!(format_bytes(1023 * 1024), "1.0M");
+        assert_eq!(format_bytes(1000), "1.0K");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn samples_own_process_group() {
+        let pgrp = unsafe { libc::getpgrp() } as u32;
+        let mut sampler = MetricsSampler::new();
+        let groups = sampler.sample(&[pgrp], Some(pgrp));
+        let group = &groups[&pgrp];
+        assert!(group.rss > 0);
+        let me = std::process::id();
+        let child = group.children.iter().find(|c| c.pid == me).unwrap();
+        // The detail view reads the full command line
+        assert!(child.command.contains(' ') || child.command.contains('/'));
+    }
+}
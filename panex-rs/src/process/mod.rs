@@ -1,7 +1,15 @@
 pub mod buffer;
+pub mod export;
+pub mod grid;
+pub mod line;
+pub mod links;
 pub mod manager;
+pub mod metrics;
 pub mod pty;
+pub mod tree;
+pub mod wrap;
 
 pub use buffer::TerminalBuffer;
 pub use manager::{ManagedProcess, ProcessManager};
 pub use pty::PtyHandle;
+pub use wrap::WrapIndex;
@@ -1,8 +1,28 @@
+use crate::config::Signal;
 use anyhow::{anyhow, Result};
 use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
 use std::io::{Read, Write};
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 
+/// Program and arguments that run `command` through the user's shell.
+pub fn shell_invocation(command: &str) -> (String, [String; 2]) {
+    if cfg!(windows) {
+        ("cmd".to_string(), ["/C".to_string(), command.to_string()])
+    } else {
+        let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
+        (shell, ["-c".to_string(), command.to_string()])
+    }
+}
+
+/// How a child exited.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExitInfo {
+    pub code: i32,
+    /// Name of the signal that ended it, if any
+    pub signal: Option<String>,
+}
+
 pub struct PtyHandle {
     master: Arc<Mutex<Box<dyn MasterPty + Send>>>,
     writer: Arc<Mutex<Box<dyn Write + Send>>>,
@@ -11,7 +31,16 @@ pub struct PtyHandle {
 }
 
 impl PtyHandle {
-    pub fn spawn(command: &str, cols: u16, rows: u16) -> Result<Self> {
+    /// Run `command` through the shell in a new PTY, with `env` added to the
+    /// inherited environment. A relative `cwd_override` is resolved against
+    /// panex's own working directory.
+    pub fn spawn(
+        command: &str,
+        cols: u16,
+        rows: u16,
+        env: &[(&str, String)],
+        cwd_override: Option<&Path>,
+    ) -> Result<Self> {
         let pty_system = native_pty_system();
 
         let pair = pty_system
@@ -23,18 +52,14 @@ impl PtyHandle {
             })
             .map_err(|e| anyhow!("Failed to open PTY: {}", e))?;
 
-        let cwd = std::env::current_dir().map_err(|e| anyhow!("Failed to get cwd: {}", e))?;
+        let mut cwd = std::env::current_dir().map_err(|e| anyhow!("Failed to get cwd: {}", e))?;
+        if let Some(dir) = cwd_override {
+            cwd = cwd.join(dir);
+        }
 
-        let mut cmd = if cfg!(windows) {
-            let mut cmd = CommandBuilder::new("cmd");
-            cmd.args(["/C", command]);
-            cmd
-        } else {
-            let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
-            let mut cmd = CommandBuilder::new(&shell);
-            cmd.args(["-c", command]);
-            cmd
-        };
+        let (program, args) = shell_invocation(command);
+        let mut cmd = CommandBuilder::new(program);
+        cmd.args(args);
 
         cmd.cwd(cwd);
 
@@ -42,6 +67,9 @@ impl PtyHandle {
         for (key, value) in std::env::vars() {
             cmd.env(key, value);
         }
+        for (key, value) in env {
+            cmd.env(key, value);
+        }
 
         let child = pair
             .slave
@@ -88,49 +116,42 @@ impl PtyHandle {
         Ok(())
     }
 
-    pub fn kill(&self, _timeout_ms: u64) -> Result<()> {
-        let mut child = self.child.lock().map_err(|_| anyhow!("Lock poisoned"))?;
-
-        // Try to kill the process group first (kills children too)
-        #[cfg(unix)]
-        if let Some(pid) = child.process_id() {
-            // Send SIGTERM for graceful shutdown
-            unsafe {
-                libc::kill(-(pid as i32), libc::SIGTERM);
-            }
-
-            // Poll every 10ms until timeout
-            let iterations = (_timeout_ms / 10).max(1);
-            for _ in 0..iterations {
-                std::thread::sleep(std::time::Duration::from_millis(10));
-                if let Ok(Some(_)) = child.try_wait() {
-                    return Ok(());
-                }
-            }
+    /// Child pid, which is also its process group id (the child leads its own session).
+    pub fn pid(&self) -> Option<u32> {
+        self.child.lock().ok()?.process_id()
+    }
 
-            // Force kill if still running
-            unsafe {
-                libc::kill(-(pid as i32), libc::SIGKILL);
-            }
+    /// Non-blocking check for exit. Returns how the child exited once it has
+    /// been reaped, None while it is still running.
+    pub fn try_wait(&self) -> Option<ExitInfo> {
+        let mut child = self.child.lock().ok()?;
+        match child.try_wait() {
+            Ok(Some(status)) => Some(ExitInfo {
+                code: status.exit_code() as i32,
+                signal: status.signal().map(str::to_string),
+            }),
+            _ => None,
         }
-
-        // Also call the standard kill as fallback
-        let _ = child.kill();
-
-        // Non-blocking wait to reap zombie
-        let _ = child.try_wait();
-
-        Ok(())
     }
 
-    /// Send SIGTERM without waiting - for progressive shutdown
-    pub fn terminate(&self) -> Result<()> {
-        let _child = self.child.lock().map_err(|_| anyhow!("Lock poisoned"))?;
+    /// Send `signal` to the process group without waiting. Platforms
+    /// without signals have no graceful stop, so any terminating signal
+    /// kills outright and pause/resume is unsupported.
+    pub fn signal(&self, signal: Signal) -> Result<()> {
+        #[allow(unused_mut)]
+        let mut child = self.child.lock().map_err(|_| anyhow!("Lock poisoned"))?;
 
         #[cfg(unix)]
-        if let Some(pid) = _child.process_id() {
+        if let Some(pid) = child.process_id() {
             unsafe {
-                libc::kill(-(pid as i32), libc::SIGTERM);
+                libc::kill(-(pid as i32), signal.number());
+            }
+        }
+        #[cfg(not(unix))]
+        match signal {
+            Signal::Stop | Signal::Cont => return Err(anyhow!("Signals are not supported")),
+            _ => {
+                let _ = child.kill();
             }
         }
 
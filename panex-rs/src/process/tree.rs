@@ -0,0 +1,304 @@
+use super::metrics::{self, ProcStat, SAMPLE_INTERVAL};
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+
+/// Owner shown for adopted processes whose parent was never seen in a scan.
+pub const UNKNOWN_OWNER: &str = "?";
+
+/// One process in a managed process's descendant tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeEntry {
+    pub pid: u32,
+    pub pgrp: u32,
+    /// Start time in ticks since boot; distinguishes reused pids
+    pub start_time: u64,
+    /// Nesting below the managed process (0 = the managed process itself)
+    pub depth: usize,
+    pub command: String,
+    /// Outside the managed process's group, so stopping it won't reach this one
+    pub escaped: bool,
+    /// Re-parented to panex after its own parent exited
+    pub adopted: bool,
+}
+
+/// Descendant trees of all managed processes, rebuilt from `/proc`.
+///
+/// The tree is walked through parent pids. When a parent exits its children
+/// are re-parented to panex (see `become_subreaper`), and the owner recorded
+/// in earlier scans keeps them attributed to the right managed process.
+#[derive(Debug, Default)]
+pub struct ProcessTree {
+    /// Managed process name -> its tree in depth-first order
+    trees: HashMap<String, Vec<TreeEntry>>,
+    /// Owner of every process seen in the last scan, keyed by (pid, start time)
+    owners: HashMap<(u32, u64), String>,
+    last: Option<Instant>,
+}
+
+impl ProcessTree {
+    /// True once `SAMPLE_INTERVAL` has passed since the last scan.
+    pub fn due(&self) -> bool {
+        self.last.is_none_or(|t| t.elapsed() >= SAMPLE_INTERVAL)
+    }
+
+    /// Rescan `/proc`. `leaders` pairs managed process names with their pids;
+    /// `helpers` are panex's own children (stop commands), which are skipped.
+    /// Adopted processes that have exited are reaped here, if an earlier scan
+    /// saw them under a managed process.
+    pub fn scan(&mut self, leaders: &[(String, u32)], helpers: &[u32]) {
+        self.last = Some(Instant::now());
+        let reap = self.update(&metrics::read_stats(), leaders, helpers, std::process::id());
+        #[cfg(unix)]
+        for pid in reap {
+            unsafe {
+                libc::waitpid(pid as i32, std::ptr::null_mut(), libc::WNOHANG);
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = reap;
+    }
+
+    /// Rebuild the trees from `stats`. Returns adopted zombies to reap.
+    fn update(
+        &mut self,
+        stats: &[ProcStat],
+        leaders: &[(String, u32)],
+        helpers: &[u32],
+        own_pid: u32,
+    ) -> Vec<u32> {
+        let mut children: HashMap<u32, Vec<&ProcStat>> = HashMap::new();
+        for stat in stats {
+            children.entry(stat.ppid).or_default().push(stat);
+        }
+        for list in children.values_mut() {
+            list.sort_by_key(|s| s.pid);
+        }
+        let leader_pids: HashSet<u32> = leaders.iter().map(|(_, pid)| *pid).collect();
+
+        let mut walk = Walk {
+            children: &children,
+            trees: HashMap::new(),
+            owners: HashMap::new(),
+            seen: HashSet::new(),
+        };
+        for (name, pid) in leaders {
+            if let Some(root) = stats.iter().find(|s| s.pid == *pid && s.state != 'Z') {
+                walk.visit(name, root, *pid, 0, false);
+            }
+        }
+
+        let mut reap = Vec::new();
+        let adopted = children.get(&own_pid).into_iter().flatten();
+        for stat in adopted.filter(|s| !leader_pids.contains(&s.pid) && !helpers.contains(&s.pid)) {
+            if stat.state == 'Z' {
+                // Only descendants of a managed process: panex's own children
+                // (pipe commands, the browser opener) are waited on by their
+                // handles and would show up here unowned
+                let owner = self.owners.get(&(stat.pid, stat.start_time));
+                if owner.is_some_and(|owner| owner != UNKNOWN_OWNER) {
+                    reap.push(stat.pid);
+                }
+                continue;
+            }
+            let owner = self
+                .owners
+                .get(&(stat.pid, stat.start_time))
+                .map_or(UNKNOWN_OWNER, String::as_str);
+            let leader = leaders
+                .iter()
+                .find(|(name, _)| name == owner)
+                .map_or(0, |(_, pid)| *pid);
+            walk.visit(owner, stat, leader, 1, true);
+        }
+
+        self.trees = walk.trees;
+        self.owners = walk.owners;
+        reap
+    }
+
+    /// Tree of one managed process, the process itself first.
+    pub fn entries(&self, name: &str) -> &[TreeEntry] {
+        self.trees.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every descendant outside its managed process's group, with its owner.
+    pub fn escaped(&self) -> Vec<(String, TreeEntry)> {
+        let mut escaped: Vec<_> = self
+            .trees
+            .iter()
+            .flat_map(|(name, entries)| {
+                entries
+                    .iter()
+                    .filter(|e| e.escaped)
+                    .map(move |e| (name.clone(), e.clone()))
+            })
+            .collect();
+        escaped.sort_by_key(|(_, e)| e.pid);
+        escaped
+    }
+}
+
+/// Depth-first walk state shared by all roots of one scan.
+struct Walk<'a> {
+    children: &'a HashMap<u32, Vec<&'a ProcStat>>,
+    trees: HashMap<String, Vec<TreeEntry>>,
+    owners: HashMap<(u32, u64), String>,
+    seen: HashSet<u32>,
+}
+
+impl Walk<'_> {
+    fn visit(&mut self, owner: &str, stat: &ProcStat, leader: u32, depth: usize, adopted: bool) {
+        if stat.state == 'Z' || !self.seen.insert(stat.pid) {
+            return;
+        }
+        self.owners
+            .insert((stat.pid, stat.start_time), owner.to_string());
+        let command = metrics::read_cmdline(stat.pid).unwrap_or_else(|| stat.comm.clone());
+        self.trees
+            .entry(owner.to_string())
+            .or_default()
+            .push(TreeEntry {
+                pid: stat.pid,
+                pgrp: stat.pgrp,
+                start_time: stat.start_time,
+                depth,
+                command,
+                escaped: stat.pgrp != leader,
+                adopted,
+            });
+        let children = self.children;
+        for child in children.get(&stat.pid).into_iter().flatten() {
+            self.visit(owner, child, leader, depth + 1, false);
+        }
+    }
+}
+
+/// Make panex the subreaper of its descendants, so processes that daemonize
+/// are re-parented to panex rather than init and stay in the tree.
+pub fn become_subreaper() {
+    #[cfg(target_os = "linux")]
+    unsafe {
+        libc::prctl(libc::PR_SET_CHILD_SUBREAPER, 1, 0, 0, 0);
+    }
+}
+
+/// Whether `entry` still refers to the same live process.
+pub fn is_alive(entry: &TreeEntry) -> bool {
+    std::fs::read_to_string(format!("/proc/{}/stat", entry.pid))
+        .ok()
+        .and_then(|line| metrics::parse_stat(&line))
+        .is_some_and(|s| s.start_time == entry.start_time && s.state != 'Z')
+}
+
+/// Send `signal` to an escaped process, and to its whole group when it leads one.
+#[cfg(unix)]
+pub fn signal(entry: &TreeEntry, signal: i32) {
+    if !is_alive(entry) {
+        return;
+    }
+    let target = if entry.pgrp == entry.pid {
+        -(entry.pid as i32)
+    } else {
+        entry.pid as i32
+    };
+    unsafe {
+        libc::kill(target, signal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stat(pid: u32, ppid: u32, pgrp: u32, comm: &str) -> ProcStat {
+        ProcStat {
+            pid,
+            comm: comm.to_string(),
+            state: 'S',
+            ppid,
+            pgrp,
+            cpu_ticks: 0,
+            start_time: pid as u64,
+            rss_pages: 0,
+        }
+    }
+
+    fn pids(tree: &ProcessTree, name: &str) -> Vec<(u32, usize, bool, bool)> {
+        tree.entries(name)
+            .iter()
+            .map(|e| (e.pid, e.depth, e.escaped, e.adopted))
+            .collect()
+    }
+
+    // Pids far above any real pid so read_cmdline falls back to comm
+    const PANEX: u32 = 4_000_000;
+    const WEB: u32 = 4_000_100;
+
+    #[test]
+    fn walks_descendants_and_flags_group_escapes() {
+        let mut tree = ProcessTree::default();
+        let stats = [
+            stat(PANEX, 1, PANEX, "panex"),
+            stat(WEB, PANEX, WEB, "sh"),
+            stat(WEB + 1, WEB, WEB, "node"),
+            stat(WEB + 2, WEB + 1, WEB + 2, "esbuild"),
+            stat(WEB + 3, WEB + 2, WEB + 2, "worker"),
+            stat(42, 1, 42, "unrelated"),
+        ];
+        let leaders = [("web".to_string(), WEB)];
+        assert!(tree.update(&stats, &leaders, &[], PANEX).is_empty());
+
+        assert_eq!(
+            pids(&tree, "web"),
+            [
+                (WEB, 0, false, false),
+                (WEB + 1, 1, false, false),
+                (WEB + 2, 2, true, false),
+                (WEB + 3, 3, true, false),
+            ]
+        );
+        assert_eq!(tree.entries("web")[2].command, "esbuild");
+        let escaped: Vec<u32> = tree.escaped().iter().map(|(_, e)| e.pid).collect();
+        assert_eq!(escaped, [WEB + 2, WEB + 3]);
+    }
+
+    #[test]
+    fn adopted_orphans_keep_their_owner() {
+        let mut tree = ProcessTree::default();
+        let leaders = [("web".to_string(), WEB)];
+        tree.update(
+            &[
+                stat(WEB, PANEX, WEB, "sh"),
+                stat(WEB + 1, WEB, WEB + 1, "daemon"),
+            ],
+            &leaders,
+            &[],
+            PANEX,
+        );
+
+        // The shell exits; its daemon is re-parented to panex, and an
+        // orphan nobody saw before shows up under the unknown owner
+        let zombie = |pid: u32| ProcStat {
+            state: 'Z',
+            ..stat(pid, PANEX, pid, "gone")
+        };
+        let reap = tree.update(
+            &[
+                stat(WEB + 1, PANEX, WEB + 1, "daemon"),
+                stat(WEB + 9, PANEX, WEB + 9, "stray"),
+                zombie(WEB + 5),
+            ],
+            &leaders,
+            &[],
+            PANEX,
+        );
+        // Never seen in a tree, so possibly panex's own child being waited on
+        assert!(reap.is_empty());
+        assert_eq!(pids(&tree, "web"), [(WEB + 1, 1, true, true)]);
+        assert_eq!(pids(&tree, UNKNOWN_OWNER), [(WEB + 9, 1, true, true)]);
+
+        // Once they exit, only the daemon seen under `web` is reaped
+        let reap = tree.update(&[zombie(WEB + 1), zombie(WEB + 9)], &leaders, &[], PANEX);
+        assert_eq!(reap, [WEB + 1]);
+    }
+}
@@ -0,0 +1,162 @@
+use super::buffer::{Line, LineChanges, LineView};
+use std::collections::VecDeque;
+
+/// Display rows a line occupies when wrapped at `width` (empty lines take one).
+pub fn wrapped_height(line: &Line, width: usize) -> usize {
+    if width == 0 || line.is_empty() {
+        1
+    } else {
+        line.len().div_ceil(width)
+    }
+}
+
+/// Maps wrapped display rows to buffer positions.
+///
+/// Stores the first display row of every buffer line. Rows are absolute
+/// (offset by the first line's start) so dropping lines from the front is
+/// O(1), and a sync only recomputes lines from the first changed one.
+#[derive(Debug, Clone, Default)]
+pub struct WrapIndex {
+    width: usize,
+    starts: VecDeque<usize>,
+    /// Display row just past the last indexed line
+    end: usize,
+}
+
+impl WrapIndex {
+    /// Build an index for `lines` wrapped at `width`.
+    pub fn new(lines: LineView<'_>, width: usize) -> Self {
+        let mut index = Self {
+            width,
+            starts: VecDeque::with_capacity(lines.len()),
+            end: 0,
+        };
+        index.extend(lines, 0);
+        index
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Bring the index up to date with `lines` after `changes`. A width change
+    /// rebuilds from scratch.
+    pub fn sync(&mut self, lines: LineView<'_>, changes: LineChanges, width: usize) {
+        if width != self.width {
+            *self = Self::new(lines, width);
+            return;
+        }
+        for _ in 0..changes.popped_front {
+            if self.starts.pop_front().is_none() {
+                break;
+            }
+        }
+        let from = changes
+            .first_changed
+            .unwrap_or(usize::MAX)
+            .min(self.starts.len())
+            .min(lines.len());
+        if from < self.starts.len() {
+            self.end = self.starts[from];
+            self.starts.truncate(from);
+        }
+        self.extend(lines, from);
+    }
+
+    /// Display row at which buffer `row` starts (total rows for `row == len`).
+    pub fn row_start(&self, row: usize) -> usize {
+        self.starts.get(row).copied().unwrap_or(self.end) - self.base()
+    }
+
+    /// Display rows taken by the first `line_count` buffer lines (at least 1).
+    pub fn total_rows(&self, line_count: usize) -> usize {
+        self.row_start(line_count).max(1)
+    }
+
+    /// Buffer row and starting column shown on `display_row`. Rows past the
+    /// end map into the last line.
+    pub fn locate(&self, display_row: usize) -> (usize, usize) {
+        if self.starts.is_empty() {
+            return (0, 0);
+        }
+        let target = display_row + self.base();
+        let row = self
+            .starts
+            .partition_point(|&start| start <= target)
+            .saturating_sub(1);
+        (row, (target - self.starts[row]) * self.width)
+    }
+
+    fn base(&self) -> usize {
+        self.starts.front().copied().unwrap_or(self.end)
+    }
+
+    fn extend(&mut self, lines: LineView<'_>, from: usize) {
+        for line in lines.iter().skip(from) {
+            self.starts.push_back(self.end);
+            self.end += wrapped_height(line, self.width);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::TerminalBuffer;
+
+    /// Sync `index` from `buf` and check it matches a fresh build.
+    fn sync_and_check(index: &mut WrapIndex, buf: &mut TerminalBuffer, width: usize) {
+        let changes = buf.take_line_changes();
+        index.sync(buf.get_all_lines(), changes, width);
+        let fresh = WrapIndex::new(buf.get_all_lines(), width);
+        let lines = buf.get_all_lines().len();
+        let synced: Vec<_> = (0..=lines).map(|r| index.row_start(r)).collect();
+        let expected: Vec<_> = (0..=lines).map(|r| fresh.row_start(r)).collect();
+        assert_eq!(synced, expected);
+    }
+
+    #[test]
+    fn maps_display_rows_to_line_chunks() {
+        let mut buf = TerminalBuffer::new(80, 24);
+        buf.write(b"0123456789\r\n\r\nabc");
+        let index = WrapIndex::new(buf.get_all_lines(), 4);
+        // "0123456789" takes 3 rows, the empty line 1, "abc" 1
+        assert_eq!(index.total_rows(3), 5);
+        assert_eq!(index.locate(0), (0, 0));
+        assert_eq!(index.locate(2), (0, 8));
+        assert_eq!(index.locate(3), (1, 0));
+        assert_eq!(index.locate(4), (2, 0));
+        assert_eq!(index.row_start(2), 4);
+    }
+
+    #[test]
+    fn incremental_sync_matches_rebuild() {
+        let mut buf = TerminalBuffer::with_max_scrollback(20, 5, 30);
+        let mut index = WrapIndex::default();
+        sync_and_check(&mut index, &mut buf, 7);
+
+        // Appends, then scrollback trimming drops lines from the front
+        for i in 0..40 {
+            buf.write(format!("line {i} {}\r\n", "x".repeat(i % 13)).as_bytes());
+            if i % 7 == 0 {
+                sync_and_check(&mut index, &mut buf, 7);
+            }
+        }
+        sync_and_check(&mut index, &mut buf, 7);
+
+        // Cursor-addressed edits, erase and line insert/delete near the bottom
+        buf.write(b"\x1b[2;1Hshort\x1b[K\x1b[4;1H\x1b[L\x1b[1;1H\x1b[2M");
+        sync_and_check(&mut index, &mut buf, 7);
+
+        // Eviction and a width change
+        buf.evict_oldest(10);
+        sync_and_check(&mut index, &mut buf, 7);
+        sync_and_check(&mut index, &mut buf, 3);
+
+        // Alternate screen clears and restores
+        buf.write(b"\x1b[?1049hfull screen\x1b[?1049l");
+        sync_and_check(&mut index, &mut buf, 3);
+        buf.clear_for_restart();
+        sync_and_check(&mut index, &mut buf, 3);
+    }
+}
@@ -0,0 +1,120 @@
+use crate::config::ProcessConfig;
+use anyhow::{bail, Result};
+
+/// Procfile read by `--procfile` without a path.
+pub const DEFAULT_PROCFILE: &str = "Procfile";
+
+/// First port handed out when neither `.env` nor the environment sets `PORT`.
+pub const DEFAULT_BASE_PORT: u16 = 5000;
+
+/// Instance counts per Procfile entry, in the order given.
+pub type Formation = Vec<(String, usize)>;
+
+/// Ports reserved per process type: `web` gets 5000, 5001…, `worker` 5100…
+const PORTS_PER_TYPE: u16 = 100;
+
+/// Most instances of one type: more would take the next type's ports.
+const MAX_INSTANCES: usize = PORTS_PER_TYPE as usize;
+
+/// Parse `name: command` lines. Blank lines and `#` comments are skipped.
+pub fn parse_procfile(text: &str) -> Result<Vec<(String, String)>> {
+    let mut entries: Vec<(String, String)> = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((name, command)) = line.split_once(':') else {
+            bail!("line {}: expected `name: command`", number + 1);
+        };
+        let (name, command) = (name.trim(), command.trim());
+        let valid = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+        if name.is_empty() || !name.chars().all(valid) {
+            bail!("line {}: invalid process name {name:?}", number + 1);
+        }
+        if command.is_empty() {
+            bail!("line {}: {name} has no command", number + 1);
+        }
+        if entries.iter().any(|(n, _)| n == name) {
+            bail!("line {}: duplicate process name {name:?}", number + 1);
+        }
+        entries.push((name.to_string(), command.to_string()));
+    }
+    Ok(entries)
+}
+
+/// Parse a formation like `web=2,worker=1`. `all=N` sets the default count
+/// for types not listed. Counts go up to 100.
+pub fn parse_formation(s: &str) -> Result<Formation, String> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let (name, count) = part
+                .split_once('=')
+                .ok_or_else(|| format!("expected name=count, got {part:?}"))?;
+            let count = count
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid count in {part:?}"))?;
+            if count > MAX_INSTANCES {
+                return Err(format!("{part:?} is more than {MAX_INSTANCES} instances"));
+            }
+            Ok((name.trim().to_string(), count))
+        })
+        .collect()
+}
+
+/// Expand Procfile entries into processes, the way foreman does. Each entry
+/// runs `formation` times (default once); with more than one instance they
+/// are named `<name>.1`, `<name>.2`, … Every instance gets `env` plus its own
+/// `PORT`: `base_port`, plus 100 per entry and 1 per instance.
+pub fn procfile_processes(
+    entries: &[(String, String)],
+    formation: &[(String, usize)],
+    base_port: u16,
+    env: &[(String, String)],
+) -> Result<Vec<ProcessConfig>> {
+    for (name, _) in formation {
+        if name != "all" && !entries.iter().any(|(n, _)| n == name) {
+            bail!("formation names {name:?}, which is not in the Procfile");
+        }
+    }
+    let count_for = |name: &str| {
+        let lookup = |key: &str| formation.iter().rev().find(|(n, _)| n == key);
+        lookup(name)
+            .or_else(|| lookup("all"))
+            .map_or(1, |(_, c)| *c)
+    };
+
+    let mut processes = Vec::new();
+    for (index, (name, command)) in entries.iter().enumerate() {
+        let count = count_for(name);
+        for instance in 0..count {
+            let port = u16::try_from(index)
+                .ok()
+                .and_then(|index| index.checked_mul(PORTS_PER_TYPE))
+                .and_then(|offset| base_port.checked_add(offset))
+                .zip(u16::try_from(instance).ok())
+                .and_then(|(port, instance)| port.checked_add(instance));
+            let Some(port) = port else {
+                bail!("ran out of ports for {name} (base port {base_port})");
+            };
+            let mut env = env.to_vec();
+            env.retain(|(key, _)| key != "PORT");
+            env.push(("PORT".to_string(), port.to_string()));
+            let name = if count == 1 {
+                name.clone()
+            } else {
+                format!("{name}.{}", instance + 1)
+            };
+            processes.push(ProcessConfig {
+                name,
+                command: command.clone(),
+                env,
+                ..ProcessConfig::default()
+            });
+        }
+    }
+    Ok(processes)
+}
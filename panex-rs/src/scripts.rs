@@ -0,0 +1,349 @@
+use crate::config::ProcessConfig;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Package manager that runs the scripts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageManager {
+    Npm,
+    Pnpm,
+    Yarn,
+    Bun,
+}
+
+impl PackageManager {
+    /// Lockfiles and the package manager that writes them.
+    const LOCKFILES: [(&'static str, PackageManager); 5] = [
+        ("pnpm-lock.yaml", PackageManager::Pnpm),
+        ("yarn.lock", PackageManager::Yarn),
+        ("bun.lock", PackageManager::Bun),
+        ("bun.lockb", PackageManager::Bun),
+        ("package-lock.json", PackageManager::Npm),
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PackageManager::Npm => "npm",
+            PackageManager::Pnpm => "pnpm",
+            PackageManager::Yarn => "yarn",
+            PackageManager::Bun => "bun",
+        }
+    }
+
+    /// From a `packageManager` field like `pnpm@9.1.0`.
+    fn from_field(field: &str) -> Option<Self> {
+        let name = field.split('@').next()?;
+        [Self::Npm, Self::Pnpm, Self::Yarn, Self::Bun]
+            .into_iter()
+            .find(|pm| pm.name() == name)
+    }
+
+    /// The package manager of the package in `dir`: its `packageManager`
+    /// field, else the first lockfile found walking up from `dir`, else npm.
+    pub fn detect(dir: &Path, field: Option<&str>) -> Self {
+        if let Some(pm) = field.and_then(Self::from_field) {
+            return pm;
+        }
+        dir.ancestors()
+            .find_map(|dir| {
+                Self::LOCKFILES
+                    .iter()
+                    .find(|(file, _)| dir.join(file).is_file())
+                    .map(|(_, pm)| *pm)
+            })
+            .unwrap_or(PackageManager::Npm)
+    }
+
+    pub fn run_command(self, script: &str) -> String {
+        format!("{} run {script}", self.name())
+    }
+}
+
+/// The parts of a `package.json` panex uses.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    name: Option<String>,
+    #[serde(default)]
+    scripts: serde_json::Map<String, serde_json::Value>,
+    workspaces: Option<Workspaces>,
+    package_manager: Option<String>,
+}
+
+/// `workspaces` is a list of globs, or (yarn) an object holding one.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Workspaces {
+    List(Vec<String>),
+    Object {
+        #[serde(default)]
+        packages: Vec<String>,
+    },
+}
+
+/// A directory with a `package.json`.
+#[derive(Debug)]
+pub struct Package {
+    pub dir: PathBuf,
+    pub name: Option<String>,
+    /// Script names in file order
+    pub scripts: Vec<String>,
+    /// Workspace globs from `package.json` or `pnpm-workspace.yaml`
+    pub workspaces: Vec<String>,
+    package_manager: Option<String>,
+}
+
+impl Package {
+    pub fn read(dir: &Path) -> Result<Self> {
+        let path = dir.join("package.json");
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let manifest: Manifest =
+            serde_json::from_str(&text).with_context(|| format!("invalid {}", path.display()))?;
+        let mut workspaces = match manifest.workspaces {
+            Some(Workspaces::List(globs)) | Some(Workspaces::Object { packages: globs }) => globs,
+            None => Vec::new(),
+        };
+        if let Ok(text) = std::fs::read_to_string(dir.join("pnpm-workspace.yaml")) {
+            workspaces.extend(parse_pnpm_workspace(&text));
+        }
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            name: manifest.name,
+            scripts: manifest.scripts.keys().cloned().collect(),
+            workspaces,
+            package_manager: manifest.package_manager,
+        })
+    }
+
+    pub fn package_manager(&self) -> PackageManager {
+        PackageManager::detect(&self.dir, self.package_manager.as_deref())
+    }
+
+    /// Packages matched by the workspace globs, sorted by path.
+    pub fn workspace_packages(&self) -> Result<Vec<Package>> {
+        let mut dirs: Vec<PathBuf> = Vec::new();
+        for glob in &self.workspaces {
+            match glob.strip_prefix('!') {
+                Some(excluded) => {
+                    let excluded = expand_dirs(&self.dir, excluded);
+                    dirs.retain(|dir| !excluded.contains(dir));
+                }
+                None => dirs.extend(expand_dirs(&self.dir, glob)),
+            }
+        }
+        dirs.sort();
+        dirs.dedup();
+        dirs.into_iter()
+            .filter(|dir| dir.join("package.json").is_file())
+            .map(|dir| Package::read(&dir))
+            .collect()
+    }
+}
+
+/// The `packages:` list of a `pnpm-workspace.yaml`.
+pub fn parse_pnpm_workspace(text: &str) -> Vec<String> {
+    let mut in_packages = false;
+    let mut globs = Vec::new();
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if !line.starts_with([' ', '\t', '-']) {
+            in_packages = trimmed == "packages:";
+            continue;
+        }
+        if let Some(item) = trimmed.strip_prefix('-').filter(|_| in_packages) {
+            let item = item.trim().trim_matches(|c| c == '"' || c == '\'');
+            globs.push(item.to_string());
+        }
+    }
+    globs
+}
+
+/// Shell-style match of `text` against `pattern` with `*` and `?`.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let (pattern, text): (Vec<char>, Vec<char>) =
+        (pattern.chars().collect(), text.chars().collect());
+    let (mut p, mut t) = (0, 0);
+    // Where the last `*` was, and the text position it is matched up to
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Directories under `root` matching a workspace glob. Each segment may use
+/// `*` and `?`; `**` matches any depth. `node_modules` and hidden
+/// directories are skipped.
+fn expand_dirs(root: &Path, glob: &str) -> Vec<PathBuf> {
+    let mut dirs = vec![root.to_path_buf()];
+    for segment in glob.trim_start_matches("./").split('/') {
+        if segment.is_empty() || segment == "." {
+            continue;
+        }
+        dirs = dirs
+            .iter()
+            .flat_map(|dir| match segment {
+                "**" => descendants(dir),
+                _ if segment.contains(['*', '?']) => subdirs(dir)
+                    .into_iter()
+                    .filter(|d| {
+                        d.file_name()
+                            .and_then(|n| n.to_str())
+                            .is_some_and(|n| glob_match(segment, n))
+                    })
+                    .collect(),
+                _ => Some(dir.join(segment))
+                    .filter(|d| d.is_dir())
+                    .into_iter()
+                    .collect(),
+            })
+            .collect();
+    }
+    dirs
+}
+
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .filter(|e| {
+            let name = e.file_name();
+            let name = name.to_string_lossy();
+            name != "node_modules" && !name.starts_with('.')
+        })
+        .map(|e| e.path())
+        .collect()
+}
+
+/// `dir` and every directory below it.
+fn descendants(dir: &Path) -> Vec<PathBuf> {
+    let mut all = vec![dir.to_path_buf()];
+    let mut i = 0;
+    while i < all.len() {
+        let children = subdirs(&all[i]);
+        all.extend(children);
+        i += 1;
+    }
+    all
+}
+
+/// Nearest directory from `start` upwards with a `package.json`.
+pub fn find_package(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| dir.join("package.json").is_file())
+        .map(Path::to_path_buf)
+}
+
+/// Nearest directory from `start` upwards whose package declares workspaces.
+fn find_workspace_root(start: &Path) -> Result<Option<Package>> {
+    for dir in start.ancestors() {
+        if dir.join("package.json").is_file() {
+            let package = Package::read(dir)?;
+            if !package.workspaces.is_empty() {
+                return Ok(Some(package));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// `dir` relative to `cwd` when it is below it; None for `cwd` itself.
+fn relative_dir(dir: &Path, cwd: &Path) -> Option<PathBuf> {
+    match dir.strip_prefix(cwd) {
+        Ok(rel) if rel.as_os_str().is_empty() => None,
+        Ok(rel) => Some(rel.to_path_buf()),
+        Err(_) => Some(dir.to_path_buf()),
+    }
+}
+
+/// One process per script of the nearest `package.json` matching any of
+/// `patterns` (globs), named after the script, in file order. A pattern
+/// that matches nothing is an error.
+pub fn script_processes(cwd: &Path, patterns: &[String]) -> Result<Vec<ProcessConfig>> {
+    let Some(dir) = find_package(cwd) else {
+        bail!("no package.json in {} or above", cwd.display());
+    };
+    let package = Package::read(&dir)?;
+    for pattern in patterns {
+        if !package.scripts.iter().any(|s| glob_match(pattern, s)) {
+            bail!(
+                "no script matches {pattern:?} in {}",
+                dir.join("package.json").display()
+            );
+        }
+    }
+    let pm = package.package_manager();
+    Ok(package
+        .scripts
+        .iter()
+        .filter(|script| patterns.iter().any(|p| glob_match(p, script)))
+        .map(|script| ProcessConfig {
+            name: script.clone(),
+            command: pm.run_command(script),
+            cwd: relative_dir(&dir, cwd),
+            ..ProcessConfig::default()
+        })
+        .collect())
+}
+
+/// One process per workspace package that has `script`, named after the
+/// package and run in its directory.
+pub fn workspace_script_processes(cwd: &Path, script: &str) -> Result<Vec<ProcessConfig>> {
+    let Some(root) = find_workspace_root(cwd)? else {
+        bail!(
+            "no package.json with workspaces in {} or above",
+            cwd.display()
+        );
+    };
+    let pm = root.package_manager();
+    let processes: Vec<ProcessConfig> = root
+        .workspace_packages()?
+        .into_iter()
+        .filter(|package| package.scripts.iter().any(|s| s == script))
+        .map(|package| {
+            let name = package.name.clone().unwrap_or_else(|| {
+                package
+                    .dir
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            });
+            ProcessConfig {
+                name,
+                command: pm.run_command(script),
+                cwd: relative_dir(&package.dir, cwd),
+                ..ProcessConfig::default()
+            }
+        })
+        .collect();
+    if processes.is_empty() {
+        bail!("no workspace package has a {script:?} script");
+    }
+    Ok(processes)
+}
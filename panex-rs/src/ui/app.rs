@@ -1,10 +1,15 @@
+use crate::input::copy_mode::CopyMode;
+use crate::input::pipe::{PipeJob, PipeOutput};
 use crate::input::selection::BufferPos;
 use crate::input::SelectionState;
+use crate::process::tree::TreeEntry;
+use crate::ui::hints::{HintPatterns, HintState};
 use crate::ui::search::SearchState;
 use ratatui::{
     style::{Color, Modifier, Style},
     text::Span,
 };
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone)]
@@ -19,6 +24,19 @@ pub enum RestartPhase {
     Active(Instant),
 }
 
+/// Clipboard paste awaiting confirmation (multi-line content)
+#[derive(Debug, Clone)]
+pub struct PendingPaste {
+    pub name: String,
+    pub text: String,
+}
+
+impl PendingPaste {
+    pub fn line_count(&self) -> usize {
+        self.text.lines().count().max(1)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum InputMode {
     Browse,
@@ -62,11 +80,26 @@ pub struct App {
     pub mode: InputMode,
     pub show_help: bool,
     pub help_scroll: u16,
+    /// CPU/RSS columns in the process list
+    pub show_metrics: bool,
+    /// Detail popup for the selected process
+    pub show_info: bool,
+    /// Process tree popup
+    pub show_tree: bool,
+    pub tree_scroll: u16,
+    /// Signal menu for the selected process; the highlighted `Signal::MENU` entry
+    pub signal_menu: Option<usize>,
+    /// Links popup for the selected process; the highlighted entry
+    pub link_menu: Option<usize>,
+    /// Quit held back because these descendants escaped their process group
+    pub pending_quit: Option<Vec<(String, TreeEntry)>>,
     pub should_quit: bool,
     pub shutting_down: bool,
     pub shutdown_start: Option<Instant>,
     pub no_shift_tab: bool,
     pub selection: SelectionState,
+    /// Count, pending key and marks for moving a keyboard selection
+    pub copy_mode: CopyMode,
     pub auto_copy: bool,
     pub status_message: Option<(String, Instant)>,
     pub drag_edge: Option<DragEdge>,
@@ -80,8 +113,27 @@ pub struct App {
     pub pending_click: Option<(u16, u16, BufferPos, bool)>,
     /// Search state for scrollback search
     pub search: SearchState,
+    /// Hint mode: labelled matches on screen, while shown
+    pub hints: Option<HintState>,
+    pub hint_patterns: HintPatterns,
+    /// Command `|` pipes to, from `pipe_command` or `--pipe-command`
+    pub pipe_command: Option<String>,
+    /// Pipe command still running
+    pub pipe_job: Option<PipeJob>,
+    /// Output of the last pipe command, while shown
+    pub pipe_output: Option<PipeOutput>,
+    /// Text typed at the `:` command prompt, while it is open
+    pub command_prompt: Option<String>,
+    /// Config file that `:write` saves to by default
+    pub config_path: Option<PathBuf>,
     /// Restart popup state — Pending → Active(Instant) → None
     pub restarting: Option<(RestartAction, RestartPhase)>,
+    /// Multi-line clipboard paste waiting for y/n confirmation
+    pub pending_paste: Option<PendingPaste>,
+    /// Editor command template for file references (`$VISUAL`/`$EDITOR` if unset)
+    pub editor: Option<String>,
+    /// Editor command the main loop runs next, with the terminal handed over
+    pub pending_editor: Option<String>,
 }
 
 impl App {
@@ -91,11 +143,19 @@ impl App {
             mode: InputMode::Browse,
             show_help: false,
             help_scroll: 0,
+            show_metrics: false,
+            show_info: false,
+            show_tree: false,
+            tree_scroll: 0,
+            signal_menu: None,
+            link_menu: None,
+            pending_quit: None,
             should_quit: false,
             shutting_down: false,
             shutdown_start: None,
             no_shift_tab,
             selection: SelectionState::new(),
+            copy_mode: CopyMode::default(),
             auto_copy,
             status_message: None,
             drag_edge: None,
@@ -104,7 +164,17 @@ impl App {
             last_drag_row: None,
             pending_click: None,
             search: SearchState::default(),
+            hints: None,
+            hint_patterns: HintPatterns::default(),
+            pipe_command: None,
+            pipe_job: None,
+            pipe_output: None,
+            command_prompt: None,
+            config_path: None,
             restarting: None,
+            pending_paste: None,
+            editor: None,
+            pending_editor: None,
         }
     }
 
@@ -147,6 +217,16 @@ impl App {
         self.status_message = Some((msg.to_string(), Instant::now()));
     }
 
+    /// Drop the status message once it has expired. Returns true if it was
+    /// dropped, i.e. the status bar needs redrawing.
+    pub fn expire_status(&mut self) -> bool {
+        if self.status_message.is_some() && self.active_status().is_none() {
+            self.status_message = None;
+            return true;
+        }
+        false
+    }
+
     /// Returns status message if still within display duration (2s)
     pub fn active_status(&self) -> Option<&str> {
         self.status_message.as_ref().and_then(|(msg, time)| {
@@ -0,0 +1,158 @@
+use crate::process::export::ExportFormat;
+use std::path::PathBuf;
+
+/// Prompt commands, for parsing and tab completion.
+pub const COMMANDS: [&str; 8] = [
+    "new", "rm", "edit", "rename", "dup", "pipe", "export", "write",
+];
+
+/// A command entered at the `:` prompt. Commands without a process name act
+/// on the selected process.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// `new <name> <command>`: add a process and start it
+    New { name: String, command: String },
+    /// `rm [name]`: stop a process and remove it
+    Remove(Option<String>),
+    /// `edit [command]`: replace the command and restart; without one, the
+    /// prompt is filled with the current command
+    Edit(Option<String>),
+    /// `rename <new name>`
+    Rename(String),
+    /// `dup [name]`: start a copy under a free name
+    Dup(Option<String>),
+    /// `pipe [command]`: pipe the selection or scrollback to a shell
+    /// command (default: `pipe_command`) and show its output
+    Pipe(Option<String>),
+    /// `export [text|ansi|html] [path]`: write the selection or scrollback
+    /// to a file; the format defaults to the path's extension, then text
+    Export {
+        format: Option<ExportFormat>,
+        path: Option<PathBuf>,
+    },
+    /// `write [path]`: save all processes to the config file
+    Write(Option<PathBuf>),
+}
+
+/// Parse a prompt line (without the leading `:`).
+pub fn parse(input: &str) -> Result<Command, String> {
+    let input = input.trim();
+    let (word, rest) = input.split_once(' ').unwrap_or((input, ""));
+    let rest = rest.trim();
+    let arg = (!rest.is_empty()).then(|| rest.to_string());
+    match word {
+        "new" => {
+            let (name, command) = rest.split_once(' ').ok_or("usage: new <name> <command>")?;
+            Ok(Command::New {
+                name: name.to_string(),
+                command: command.trim().to_string(),
+            })
+        }
+        "rm" => Ok(Command::Remove(arg)),
+        "edit" => Ok(Command::Edit(arg)),
+        "rename" => arg
+            .map(Command::Rename)
+            .ok_or_else(|| "usage: rename <new name>".into()),
+        "dup" => Ok(Command::Dup(arg)),
+        "pipe" => Ok(Command::Pipe(arg)),
+        "export" => {
+            let (first, path) = rest.split_once(' ').unwrap_or((rest, ""));
+            let (format, path) = match ExportFormat::parse(first) {
+                Ok(format) => (Some(format), path.trim()),
+                Err(_) => (None, rest),
+            };
+            Ok(Command::Export {
+                format,
+                path: (!path.is_empty()).then(|| PathBuf::from(path)),
+            })
+        }
+        "w" | "write" => Ok(Command::Write(arg.map(PathBuf::from))),
+        "" => Err("empty command".into()),
+        other => Err(format!("unknown command: {other}")),
+    }
+}
+
+/// Complete the last word of `input`: a command name first, then a process
+/// name for commands that take one. Extends to the longest common prefix,
+/// adding a space once the match is unique. None if nothing matches.
+pub fn complete(input: &str, names: &[String]) -> Option<String> {
+    let (head, word) = match input.rsplit_once(' ') {
+        Some((head, word)) => (Some(head), word),
+        None => (None, input),
+    };
+    let candidates: Vec<&str> = match head.map(str::trim) {
+        None => COMMANDS.to_vec(),
+        Some("rm" | "dup") => names.iter().map(String::as_str).collect(),
+        Some(_) => return None,
+    };
+    let matches: Vec<&str> = candidates
+        .into_iter()
+        .filter(|c| c.starts_with(word))
+        .collect();
+    let first = *matches.first()?;
+    let common = matches.iter().fold(first.len(), |len, m| {
+        first
+            .chars()
+            .zip(m.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum::<usize>()
+            .min(len)
+    });
+    let suffix = if matches.len() == 1 { " " } else { "" };
+    let prefix = head.map(|h| format!("{h} ")).unwrap_or_default();
+    Some(format!("{prefix}{}{suffix}", &first[..common]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands_and_arguments() {
+        assert_eq!(
+            parse("new api  cargo run --release"),
+            Ok(Command::New {
+                name: "api".into(),
+                command: "cargo run --release".into()
+            })
+        );
+        assert_eq!(parse("rm"), Ok(Command::Remove(None)));
+        assert_eq!(parse(" dup web "), Ok(Command::Dup(Some("web".into()))));
+        assert_eq!(parse("w"), Ok(Command::Write(None)));
+        assert_eq!(
+            parse("pipe jq  .status"),
+            Ok(Command::Pipe(Some("jq  .status".into())))
+        );
+        assert_eq!(
+            parse("export html out/web.html"),
+            Ok(Command::Export {
+                format: Some(ExportFormat::Html),
+                path: Some("out/web.html".into())
+            })
+        );
+        assert_eq!(
+            parse("export web.ansi"),
+            Ok(Command::Export {
+                format: None,
+                path: Some("web.ansi".into())
+            })
+        );
+        assert!(parse("new api").is_err());
+        assert!(parse("rename").is_err());
+        assert!(parse("frobnicate").is_err());
+    }
+
+    #[test]
+    fn completes_commands_then_process_names() {
+        let names = vec!["web".to_string(), "worker".to_string(), "api".to_string()];
+        assert_eq!(complete("re", &names).as_deref(), Some("rename "));
+        assert_eq!(complete("d", &names).as_deref(), Some("dup "));
+        assert_eq!(complete("rm w", &names).as_deref(), Some("rm w"));
+        assert_eq!(complete("rm wo", &names).as_deref(), Some("rm worker "));
+        assert_eq!(complete("dup ", &names).as_deref(), Some("dup "));
+        assert_eq!(complete("rm x", &names), None);
+        // `new` takes a fresh name, so there is nothing to complete
+        assert_eq!(complete("new w", &names), None);
+    }
+}
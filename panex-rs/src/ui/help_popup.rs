@@ -1,4 +1,9 @@
-use crate::ui::app::RestartAction;
+use crate::config::Signal;
+use crate::input::pipe::PipeOutput;
+use crate::process::metrics::{self, format_bytes};
+use crate::process::tree::{TreeEntry, UNKNOWN_OWNER};
+use crate::process::{ManagedProcess, ProcessManager};
+use crate::ui::app::{PendingPaste, RestartAction};
 use crate::ui::InputMode;
 use ratatui::{
     buffer::Buffer,
@@ -52,10 +57,34 @@ impl Widget for HelpPopup {
                 Span::styled("R      ", Style::default().fg(Color::Yellow)),
                 Span::raw("Restart all processes"),
             ]),
+            Line::from(vec![
+                Span::styled("s      ", Style::default().fg(Color::Yellow)),
+                Span::raw("Start stopped process"),
+            ]),
             Line::from(vec![
                 Span::styled("x      ", Style::default().fg(Color::Yellow)),
                 Span::raw("Kill selected process"),
             ]),
+            Line::from(vec![
+                Span::styled("K      ", Style::default().fg(Color::Yellow)),
+                Span::raw("Send a signal to process"),
+            ]),
+            Line::from(vec![
+                Span::styled("u      ", Style::default().fg(Color::Yellow)),
+                Span::raw("Open/copy URLs and ports"),
+            ]),
+            Line::from(vec![
+                Span::styled("f      ", Style::default().fg(Color::Yellow)),
+                Span::raw("Hints: copy (Shift: paste) a match"),
+            ]),
+            Line::from(vec![
+                Span::styled("z      ", Style::default().fg(Color::Yellow)),
+                Span::raw("Pause/resume process"),
+            ]),
+            Line::from(vec![
+                Span::styled("p      ", Style::default().fg(Color::Yellow)),
+                Span::raw("Paste clipboard into process"),
+            ]),
             Line::from(vec![
                 Span::styled("g      ", Style::default().fg(Color::Yellow)),
                 Span::raw("Toggle auto-scroll"),
@@ -88,6 +117,45 @@ impl Widget for HelpPopup {
                 Span::styled("N      ", Style::default().fg(Color::Yellow)),
                 Span::raw("Previous search match"),
             ]),
+            Line::from(vec![
+                Span::styled("v/V    ", Style::default().fg(Color::Yellow)),
+                Span::raw("Copy mode, selecting chars/lines; y copies"),
+            ]),
+            Line::from(vec![
+                Span::raw("       "),
+                Span::styled(
+                    "hjkl w b e 0 ^ $ gg G { } f F t T ; ,",
+                    Style::default().fg(Color::Yellow),
+                ),
+            ]),
+            Line::from(vec![
+                Span::raw("       "),
+                Span::styled(
+                    "H M L ^u ^d m{a-z} '{a-z} o",
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::raw(", counts (5j)"),
+            ]),
+            Line::from(vec![
+                Span::styled("|      ", Style::default().fg(Color::Yellow)),
+                Span::raw("Pipe selection or scrollback to a command"),
+            ]),
+            Line::from(vec![
+                Span::styled("m      ", Style::default().fg(Color::Yellow)),
+                Span::raw("Toggle CPU/memory columns"),
+            ]),
+            Line::from(vec![
+                Span::styled("i      ", Style::default().fg(Color::Yellow)),
+                Span::raw("Process details"),
+            ]),
+            Line::from(vec![
+                Span::styled("T      ", Style::default().fg(Color::Yellow)),
+                Span::raw("Process tree"),
+            ]),
+            Line::from(vec![
+                Span::styled(":      ", Style::default().fg(Color::Yellow)),
+                Span::raw("Command prompt (new/rm/edit/rename/dup/pipe/export/write)"),
+            ]),
             Line::from(vec![
                 Span::styled("?      ", Style::default().fg(Color::Yellow)),
                 Span::raw("Toggle help"),
@@ -137,6 +205,10 @@ impl Widget for HelpPopup {
                 Span::styled("Shift-Tab ", Style::default().fg(Color::Yellow)),
                 Span::raw("Exit focus mode"),
             ]),
+            Line::from(vec![
+                Span::styled("Paste     ", Style::default().fg(Color::Yellow)),
+                Span::raw("Forwarded to child process"),
+            ]),
             Line::from(""),
             Line::from(vec![
                 Span::styled("Mouse ", Style::default().add_modifier(Modifier::BOLD)),
@@ -280,6 +352,444 @@ impl Widget for RestartPopup {
     }
 }
 
+pub struct PastePopup {
+    message: String,
+}
+
+impl PastePopup {
+    pub fn new(paste: &PendingPaste) -> Self {
+        let message = format!("Paste {} lines into {}?", paste.line_count(), paste.name);
+        Self { message }
+    }
+}
+
+impl Widget for PastePopup {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let hint = "y/Enter:paste  any key:cancel";
+        let popup_width = (self.message.chars().count().max(hint.len()) + 6) as u16;
+        let popup_height = 6;
+        let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+        let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+        let popup_area = Rect::new(x, y, popup_width.min(area.width), popup_height);
+
+        Clear.render(popup_area, buf);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow));
+
+        let text = vec![
+            Line::from(""),
+            Line::from(Span::styled(
+                format!(" {} ", self.message),
+                Style::default().fg(Color::Yellow),
+            )),
+            Line::from(Span::styled(
+                format!(" {} ", hint),
+                Style::default().fg(Color::DarkGray),
+            )),
+        ];
+        Paragraph::new(text).block(block).render(popup_area, buf);
+    }
+}
+
+/// Details of one process: group CPU/memory, per-child breakdown and
+/// listening ports.
+pub struct ProcessInfoPopup<'a> {
+    process: &'a ManagedProcess,
+}
+
+impl<'a> ProcessInfoPopup<'a> {
+    pub fn new(process: &'a ManagedProcess) -> Self {
+        Self { process }
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let label = |s: &str| Span::styled(format!("{s:<9}"), Style::default().fg(Color::Yellow));
+        let dim = Style::default().fg(Color::DarkGray);
+        let process = self.process;
+        let pid = process.pty.as_ref().and_then(|p| p.pid());
+        let mut lines = vec![Line::from(vec![
+            label("Command"),
+            Span::raw(process.config.command.clone()),
+        ])];
+        lines.push(Line::from(vec![
+            label("PID"),
+            Span::raw(pid.map_or_else(|| "-".to_string(), |p| p.to_string())),
+        ]));
+        if !metrics::SUPPORTED {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "Metrics need /proc (Linux only)",
+                dim,
+            )));
+            return lines;
+        }
+        let Some(m) = process.metrics.as_ref() else {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("Sampling...", dim)));
+            return lines;
+        };
+        lines.push(Line::from(vec![
+            label("CPU"),
+            Span::raw(format!("{:.1}%", m.cpu_percent)),
+        ]));
+        lines.push(Line::from(vec![
+            label("Memory"),
+            Span::raw(format!(
+                "{} (peak {})",
+                format_bytes(m.rss),
+                format_bytes(m.peak_rss)
+            )),
+        ]));
+
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("{:>7} {:>6} {:>5}  COMMAND", "PID", "CPU", "MEM"),
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+        if m.children.is_empty() {
+            lines.push(Line::from(Span::styled("  not running", dim)));
+        }
+        for child in &m.children {
+            lines.push(Line::from(format!(
+                "{:>7} {:>5.1}% {:>5}  {}",
+                child.pid,
+                child.cpu_percent,
+                format_bytes(child.rss),
+                child.command
+            )));
+        }
+
+        lines.push(Line::from(""));
+        let ports = if m.ports.is_empty() {
+            Span::styled("none", dim)
+        } else {
+            let list: Vec<String> = m.ports.iter().map(u16::to_string).collect();
+            Span::raw(list.join(", "))
+        };
+        lines.push(Line::from(vec![label("Ports"), ports]));
+        lines
+    }
+}
+
+impl Widget for ProcessInfoPopup<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let popup_area = centered_rect(70, 60, area);
+
+        Clear.render(popup_area, buf);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(format!(" {} ", self.process.config.name))
+            .title_bottom(Line::from(" any key to close ").right_aligned());
+
+        Paragraph::new(self.lines())
+            .block(block)
+            .render(popup_area, buf);
+    }
+}
+
+/// One tree line: indented pid and command, with escape/adoption notes.
+fn tree_line(entry: &TreeEntry) -> Line<'static> {
+    let indent = "  ".repeat(entry.depth + 1);
+    let mut spans = vec![
+        Span::raw(indent),
+        Span::styled(
+            format!("{} ", entry.pid),
+            Style::default().fg(Color::Yellow),
+        ),
+        Span::raw(entry.command.clone()),
+    ];
+    if entry.escaped {
+        spans.push(Span::styled(
+            format!("  [own group {}]", entry.pgrp),
+            Style::default().fg(Color::Red),
+        ));
+    }
+    if entry.adopted {
+        spans.push(Span::styled(
+            "  [orphaned]",
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    Line::from(spans)
+}
+
+/// Descendant tree of every managed process. Processes outside their
+/// managed process's group are flagged, since stopping it won't reach them.
+pub struct ProcessTreePopup<'a> {
+    manager: &'a ProcessManager,
+    scroll: u16,
+}
+
+impl<'a> ProcessTreePopup<'a> {
+    pub fn new(manager: &'a ProcessManager, scroll: u16) -> Self {
+        Self { manager, scroll }
+    }
+}
+
+impl Widget for ProcessTreePopup<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let popup_area = centered_rect(80, 70, area);
+
+        Clear.render(popup_area, buf);
+
+        let tree = self.manager.process_tree();
+        let dim = Style::default().fg(Color::DarkGray);
+        let mut lines = Vec::new();
+        let owners = self
+            .manager
+            .process_names()
+            .iter()
+            .map(String::as_str)
+            .chain([UNKNOWN_OWNER]);
+        for name in owners {
+            let entries = tree.entries(name);
+            if name == UNKNOWN_OWNER && entries.is_empty() {
+                continue;
+            }
+            let title = if name == UNKNOWN_OWNER {
+                "(unknown origin)"
+            } else {
+                name
+            };
+            lines.push(Line::from(Span::styled(
+                title.to_string(),
+                Style::default().add_modifier(Modifier::BOLD),
+            )));
+            if entries.is_empty() {
+                lines.push(Line::from(Span::styled("  not running", dim)));
+            }
+            lines.extend(entries.iter().map(tree_line));
+        }
+
+        let total_lines = lines.len() as u16;
+        let inner_height = popup_area.height.saturating_sub(2);
+        let max_scroll = total_lines.saturating_sub(inner_height);
+        let scroll = self.scroll.min(max_scroll);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(" Process tree ")
+            .title_bottom(Line::from(" any key to close ").right_aligned());
+
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((scroll, 0))
+            .render(popup_area, buf);
+    }
+}
+
+/// Signal menu for the selected process.
+pub struct SignalPopup<'a> {
+    name: &'a str,
+    selected: usize,
+}
+
+impl<'a> SignalPopup<'a> {
+    pub fn new(name: &'a str, selected: usize) -> Self {
+        Self { name, selected }
+    }
+}
+
+/// What programs conventionally do on each menu signal.
+fn signal_hint(signal: Signal) -> &'static str {
+    match signal {
+        Signal::Int => "interrupt, like Ctrl-C",
+        Signal::Hup => "reload config (by convention)",
+        Signal::Usr1 | Signal::Usr2 => "application defined",
+        Signal::Quit => "quit with core dump",
+        Signal::Term => "terminate",
+        Signal::Kill => "kill, can't be handled",
+        Signal::Stop | Signal::Cont => "",
+    }
+}
+
+impl Widget for SignalPopup<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let popup_area = centered_rect(40, 50, area);
+
+        Clear.render(popup_area, buf);
+
+        let mut text = vec![Line::from("")];
+        for (i, signal) in Signal::MENU.iter().enumerate() {
+            let style = if i == self.selected {
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            text.push(Line::from(vec![
+                Span::styled(format!(" {} ", i + 1), Style::default().fg(Color::Yellow)),
+                Span::styled(format!("{:<8}", signal.name()), style),
+                Span::styled(
+                    format!(" {}", signal_hint(*signal)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]));
+        }
+        text.push(Line::from(""));
+        text.push(Line::from(Span::styled(
+            " Enter/1-7:send  any key:cancel",
+            Style::default().fg(Color::DarkGray),
+        )));
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(format!(" Signal {} ", self.name));
+        Paragraph::new(text).block(block).render(popup_area, buf);
+    }
+}
+
+/// URLs from a process's output and the ports it listens on.
+pub struct LinksPopup<'a> {
+    name: &'a str,
+    links: &'a [String],
+    selected: usize,
+}
+
+impl<'a> LinksPopup<'a> {
+    pub fn new(name: &'a str, links: &'a [String], selected: usize) -> Self {
+        Self {
+            name,
+            links,
+            selected,
+        }
+    }
+}
+
+impl Widget for LinksPopup<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let popup_area = centered_rect(60, 50, area);
+
+        Clear.render(popup_area, buf);
+
+        let selected = self.selected.min(self.links.len().saturating_sub(1));
+        let mut text = vec![Line::from("")];
+        for (i, link) in self.links.iter().enumerate() {
+            let style = if i == selected {
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            text.push(Line::from(Span::styled(format!(" {link} "), style)));
+        }
+        text.push(Line::from(""));
+        text.push(Line::from(Span::styled(
+            " Enter/o:open  y:copy  any key:close",
+            Style::default().fg(Color::DarkGray),
+        )));
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(format!(" Links {} ", self.name));
+        Paragraph::new(text).block(block).render(popup_area, buf);
+    }
+}
+
+/// Read-only output of a command the selection or scrollback was piped to.
+pub struct PipePopup<'a> {
+    output: &'a PipeOutput,
+}
+
+impl<'a> PipePopup<'a> {
+    pub fn new(output: &'a PipeOutput) -> Self {
+        Self { output }
+    }
+
+    /// How far `output` can scroll in the popup on a screen `height` rows
+    /// tall.
+    pub fn max_scroll(output: &PipeOutput, height: u16) -> u16 {
+        let popup_area = centered_rect(80, 70, Rect::new(0, 0, 1, height));
+        let total_lines = output.text.lines().count() as u16;
+        total_lines.saturating_sub(popup_area.height.saturating_sub(2))
+    }
+}
+
+impl Widget for PipePopup<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let popup_area = centered_rect(80, 70, area);
+
+        Clear.render(popup_area, buf);
+
+        let lines: Vec<Line> = self.output.text.lines().map(Line::from).collect();
+        let scroll = self
+            .output
+            .scroll
+            .min(Self::max_scroll(self.output, area.height));
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(format!(" | {} ", self.output.command))
+            .title_bottom(Line::from(" y:copy  any key:close ").right_aligned());
+
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((scroll, 0))
+            .render(popup_area, buf);
+    }
+}
+
+/// Quit confirmation listing descendants that escaped their process group.
+pub struct EscapedQuitPopup<'a> {
+    escaped: &'a [(String, TreeEntry)],
+}
+
+impl<'a> EscapedQuitPopup<'a> {
+    pub fn new(escaped: &'a [(String, TreeEntry)]) -> Self {
+        Self { escaped }
+    }
+}
+
+impl Widget for EscapedQuitPopup<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let popup_area = centered_rect(70, 50, area);
+
+        Clear.render(popup_area, buf);
+
+        let count = self.escaped.len();
+        let noun = if count == 1 { "process" } else { "processes" };
+        let mut text = vec![
+            Line::from(""),
+            Line::from(Span::styled(
+                format!(" {count} {noun} left their process group and will keep running:"),
+                Style::default().fg(Color::Yellow),
+            )),
+            Line::from(""),
+        ];
+        for (owner, entry) in self.escaped {
+            text.push(Line::from(vec![
+                Span::raw(format!("  {owner}: ")),
+                Span::styled(
+                    format!("{} ", entry.pid),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::raw(entry.command.clone()),
+            ]));
+        }
+        text.push(Line::from(""));
+        text.push(Line::from(Span::styled(
+            " k:kill them too  q/Enter:leave running  any key:cancel",
+            Style::default().fg(Color::DarkGray),
+        )));
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow))
+            .title(" Quit ");
+        Paragraph::new(text).block(block).render(popup_area, buf);
+    }
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::vertical([
         Constraint::Percentage((100 - percent_y) / 2),
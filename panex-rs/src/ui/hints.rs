@@ -0,0 +1,352 @@
+use crate::process::links::trim_url;
+use crate::process::ManagedProcess;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Label letters, home row first. Lowercase only: Shift+label pastes.
+const LABEL_CHARS: &str = "asdfghjklqwertyuiopzxcvbnm";
+
+/// Length of a raw match to keep; 0 drops it.
+type Accept = fn(&str) -> usize;
+
+/// Built-in patterns, most specific first.
+const BUILTIN_PATTERNS: [(&str, Accept); 6] = [
+    // URLs
+    (r#"https?://[^\s<>"'`|]+"#, |m| match trim_url(m) {
+        url if url.ends_with("://") => 0,
+        url => url.len(),
+    }),
+    // UUIDs
+    (
+        r"\b[0-9a-fA-F]{8}(?:-[0-9a-fA-F]{4}){3}-[0-9a-fA-F]{12}\b",
+        str::len,
+    ),
+    // Paths, with an optional :line:col
+    (
+        r"(?:~|\.{1,2})?/?(?:[\w.@+-]+/)+[\w.@+-]+(?::\d+){0,2}",
+        |m| m.trim_end_matches(['.', ',']).len(),
+    ),
+    // IPv4, with an optional port
+    (r"\b(?:\d{1,3}\.){3}\d{1,3}(?::\d{1,5})?\b", str::len),
+    // IPv6; a digit tells them apart from `abc::def`
+    (
+        r"(?i)\b(?:[0-9a-f]{1,4}:){7}[0-9a-f]{1,4}\b|\b(?:[0-9a-f]{1,4}:){1,6}:[0-9a-f]{1,4}\b",
+        |m| {
+            if m.contains(|c: char| c.is_ascii_digit()) {
+                m.len()
+            } else {
+                0
+            }
+        },
+    ),
+    // Commit SHAs and other hex digests; words and plain numbers aren't
+    (r"\b[0-9a-f]{7,64}\b", |m| {
+        let digit = m.contains(|c: char| c.is_ascii_digit());
+        let letter = m.contains(|c: char| c.is_ascii_alphabetic());
+        if digit && letter {
+            m.len()
+        } else {
+            0
+        }
+    }),
+];
+
+/// What hint mode looks for: the config's `hint_patterns`, then the
+/// built-in ones. A match overlapping an earlier one is dropped.
+pub struct HintPatterns {
+    user: Vec<String>,
+    patterns: Vec<(Regex, Accept)>,
+}
+
+impl Default for HintPatterns {
+    fn default() -> Self {
+        Self::new(&[]).expect("built-in hint patterns are valid")
+    }
+}
+
+impl HintPatterns {
+    pub fn new(user: &[String]) -> Result<Self, regex::Error> {
+        let user_patterns = user
+            .iter()
+            .map(|pattern| Ok((Regex::new(pattern)?, str::len as Accept)));
+        let builtin = BUILTIN_PATTERNS
+            .iter()
+            .map(|(pattern, accept)| Ok((Regex::new(pattern)?, *accept)));
+        Ok(Self {
+            user: user.to_vec(),
+            patterns: user_patterns.chain(builtin).collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// The config's patterns, as written.
+    pub fn user(&self) -> &[String] {
+        &self.user
+    }
+
+    /// Byte ranges of the matches in `text`, in order. For a pattern with a
+    /// capture group, the first group is the match.
+    pub fn find(&self, text: &str) -> Vec<(usize, usize)> {
+        let mut found: Vec<(usize, usize)> = Vec::new();
+        for (regex, accept) in &self.patterns {
+            for captures in regex.captures_iter(text) {
+                let Some(m) = captures.get(1).or_else(|| captures.get(0)) else {
+                    continue;
+                };
+                let len = accept(m.as_str());
+                let (start, end) = (m.start(), m.start() + len);
+                let overlaps = found.iter().any(|&(s, e)| start < e && s < end);
+                if len > 0 && !overlaps {
+                    found.push((start, end));
+                }
+            }
+        }
+        found.sort_unstable();
+        found
+    }
+}
+
+/// A match on screen and the label that picks it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+    pub label: String,
+    pub text: String,
+    pub row: usize,
+    /// Start column (inclusive)
+    pub col_start: usize,
+    /// End column (exclusive)
+    pub col_end: usize,
+}
+
+/// How a cell is drawn while hints are shown.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HintCell {
+    /// Part of the label still to type
+    Label(char),
+    /// Rest of a match whose label is still possible
+    Match,
+}
+
+/// Outcome of typing a label character.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HintInput {
+    /// Some labels still start with the input
+    Pending,
+    /// The input is a whole label; the text of its match
+    Picked(String),
+    /// No label starts with the input
+    NoMatch,
+}
+
+/// Hint mode: labelled matches and the label typed so far.
+#[derive(Debug, Clone, Default)]
+pub struct HintState {
+    pub hints: Vec<Hint>,
+    pub typed: String,
+}
+
+impl HintState {
+    pub fn new(hints: Vec<Hint>) -> Self {
+        Self {
+            hints,
+            typed: String::new(),
+        }
+    }
+
+    /// Hints whose label starts with what was typed.
+    fn candidates(&self) -> impl Iterator<Item = &Hint> {
+        self.hints
+            .iter()
+            .filter(|hint| hint.label.starts_with(&self.typed))
+    }
+
+    pub fn cell(&self, row: usize, col: usize) -> Option<HintCell> {
+        let hint = self
+            .candidates()
+            .find(|h| h.row == row && (h.col_start..h.col_end).contains(&col))?;
+        let remaining = &hint.label[self.typed.len()..];
+        match remaining.chars().nth(col - hint.col_start) {
+            Some(c) => Some(HintCell::Label(c)),
+            None => Some(HintCell::Match),
+        }
+    }
+
+    /// Add a typed label character, in either case.
+    pub fn type_char(&mut self, c: char) -> HintInput {
+        self.typed.push(c.to_ascii_lowercase());
+        if let Some(hint) = self.hints.iter().find(|h| h.label == self.typed) {
+            return HintInput::Picked(hint.text.clone());
+        }
+        match self.candidates().next() {
+            Some(_) => HintInput::Pending,
+            None => HintInput::NoMatch,
+        }
+    }
+}
+
+/// `n` labels, none a prefix of another: single letters while they last,
+/// then pairs.
+fn labels(n: usize) -> Vec<String> {
+    let chars: Vec<char> = LABEL_CHARS.chars().collect();
+    if n <= chars.len() {
+        return chars[..n].iter().map(char::to_string).collect();
+    }
+    chars
+        .iter()
+        .flat_map(|a| chars.iter().map(move |b| format!("{a}{b}")))
+        .take(n)
+        .collect()
+}
+
+/// Label the matches of `patterns` that start on the rows the output panel
+/// shows (`width` x `height`). Labels are handed out from the bottom up,
+/// and repeated text shares a label.
+pub fn find_hints(
+    process: &mut ManagedProcess,
+    width: usize,
+    height: usize,
+    patterns: &HintPatterns,
+) -> Vec<Hint> {
+    process.sync_wrap(width);
+    let lines = process.buffer.get_all_lines();
+    let content_count = process.buffer.content_line_count();
+    // Visible column range of each visible buffer row
+    let mut visible: Vec<(usize, usize, usize)> = Vec::new();
+    if process.wrap_enabled && width > 0 {
+        let wrap = &process.wrap_index;
+        let total = wrap.total_rows(content_count);
+        let start = process.scroll_offset.min(total.saturating_sub(1));
+        for display_row in start..(start + height).min(total) {
+            let (row, col) = wrap.locate(display_row);
+            match visible.last_mut() {
+                Some((last, _, end)) if *last == row => *end = col + width,
+                _ => visible.push((row, col, col + width)),
+            }
+        }
+    } else {
+        let start = process.scroll_offset.min(content_count.saturating_sub(1));
+        let end = (start + height).min(content_count);
+        visible.extend((start..end).map(|row| (row, 0, width)));
+    }
+
+    let mut matches = Vec::new();
+    for &(row, from, to) in visible.iter().rev() {
+        let Some(line) = lines.get(row) else {
+            continue;
+        };
+        let text = line.text();
+        for (start, end) in patterns.find(&text) {
+            // Columns are chars: the line has one per cell
+            let col_start = text[..start].chars().count();
+            let col_end = col_start + text[start..end].chars().count();
+            if (from..to).contains(&col_start) {
+                matches.push((row, col_start, col_end, text[start..end].to_string()));
+            }
+        }
+    }
+
+    let mut distinct: Vec<&str> = Vec::new();
+    for (_, _, _, text) in &matches {
+        if !distinct.contains(&text.as_str()) {
+            distinct.push(text);
+        }
+    }
+    let labels: HashMap<&str, String> = distinct
+        .iter()
+        .copied()
+        .zip(labels(distinct.len()))
+        .collect();
+    matches
+        .iter()
+        .filter_map(|(row, col_start, col_end, text)| {
+            Some(Hint {
+                label: labels.get(text.as_str())?.clone(),
+                text: text.clone(),
+                row: *row,
+                col_start: *col_start,
+                col_end: *col_end,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(patterns: &HintPatterns, text: &str) -> Vec<String> {
+        patterns
+            .find(text)
+            .into_iter()
+            .map(|(start, end)| text[start..end].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn builtin_patterns_find_urls_paths_hashes_and_ips() {
+        let patterns = HintPatterns::default();
+        assert_eq!(
+            found(
+                &patterns,
+                "GET http://localhost:3000/api/users 200 (cached)."
+            ),
+            ["http://localhost:3000/api/users"]
+        );
+        assert_eq!(
+            found(&patterns, "error at src/main.rs:42:7, see ./docs/x.md."),
+            ["src/main.rs:42:7", "./docs/x.md"]
+        );
+        assert_eq!(
+            found(&patterns, "commit 3f9a2c1d on 10.0.0.12:8080 from fe80::1"),
+            ["3f9a2c1d", "10.0.0.12:8080", "fe80::1"]
+        );
+        assert_eq!(
+            found(&patterns, "id 550e8400-e29b-41d4-a716-446655440000 ok"),
+            ["550e8400-e29b-41d4-a716-446655440000"]
+        );
+        // Words, numbers, times and Rust paths are left alone
+        assert!(found(&patterns, "deadbeef 1234567 12:30:45 std::fmt abc::def").is_empty());
+    }
+
+    #[test]
+    fn user_patterns_come_first_and_may_capture() {
+        let patterns = HintPatterns::new(&[
+            "(?:ticket )?(PROJ-\\d+)".to_string(),
+            "v\\d+\\.\\d+".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(
+            found(&patterns, "fix ticket PROJ-42 in v1.2 at src/a.rs"),
+            ["PROJ-42", "v1.2", "src/a.rs"]
+        );
+        assert!(HintPatterns::new(&["(".to_string()]).is_err());
+    }
+
+    #[test]
+    fn labels_are_prefix_free() {
+        assert_eq!(labels(3), ["a", "s", "d"]);
+        let many = labels(30);
+        assert_eq!(&many[..3], ["aa", "as", "ad"]);
+        assert!(many
+            .iter()
+            .all(|a| many.iter().filter(|b| b.starts_with(a.as_str())).count() == 1));
+    }
+
+    #[test]
+    fn typing_narrows_then_picks() {
+        let hint = |label: &str, text: &str| Hint {
+            label: label.to_string(),
+            text: text.to_string(),
+            row: 0,
+            col_start: 0,
+            col_end: text.len(),
+        };
+        let mut state = HintState::new(vec![hint("aa", "one"), hint("as", "two")]);
+        assert_eq!(state.type_char('a'), HintInput::Pending);
+        assert_eq!(state.cell(0, 0), Some(HintCell::Label('a')));
+        assert_eq!(state.cell(0, 1), Some(HintCell::Match));
+        assert_eq!(state.type_char('S'), HintInput::Picked("two".to_string()));
+
+        let mut state = HintState::new(vec![hint("a", "one")]);
+        assert_eq!(state.type_char('x'), HintInput::NoMatch);
+    }
+}
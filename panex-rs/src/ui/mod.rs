@@ -1,5 +1,7 @@
 pub mod app;
+pub mod command;
 pub mod help_popup;
+pub mod hints;
 pub mod output_panel;
 pub mod process_list;
 pub mod search;
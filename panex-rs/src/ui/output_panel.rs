@@ -1,5 +1,7 @@
 use crate::input::SelectionState;
+use crate::process::buffer::Cell;
 use crate::process::ManagedProcess;
+use crate::ui::hints::{HintCell, HintState};
 use crate::ui::search::SearchState;
 use crate::ui::InputMode;
 use ratatui::{
@@ -16,6 +18,7 @@ pub struct OutputPanel<'a> {
     mode: InputMode,
     selection: &'a SelectionState,
     search: &'a SearchState,
+    hints: Option<&'a HintState>,
 }
 
 impl<'a> OutputPanel<'a> {
@@ -24,16 +27,55 @@ impl<'a> OutputPanel<'a> {
         mode: InputMode,
         selection: &'a SelectionState,
         search: &'a SearchState,
+        hints: Option<&'a HintState>,
     ) -> Self {
         Self {
             process,
             mode,
             selection,
             search,
+            hints,
         }
     }
 }
 
+impl OutputPanel<'_> {
+    /// Span for one cell, with hint labels, selection and search
+    /// highlighting applied.
+    fn styled_cell(&self, row: usize, col: usize, cell: Cell) -> Span<'static> {
+        match self.hints.and_then(|hints| hints.cell(row, col)) {
+            Some(HintCell::Label(c)) => {
+                let style = Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD);
+                return Span::styled(c.to_string(), style);
+            }
+            Some(HintCell::Match) => {
+                let style = cell
+                    .style
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::UNDERLINED);
+                return Span::styled(cell.c.to_string(), style);
+            }
+            None => {}
+        }
+        let style = if self.selection.contains(row, col) {
+            cell.style.add_modifier(Modifier::REVERSED)
+        } else if self.search.is_current_match(row, col) {
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else if self.search.contains_any_match(row, col) {
+            cell.style.add_modifier(Modifier::REVERSED)
+        } else {
+            cell.style
+        };
+        Span::styled(cell.c.to_string(), style)
+    }
+}
+
 impl Widget for OutputPanel<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
         // Clear entire area first to prevent artifacts from underlying terminal
@@ -46,52 +88,27 @@ impl Widget for OutputPanel<'_> {
                 let inner_width = area.width as usize;
 
                 if process.wrap_enabled && inner_width > 0 {
-                    // Wrap mode: split long lines into multiple display lines
-                    let content_count = content_buffer_line_count(buffer);
-                    let mut wrapped_lines: Vec<Line> = Vec::new();
-                    // Track buffer row for each wrapped line (for selection)
-                    let mut line_map: Vec<(usize, usize)> = Vec::new(); // (buffer_row, start_col)
-                    for (row_idx, line) in buffer.iter().take(content_count).enumerate() {
-                        if line.cells.is_empty() {
-                            wrapped_lines.push(Line::from(""));
-                            line_map.push((row_idx, 0));
-                        } else {
-                            for (chunk_idx, chunk) in line.cells.chunks(inner_width).enumerate() {
-                                let start_col = chunk_idx * inner_width;
-                                let spans: Vec<Span> = chunk
-                                    .iter()
-                                    .enumerate()
-                                    .map(|(i, cell)| {
-                                        let col = start_col + i;
-                                        let style = if self.selection.contains(row_idx, col) {
-                                            cell.style.add_modifier(Modifier::REVERSED)
-                                        } else if self.search.is_current_match(row_idx, col) {
-                                            Style::default()
-                                                .fg(Color::Black)
-                                                .bg(Color::Yellow)
-                                                .add_modifier(Modifier::BOLD)
-                                        } else if self.search.contains_any_match(row_idx, col) {
-                                            cell.style.add_modifier(Modifier::REVERSED)
-                                        } else {
-                                            cell.style
-                                        };
-                                        Span::styled(cell.c.to_string(), style)
-                                    })
-                                    .collect();
-                                wrapped_lines.push(Line::from(spans));
-                                line_map.push((row_idx, start_col));
-                            }
-                        }
-                    }
-
-                    let total_lines = wrapped_lines.len().max(1);
+                    // Wrap mode: only the visible display rows are built
+                    let wrap = &process.wrap_index;
+                    let total_lines = wrap.total_rows(process.buffer.content_line_count());
                     let start = process.scroll_offset.min(total_lines.saturating_sub(1));
                     let end = (start + inner_height).min(total_lines);
 
-                    wrapped_lines
-                        .into_iter()
-                        .skip(start)
-                        .take(end - start)
+                    (start..end)
+                        .map(|display_row| {
+                            let (row_idx, start_col) = wrap.locate(display_row);
+                            let Some(line) = buffer.get(row_idx) else {
+                                return Line::from("");
+                            };
+                            let spans: Vec<Span> = line
+                                .cells(buffer.styles())
+                                .enumerate()
+                                .skip(start_col)
+                                .take(inner_width)
+                                .map(|(col, cell)| self.styled_cell(row_idx, col, cell))
+                                .collect();
+                            Line::from(spans)
+                        })
                         .collect()
                 } else {
                     // Normal mode: truncate lines at viewport width
@@ -106,25 +123,10 @@ impl Widget for OutputPanel<'_> {
                         .take(end - start)
                         .map(|(row_idx, line)| {
                             let spans: Vec<Span> = line
-                                .cells
-                                .iter()
+                                .cells(buffer.styles())
                                 .enumerate()
                                 .take(inner_width)
-                                .map(|(col, cell)| {
-                                    let style = if self.selection.contains(row_idx, col) {
-                                        cell.style.add_modifier(Modifier::REVERSED)
-                                    } else if self.search.is_current_match(row_idx, col) {
-                                        Style::default()
-                                            .fg(Color::Black)
-                                            .bg(Color::Yellow)
-                                            .add_modifier(Modifier::BOLD)
-                                    } else if self.search.contains_any_match(row_idx, col) {
-                                        cell.style.add_modifier(Modifier::REVERSED)
-                                    } else {
-                                        cell.style
-                                    };
-                                    Span::styled(cell.c.to_string(), style)
-                                })
+                                .map(|(col, cell)| self.styled_cell(row_idx, col, cell))
                                 .collect();
                             Line::from(spans)
                         })
@@ -139,38 +141,47 @@ impl Widget for OutputPanel<'_> {
     }
 }
 
-/// Compute total display lines accounting for wrap mode, excluding trailing empty lines
-fn display_line_count(process: &ManagedProcess, viewport_width: usize) -> usize {
-    if process.wrap_enabled && viewport_width > 0 {
-        let buffer = process.buffer.get_all_lines();
-        // Exclude trailing empty lines (consistent with content_line_count)
-        let content_count = content_buffer_line_count(buffer);
-        buffer
-            .iter()
-            .take(content_count)
-            .map(|line| {
-                if line.cells.is_empty() {
-                    1
-                } else {
-                    line.cells.len().div_ceil(viewport_width)
-                }
-            })
-            .sum::<usize>()
-            .max(1)
+/// Screen position of the child's cursor relative to the output panel's
+/// top-left, or None if it is hidden or scrolled out of view.
+pub fn cursor_screen_position(process: &ManagedProcess, area: Rect) -> Option<(u16, u16)> {
+    if !process.buffer.cursor_visible() || area.width == 0 || area.height == 0 {
+        return None;
+    }
+    let width = area.width as usize;
+    let (row, col) = process.buffer.cursor_position();
+    let (visual_row, visual_col) = if process.wrap_enabled {
+        let before = process.wrap_index.row_start(row);
+        // A cursor just past a full line sits at its end, not on a new row
+        let line_len = process
+            .buffer
+            .get_all_lines()
+            .get(row)
+            .map_or(0, |l| l.len());
+        let col = if col > 0 && col == line_len && col % width == 0 {
+            col - 1
+        } else {
+            col
+        };
+        (before + col / width, col % width)
     } else {
-        process.buffer.content_line_count()
+        (row, col.min(width - 1))
+    };
+    let screen_row = visual_row.checked_sub(process.scroll_offset)?;
+    if screen_row >= area.height as usize {
+        return None;
     }
+    Some((area.x + visual_col as u16, area.y + screen_row as u16))
 }
 
-/// Count buffer lines excluding trailing empty ones
-fn content_buffer_line_count(
-    buffer: &std::collections::VecDeque<crate::process::buffer::Line>,
-) -> usize {
-    let mut count = buffer.len();
-    while count > 0 && buffer[count - 1].cells.is_empty() {
-        count -= 1;
+/// Compute total display lines accounting for wrap mode, excluding trailing empty lines
+fn display_line_count(process: &mut ManagedProcess, viewport_width: usize) -> usize {
+    let content_count = process.buffer.content_line_count();
+    if process.wrap_enabled && viewport_width > 0 {
+        process.sync_wrap(viewport_width);
+        process.wrap_index.total_rows(content_count)
+    } else {
+        content_count
     }
-    count.max(1)
 }
 
 pub fn scroll_up(process: &mut ManagedProcess, amount: usize) {
@@ -209,3 +220,91 @@ pub fn scroll_to_bottom(
     process.scroll_offset = max_scroll;
     process.auto_scroll = true;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ProcessConfig;
+
+    fn process(wrap_enabled: bool) -> ManagedProcess {
+        let config = ProcessConfig {
+            name: "p".to_string(),
+            command: "true".to_string(),
+            no_shift_tab: false,
+            wrap_enabled,
+            ..ProcessConfig::default()
+        };
+        ManagedProcess::new(config, 10, 5, 100)
+    }
+
+    #[test]
+    fn cursor_position_is_offset_by_panel_and_scroll() {
+        let mut p = process(false);
+        p.buffer.write(b"a\r\nb\r\nc\r\nab");
+        let area = Rect::new(21, 0, 10, 5);
+        assert_eq!(cursor_screen_position(&p, area), Some((23, 3)));
+
+        p.scroll_offset = 2;
+        assert_eq!(cursor_screen_position(&p, area), Some((23, 1)));
+
+        p.scroll_offset = 0;
+        let short = Rect::new(21, 0, 10, 3);
+        assert_eq!(cursor_screen_position(&p, short), None);
+    }
+
+    #[test]
+    fn hidden_cursor_has_no_position() {
+        let mut p = process(false);
+        p.buffer.write(b"\x1b[?25lhello");
+        assert_eq!(cursor_screen_position(&p, Rect::new(0, 0, 10, 5)), None);
+    }
+
+    #[test]
+    fn cursor_position_follows_wrapped_lines() {
+        let mut p = process(true);
+        p.buffer.resize(20, 5);
+        // 14 chars wrap to two rows in a 10-wide viewport
+        p.buffer.write(b"0123456789abcd\r\nxy");
+        p.sync_wrap(10);
+        let area = Rect::new(0, 0, 10, 5);
+        assert_eq!(cursor_screen_position(&p, area), Some((2, 2)));
+
+        let mut p = process(true);
+        p.buffer.resize(20, 5);
+        p.buffer.write(b"01234567");
+        p.sync_wrap(4);
+        assert_eq!(
+            cursor_screen_position(&p, Rect::new(0, 0, 4, 5)),
+            Some((3, 1))
+        );
+    }
+
+    fn render_rows(p: &ManagedProcess, area: Rect) -> Vec<String> {
+        let selection = SelectionState::new();
+        let search = SearchState::default();
+        let mut buf = Buffer::empty(area);
+        OutputPanel::new(Some(p), InputMode::Browse, &selection, &search, None)
+            .render(area, &mut buf);
+        (0..area.height)
+            .map(|y| {
+                (0..area.width)
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn wrap_mode_renders_visible_rows_from_index() {
+        let mut p = process(true);
+        p.buffer.resize(20, 5);
+        p.buffer
+            .write(b"0123456789abcdefghij\r\nshort\r\nABCDEFGHIJKL");
+        p.sync_wrap(10);
+        p.scroll_offset = 1;
+        let rows = render_rows(&p, Rect::new(0, 0, 10, 3));
+        assert_eq!(rows, ["abcdefghij", "short", "ABCDEFGHIJ"]);
+    }
+}
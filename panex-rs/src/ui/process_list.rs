@@ -1,4 +1,5 @@
 use crate::config::ProcessStatus;
+use crate::process::metrics::{format_bytes, GroupMetrics};
 use crate::process::ProcessManager;
 use ratatui::{
     buffer::Buffer,
@@ -23,14 +24,48 @@ fn strip_suffixes(name: &str) -> &str {
     s
 }
 
+/// Width of the optional metrics column, e.g. " 12% 812M "
+const METRICS_WIDTH: usize = 11;
+
+/// CPU and RSS right-aligned to `METRICS_WIDTH`; blank without a live sample.
+fn metrics_text(metrics: Option<&GroupMetrics>, alive: bool) -> String {
+    match metrics {
+        Some(m) if alive => format!(
+            " {:>3.0}% {:>4} ",
+            m.cpu_percent.min(999.0),
+            format_bytes(m.rss)
+        ),
+        _ => " ".repeat(METRICS_WIDTH),
+    }
+}
+
+/// Port badge after the name: the assigned port, else the first listening
+/// one, with `+N` for further listening ports. The flag is set when the
+/// shown port is being listened on.
+fn port_badge(assigned: Option<u16>, listening: &[u16]) -> Option<(String, bool)> {
+    let mut ports: Vec<u16> = assigned.into_iter().collect();
+    ports.extend(listening.iter().filter(|p| Some(**p) != assigned));
+    let first = *ports.first()?;
+    let text = match ports.len() {
+        1 => format!(" :{first}"),
+        n => format!(" :{first}+{}", n - 1),
+    };
+    Some((text, listening.contains(&first)))
+}
+
 pub struct ProcessList<'a> {
     manager: &'a ProcessManager,
     selected: usize,
+    show_metrics: bool,
 }
 
 impl<'a> ProcessList<'a> {
-    pub fn new(manager: &'a ProcessManager, selected: usize) -> Self {
-        Self { manager, selected }
+    pub fn new(manager: &'a ProcessManager, selected: usize, show_metrics: bool) -> Self {
+        Self {
+            manager,
+            selected,
+            show_metrics,
+        }
     }
 }
 
@@ -75,20 +110,28 @@ impl Widget for ProcessList<'_> {
                 let style = Style::default().bg(bg_color);
                 let name_style = if is_selected {
                     style.add_modifier(Modifier::BOLD)
+                } else if process.status == ProcessStatus::Stopping {
+                    style.fg(Color::Yellow)
                 } else if is_stopped {
                     style.fg(Color::DarkGray)
                 } else {
                     style
                 };
 
-                // Calculate padding: icon(2) + name + spaces + wrap(1) + pin(1)
+                // Calculate padding: icon(2) + name + [port] + spaces + [metrics] + wrap(1) + pin(1)
                 let icon_width = 2; // icon + space
-                let indicators_width = 2; // wrap(1) + pin(1)
-                let name_max = width.saturating_sub(icon_width + indicators_width);
+                let port = port_badge(process.port, &process.listening);
+                let port_width = port.as_ref().map_or(0, |(text, _)| text.len());
+                let metrics = self
+                    .show_metrics
+                    .then(|| metrics_text(process.metrics.as_ref(), process.status.is_alive()));
+                let indicators_width = 2 + metrics.as_ref().map_or(0, |_| METRICS_WIDTH);
+                let name_max = width.saturating_sub(icon_width + port_width + indicators_width);
                 let stripped_name = strip_suffixes(name);
                 let display_name: String = stripped_name.chars().take(name_max).collect();
                 let name_len = display_name.chars().count();
-                let padding = width.saturating_sub(icon_width + name_len + indicators_width);
+                let padding =
+                    width.saturating_sub(icon_width + name_len + port_width + indicators_width);
 
                 let wrap_style = if process.wrap_enabled {
                     Style::default().fg(Color::Black).bg(Color::White)
@@ -104,16 +147,24 @@ impl Widget for ProcessList<'_> {
                     Style::default().fg(Color::LightBlue).bg(bg_color)
                 };
 
-                let line = Line::from(vec![
+                let mut spans = vec![
                     Span::styled(
                         format!("{} ", icon),
                         Style::default().fg(status_color).bg(bg_color),
                     ),
                     Span::styled(display_name, name_style),
-                    Span::styled(" ".repeat(padding), style),
-                    Span::styled(wrap, wrap_style),
-                    Span::styled(pin, pin_style),
-                ]);
+                ];
+                if let Some((port, listening)) = port {
+                    let color = if listening { Color::Green } else { Color::Cyan };
+                    spans.push(Span::styled(port, style.fg(color)));
+                }
+                spans.push(Span::styled(" ".repeat(padding), style));
+                if let Some(metrics) = metrics {
+                    spans.push(Span::styled(metrics, style.fg(Color::Cyan)));
+                }
+                spans.push(Span::styled(wrap, wrap_style));
+                spans.push(Span::styled(pin, pin_style));
+                let line = Line::from(spans);
 
                 ListItem::new(line)
             })
@@ -123,3 +174,22 @@ impl Widget for ProcessList<'_> {
         Widget::render(list, area, buf);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn port_badge_prefers_the_assigned_port() {
+        assert_eq!(port_badge(None, &[]), None);
+        assert_eq!(port_badge(Some(3000), &[]), Some((" :3000".into(), false)));
+        assert_eq!(
+            port_badge(Some(3000), &[3000, 9229]),
+            Some((" :3000+1".into(), true))
+        );
+        assert_eq!(
+            port_badge(None, &[5173, 24678]),
+            Some((" :5173+1".into(), true))
+        );
+    }
+}
@@ -1,6 +1,4 @@
-use std::collections::VecDeque;
-
-use crate::process::buffer::Line;
+use crate::process::buffer::LineView;
 
 /// A single search match location in the buffer
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -193,7 +191,7 @@ impl SearchState {
 }
 
 /// Find all occurrences of `query` in the buffer lines (case-insensitive).
-pub fn find_matches(query: &str, buffer: &VecDeque<Line>) -> Vec<SearchMatch> {
+pub fn find_matches(query: &str, buffer: LineView<'_>) -> Vec<SearchMatch> {
     if query.is_empty() {
         return Vec::new();
     }
@@ -203,7 +201,7 @@ pub fn find_matches(query: &str, buffer: &VecDeque<Line>) -> Vec<SearchMatch> {
 
     for (row, line) in buffer.iter().enumerate() {
         // Build the line text from cells
-        let text: String = line.cells.iter().map(|c| c.c).collect();
+        let text = line.text();
         let text_lower = text.to_lowercase();
 
         let mut start = 0;
@@ -1,3 +1,4 @@
+use crate::ui::hints::HintState;
 use crate::ui::search::SearchState;
 use crate::ui::InputMode;
 use ratatui::{
@@ -14,6 +15,8 @@ pub struct StatusBar<'a> {
     proc_no_shift_tab: bool,
     status_message: Option<&'a str>,
     search: &'a SearchState,
+    command_prompt: Option<&'a str>,
+    hints: Option<&'a HintState>,
 }
 
 impl<'a> StatusBar<'a> {
@@ -23,6 +26,8 @@ impl<'a> StatusBar<'a> {
         proc_no_shift_tab: bool,
         status_message: Option<&'a str>,
         search: &'a SearchState,
+        command_prompt: Option<&'a str>,
+        hints: Option<&'a HintState>,
     ) -> Self {
         Self {
             mode,
@@ -30,12 +35,54 @@ impl<'a> StatusBar<'a> {
             proc_no_shift_tab,
             status_message,
             search,
+            command_prompt,
+            hints,
         }
     }
 }
 
 impl Widget for StatusBar<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
+        // The command prompt takes over the status bar while open
+        if let Some(input) = self.command_prompt {
+            let line = Line::from(vec![
+                Span::styled(
+                    " COMMAND ",
+                    Style::default()
+                        .fg(Color::Black)
+                        .bg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" :"),
+                Span::styled(input, Style::default().fg(Color::White)),
+                Span::styled("▌", Style::default().fg(Color::DarkGray)),
+            ]);
+            Paragraph::new(line).render(area, buf);
+            return;
+        }
+
+        // Hint mode shows what was typed and what a label does
+        if let Some(hints) = self.hints {
+            let line = Line::from(vec![
+                Span::styled(
+                    " HINTS ",
+                    Style::default()
+                        .fg(Color::Black)
+                        .bg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" "),
+                Span::styled(hints.typed.as_str(), Style::default().fg(Color::White)),
+                Span::styled("▌", Style::default().fg(Color::DarkGray)),
+                Span::styled(
+                    "  label:copy  Shift+label:paste  Esc:cancel",
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]);
+            Paragraph::new(line).render(area, buf);
+            return;
+        }
+
         // Search mode overrides the normal status bar
         if self.search.is_typing() {
             let query = self.search.query();
@@ -87,7 +134,7 @@ impl Widget for StatusBar<'_> {
 
         let no_shift_tab = self.no_shift_tab || self.proc_no_shift_tab;
         let hints = match self.mode {
-            InputMode::Browse => "↑↓:nav  Enter/Tab:focus  r:restart  R:all  x:kill  g:pin  t/b:top/bot  /:search  v/V:select  ?:help  q:quit",
+            InputMode::Browse => "↑↓:nav  Enter/Tab:focus  r:restart  R:all  x:kill  g:pin  t/b:top/bot  /:search  v/V:select  p:paste  ?:help  q:quit",
             InputMode::Focus => {
                 if no_shift_tab {
                     "Click LPanel:exit"
@@ -97,10 +144,10 @@ impl Widget for StatusBar<'_> {
             }
         };
 
-        // Show COPIED badge in place of mode label when status message is active
-        let mode_badge = if self.status_message.is_some() {
+        // Show status badge (e.g. COPIED, PASTED) in place of mode label when active
+        let mode_badge = if let Some(msg) = self.status_message {
             Span::styled(
-                " COPIED ",
+                format!(" {} ", msg.trim_end_matches('!').to_uppercase()),
                 Style::default()
                     .fg(Color::Black)
                     .bg(Color::Yellow)
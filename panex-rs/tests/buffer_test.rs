@@ -1,4 +1,6 @@
-use panex::process::buffer::TerminalBuffer;
+use panex::process::buffer::{
+    CursorShape, KeyboardModes, MouseEncoding, MouseModes, MouseTracking, TerminalBuffer,
+};
 
 /// Helper: create a buffer, write data, return test string
 fn run(cols: usize, rows: usize, input: &[u8]) -> String {
@@ -45,6 +47,85 @@ fn alternate_screen_clears() {
     insta::assert_snapshot!(output, @"after");
 }
 
+#[test]
+fn full_screen_region_scrolls_many_lines() {
+    // Alternate screen with a 4-row region covering the whole screen
+    let mut input = b"\x1b[?1049h\x1b[1;4r\x1b[4;1H".to_vec();
+    for i in 0..1000 {
+        input.extend_from_slice(format!("\r\nline {i}").as_bytes());
+    }
+    let output = run(20, 4, &input);
+    insta::assert_snapshot!(output, @r"
+    line 996
+    line 997
+    line 998
+    line 999
+    ");
+}
+
+#[test]
+fn oversized_scroll_count_blanks_the_region() {
+    let output = run(20, 4, b"\x1b[?1049ha\r\nb\r\nc\r\nd\x1b[2;3r\x1b[1000S");
+    assert_eq!(output, "a\n\n\nd");
+    let output = run(20, 4, b"\x1b[?1049ha\r\nb\r\nc\r\nd\x1b[2;3r\x1b[1000T");
+    assert_eq!(output, "a\n\n\nd");
+}
+
+#[test]
+fn region_rows_in_scrollback_scroll_in_place() {
+    // Region rows are absolute, so with 10 lines on a 4-row screen rows 2-3
+    // lie in scrollback
+    let mut input = Vec::new();
+    for i in 0..10 {
+        input.extend_from_slice(format!("old {i}\r\n").as_bytes());
+    }
+    input.extend_from_slice(b"\x1b[2;3r\x1b[2;1H\x1b[M\x1b[5;1H\x1b[L");
+    // The IL at row 5 is outside the region and ignored
+    let mut buf = TerminalBuffer::new(20, 4);
+    buf.write(&input);
+    let lines: Vec<String> = buf.get_all_lines().iter().map(|l| l.text()).collect();
+    assert_eq!(
+        &lines[..6],
+        ["old 0", "old 2", "", "old 3", "old 4", "old 5"]
+    );
+}
+
+#[test]
+fn whole_screen_region_keeps_scrolled_lines_as_scrollback() {
+    // Normal screen with a region covering all 3 rows
+    let mut input = b"a\r\nb\r\nc\x1b[1;3r\x1b[3;1H".to_vec();
+    for i in 0..4 {
+        input.extend_from_slice(format!("\r\nline {i}").as_bytes());
+    }
+    let mut buf = TerminalBuffer::new(20, 3);
+    buf.write(&input);
+    let lines: Vec<String> = buf.get_all_lines().iter().map(|l| l.text()).collect();
+    assert_eq!(
+        lines,
+        ["a", "b", "c", "line 0", "line 1", "line 2", "line 3"]
+    );
+    assert_eq!(buf.cursor_position(), (6, 6));
+}
+
+#[test]
+fn styles_past_the_table_limit_keep_rendering() {
+    use ratatui::style::Color;
+    // A red line that stays, then 70,000 distinct truecolor styles drawn
+    // over one cell: the table fills and is compacted to the live styles
+    let mut input = b"\x1b[31mkeep\x1b[0m\r\n".to_vec();
+    for i in 0..70_000u32 {
+        let [_, r, g, b] = i.to_be_bytes();
+        input.extend_from_slice(format!("\r\x1b[38;2;{r};{g};{b}mX").as_bytes());
+    }
+    let mut buf = TerminalBuffer::new(20, 5);
+    buf.write(&input);
+    let lines = buf.get_all_lines();
+    let fg = |row: usize| lines[row].cell(0, lines.styles()).unwrap().style.fg;
+    assert_eq!(fg(0), Some(Color::Red));
+    let [_, r, g, b] = 69_999u32.to_be_bytes();
+    assert_eq!(fg(1), Some(Color::Rgb(r, g, b)));
+}
+
 // --- Mouse mode tracking tests (Step 8) ---
 
 #[test]
@@ -198,3 +279,189 @@ fn special_keys_cursor_shown_but_decckm_on() {
         "DECCKM alone is sufficient even after cursor shown"
     );
 }
+
+// --- Bracketed paste mode tracking ---
+
+#[test]
+fn bracketed_paste_default_off() {
+    let buf = TerminalBuffer::new(80, 24);
+    assert!(!buf.wants_bracketed_paste());
+}
+
+#[test]
+fn bracketed_paste_on_then_off() {
+    let mut buf = TerminalBuffer::new(80, 24);
+    buf.write(b"\x1b[?2004h");
+    assert!(buf.wants_bracketed_paste(), "DECSET 2004 should enable");
+    buf.write(b"\x1b[?2004l");
+    assert!(!buf.wants_bracketed_paste(), "DECRST 2004 should disable");
+}
+
+// --- Keyboard mode tracking ---
+
+#[test]
+fn keyboard_modes_default_legacy() {
+    let buf = TerminalBuffer::new(80, 24);
+    assert_eq!(buf.keyboard_modes(), KeyboardModes::default());
+}
+
+#[test]
+fn keypad_application_mode_tracked() {
+    let mut buf = TerminalBuffer::new(80, 24);
+    buf.write(b"\x1b=");
+    assert!(buf.keyboard_modes().application_keypad, "DECKPAM");
+    buf.write(b"\x1b>");
+    assert!(!buf.keyboard_modes().application_keypad, "DECKPNM");
+}
+
+#[test]
+fn modify_other_keys_tracked_without_touching_sgr() {
+    let mut buf = TerminalBuffer::new(80, 24);
+    buf.write(b"\x1b[>4;2mtext");
+    assert_eq!(buf.keyboard_modes().modify_other_keys, 2);
+    // CSI > 4 ; 2 m must not be parsed as SGR 4 (underline)
+    let lines = buf.get_all_lines();
+    let cell = lines[0].cell(0, lines.styles()).unwrap();
+    assert!(!cell
+        .style
+        .add_modifier
+        .contains(ratatui::style::Modifier::UNDERLINED));
+    buf.write(b"\x1b[>4n");
+    assert_eq!(buf.keyboard_modes().modify_other_keys, 0);
+}
+
+#[test]
+fn kitty_keyboard_push_pop_and_query() {
+    let mut buf = TerminalBuffer::new(80, 24);
+    buf.write(b"\x1b[>1u");
+    assert_eq!(buf.keyboard_modes().kitty_flags, 1);
+    buf.write(b"\x1b[>9u");
+    assert_eq!(buf.keyboard_modes().kitty_flags, 9);
+    buf.write(b"\x1b[?u");
+    assert_eq!(buf.take_pending_responses(), vec![b"\x1b[?9u".to_vec()]);
+    buf.write(b"\x1b[<u");
+    assert_eq!(buf.keyboard_modes().kitty_flags, 1);
+    buf.write(b"\x1b[<u");
+    assert_eq!(buf.keyboard_modes().kitty_flags, 0);
+    assert!(!buf.wants_special_keys());
+}
+
+#[test]
+fn kitty_keyboard_set_with_modes() {
+    let mut buf = TerminalBuffer::new(80, 24);
+    buf.write(b"\x1b[=1u");
+    assert_eq!(buf.keyboard_modes().kitty_flags, 1);
+    buf.write(b"\x1b[=8;2u");
+    assert_eq!(buf.keyboard_modes().kitty_flags, 9);
+    buf.write(b"\x1b[=1;3u");
+    assert_eq!(buf.keyboard_modes().kitty_flags, 8);
+}
+
+#[test]
+fn restore_cursor_still_works_without_intermediates() {
+    let output = run(80, 24, b"ab\x1b[sXYZ\x1b[u!");
+    assert_eq!(output, "ab!YZ");
+}
+
+#[test]
+fn test_mouse_modes_track_tracking_and_encoding_separately() {
+    let mut buf = TerminalBuffer::new(80, 24);
+    assert_eq!(buf.mouse_modes(), MouseModes::default());
+    assert!(!buf.wants_mouse());
+
+    // Combined DECSET: tracking + encoding in one sequence
+    buf.write(b"\x1b[?1002;1006h");
+    assert_eq!(
+        buf.mouse_modes(),
+        MouseModes {
+            tracking: MouseTracking::ButtonEvent,
+            encoding: MouseEncoding::Sgr,
+        }
+    );
+    assert!(buf.wants_mouse());
+
+    buf.write(b"\x1b[?1015h");
+    assert_eq!(buf.mouse_modes().encoding, MouseEncoding::Urxvt);
+    // Resetting an encoding that isn't active leaves the current one alone
+    buf.write(b"\x1b[?1006l");
+    assert_eq!(buf.mouse_modes().encoding, MouseEncoding::Urxvt);
+    buf.write(b"\x1b[?1015l");
+    assert_eq!(buf.mouse_modes().encoding, MouseEncoding::X10);
+
+    buf.write(b"\x1b[?1002l");
+    assert_eq!(buf.mouse_modes().tracking, MouseTracking::Off);
+}
+
+#[test]
+fn test_mouse_encoding_modes() {
+    let mut buf = TerminalBuffer::new(80, 24);
+    buf.write(b"\x1b[?9h\x1b[?1005h");
+    assert_eq!(
+        buf.mouse_modes(),
+        MouseModes {
+            tracking: MouseTracking::X10,
+            encoding: MouseEncoding::Utf8,
+        }
+    );
+    buf.write(b"\x1b[?1003h\x1b[?1016h");
+    assert_eq!(
+        buf.mouse_modes(),
+        MouseModes {
+            tracking: MouseTracking::AnyEvent,
+            encoding: MouseEncoding::SgrPixels,
+        }
+    );
+}
+
+#[test]
+fn test_cursor_position_and_visibility() {
+    let mut buf = TerminalBuffer::new(80, 24);
+    buf.write(b"hello\r\nab");
+    assert_eq!(buf.cursor_position(), (1, 2));
+    assert!(buf.cursor_visible());
+
+    buf.write(b"\x1b[?25l");
+    assert!(!buf.cursor_visible());
+    buf.write(b"\x1b[?25h");
+    assert!(buf.cursor_visible());
+}
+
+#[test]
+fn test_decscusr_sets_cursor_shape() {
+    let mut buf = TerminalBuffer::new(80, 24);
+    assert_eq!(buf.cursor_shape(), CursorShape::Default);
+
+    buf.write(b"\x1b[6 q");
+    assert_eq!(buf.cursor_shape(), CursorShape::SteadyBar);
+    buf.write(b"\x1b[3 q");
+    assert_eq!(buf.cursor_shape(), CursorShape::BlinkingUnderline);
+    buf.write(b"\x1b[ q");
+    assert_eq!(buf.cursor_shape(), CursorShape::Default);
+
+    // Not DECSCUSR (no space intermediate) — no shape change, no output
+    buf.write(b"\x1b[2q");
+    assert_eq!(buf.cursor_shape(), CursorShape::Default);
+    assert_eq!(buf.to_test_string(), "");
+}
+
+#[test]
+fn dimmed_output_keeps_its_colors_apart_from_the_process() {
+    use ratatui::style::{Color, Modifier};
+    let mut buf = TerminalBuffer::new(80, 24);
+    buf.write(b"\x1b[31ma");
+    buf.write_dimmed(b"\x1b[32mb");
+    buf.write(b"c");
+    buf.write_dimmed(b"d");
+    let lines = buf.get_all_lines();
+    let line = &lines[0];
+    let style = |col: usize| line.cell(col, lines.styles()).unwrap().style;
+    assert_eq!(line.text(), "abcd");
+    assert_eq!(style(0).fg, Some(Color::Red));
+    assert!(!style(0).add_modifier.contains(Modifier::DIM));
+    assert_eq!(style(1).fg, Some(Color::Green));
+    assert!(style(1).add_modifier.contains(Modifier::DIM));
+    assert_eq!(style(2).fg, Some(Color::Red));
+    assert!(!style(2).add_modifier.contains(Modifier::DIM));
+    assert_eq!(style(3).fg, Some(Color::Green));
+    assert!(style(3).add_modifier.contains(Modifier::DIM));
+}
@@ -0,0 +1,327 @@
+use panex::config::{
+    format_config_file, parse_config_file, Hook, PanexConfig, Port, ProcessConfig, Signal,
+};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+#[test]
+fn config_file_processes_keep_file_order() {
+    let processes = parse_config_file(
+        r#"
+        [processes.web]
+        command = "npm run dev"
+        wrap = true
+
+        [processes.api]
+        command = "cargo run"
+        autostart = false
+        stop_signal = "SIGINT"
+        stop_command = "docker compose stop api"
+        "#,
+    )
+    .unwrap()
+    .processes;
+
+    let names: Vec<&str> = processes.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["web", "api"]);
+    assert!(processes[0].wrap_enabled);
+    assert!(processes[0].autostart);
+    assert!(!processes[1].autostart);
+    assert_eq!(processes[0].stop_signal, Signal::Term);
+    assert_eq!(processes[0].stop_command, None);
+    assert_eq!(processes[1].stop_signal, Signal::Int);
+    assert_eq!(
+        processes[1].stop_command.as_deref(),
+        Some("docker compose stop api")
+    );
+}
+
+#[test]
+fn signal_names_parse_with_or_without_prefix() {
+    assert_eq!("SIGHUP".parse::<Signal>(), Ok(Signal::Hup));
+    assert_eq!("usr1".parse::<Signal>(), Ok(Signal::Usr1));
+    assert_eq!("Quit".parse::<Signal>(), Ok(Signal::Quit));
+    assert!("SIGFOO".parse::<Signal>().is_err());
+}
+
+#[test]
+fn config_file_rejects_bad_entries() {
+    let err = |text: &str| format!("{:#}", parse_config_file(text).unwrap_err());
+
+    assert!(err("[processes.web]\ncommand = \"x\"\nrestart = true").contains("restart"));
+    assert!(err("[processes.web]\nwrap = true").contains("command"));
+    assert!(err("[processes.web]\ncommand = \"x\"\nstop_signal = \"SIGBAD\"").contains("SIGBAD"));
+    // A stopped process would never exit
+    assert!(err("[processes.web]\ncommand = \"x\"\nstop_signal = \"STOP\"").contains("SIGSTOP"));
+}
+
+#[test]
+fn config_file_processes_come_before_command_line_ones() {
+    let dir = std::env::temp_dir().join(format!("panex-config-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("panex.toml");
+    std::fs::write(&path, "[processes.web]\ncommand = \"npm run dev\"\n").unwrap();
+
+    let mut config = PanexConfig::from_args(
+        vec!["cargo run".to_string()],
+        Some("api".to_string()),
+        false,
+        500,
+        10_000,
+        None,
+        false,
+    );
+    config.load_file(&path).unwrap();
+    let names: Vec<&str> = config.processes.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["web", "api"]);
+
+    // Names key the process table, so a clash is an error
+    let mut clash = PanexConfig::from_args(
+        vec!["npm start".to_string()],
+        Some("web".to_string()),
+        false,
+        500,
+        10_000,
+        None,
+        false,
+    );
+    assert!(clash.load_file(&path).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn formatted_config_file_parses_back() {
+    let text = r#"
+        editor = "code -g {file}:{line}:{col}"
+        hint_patterns = ['PROJ-\d+']
+        pipe_command = "jq ."
+
+        [processes.web]
+        command = "npm run dev"
+        wrap = true
+
+        [processes."seed db"]
+        command = "npm run seed -- --name \"demo\""
+        autostart = false
+        stop_signal = "SIGINT"
+        stop_command = "pkill -f seed"
+        "#;
+    let contents = parse_config_file(text).unwrap();
+    let processes = &contents.processes;
+    assert_eq!(
+        contents.editor.as_deref(),
+        Some("code -g {file}:{line}:{col}")
+    );
+    assert_eq!(contents.hint_patterns, [r"PROJ-\d+"]);
+    assert_eq!(contents.pipe_command.as_deref(), Some("jq ."));
+    let written = format_config_file(&contents).unwrap();
+    assert!(written.contains("[processes.web]"), "{written}");
+    assert!(!written.contains("no_shift_tab"), "{written}");
+
+    let reparsed = parse_config_file(&written).unwrap();
+    assert_eq!(reparsed.editor, contents.editor);
+    assert_eq!(reparsed.hint_patterns, contents.hint_patterns);
+    assert_eq!(reparsed.pipe_command, contents.pipe_command);
+    let reparsed = reparsed.processes;
+    let fields = |p: &ProcessConfig| {
+        (
+            p.name.clone(),
+            p.command.clone(),
+            p.wrap_enabled,
+            p.autostart,
+            p.stop_signal,
+            p.stop_command.clone(),
+        )
+    };
+    assert_eq!(
+        reparsed.iter().map(fields).collect::<Vec<_>>(),
+        processes.iter().map(fields).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn hooks_parse_and_are_written_back() {
+    let text = r#"
+        [processes.api]
+        command = "cargo run"
+        before_start = "cargo build"
+        on_ready = "curl -s localhost:3000/seed"
+        ready_pattern = "listening on"
+        on_failure = "notify-send 'api failed'"
+        env = { PORT = 3000, RUST_LOG = "debug" }
+        cwd = "services/api"
+        "#;
+    let contents = parse_config_file(text).unwrap();
+    let api = &contents.processes[0];
+    assert_eq!(api.hook(Hook::BeforeStart), Some("cargo build"));
+    assert_eq!(api.hook(Hook::AfterExit), None);
+    assert_eq!(api.ready_pattern.as_deref(), Some("listening on"));
+
+    let written = format_config_file(&contents).unwrap();
+    assert!(!written.contains("after_exit"), "{written}");
+    let reparsed = parse_config_file(&written).unwrap().processes;
+    for hook in Hook::ALL {
+        assert_eq!(reparsed[0].hook(hook), api.hook(hook), "{}", hook.name());
+    }
+    assert_eq!(reparsed[0].ready_pattern, api.ready_pattern);
+    assert_eq!(
+        reparsed[0].cwd.as_deref(),
+        Some(std::path::Path::new("services/api"))
+    );
+    assert_eq!(
+        reparsed[0].env,
+        [
+            ("PORT".to_string(), "3000".to_string()),
+            ("RUST_LOG".to_string(), "debug".to_string())
+        ]
+    );
+}
+
+#[test]
+fn env_files_resolve_in_order_with_interpolation() {
+    let dir = std::env::temp_dir().join(format!("panex-env-file-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("api")).unwrap();
+    std::fs::write(dir.join("shared.env"), "HOST=localhost\nPORT=3000\n").unwrap();
+    std::fs::write(
+        dir.join("api/.env"),
+        "PORT=4000\nURL=\"http://${HOST}:${PORT}\"\nAPI_TOKEN=t0ps3cret\n",
+    )
+    .unwrap();
+    let text = format!(
+        r#"
+        env_file = "{}"
+
+        [processes.api]
+        command = "serve --url ${{URL}} --token ${{API_TOKEN}} --level ${{LEVEL:-info}} ${{LATER}}"
+        cwd = "{}"
+        env_file = [".env"]
+        env = {{ DATABASE_URL = "postgres://app:pw@${{HOST}}/dev" }}
+        "#,
+        dir.join("shared.env").display(),
+        dir.join("api").display(),
+    );
+    let contents = parse_config_file(&text).unwrap();
+    assert_eq!(contents.env_files, [dir.join("shared.env")]);
+    assert_eq!(contents.processes[0].env_file, [PathBuf::from(".env")]);
+    let written = format_config_file(&contents).unwrap();
+    let reparsed = parse_config_file(&written).unwrap();
+    assert_eq!(reparsed.env_files, contents.env_files);
+    assert_eq!(
+        reparsed.processes[0].env_file,
+        contents.processes[0].env_file
+    );
+
+    let api = contents.processes[0]
+        .resolve(&contents.env_files, &HashMap::new())
+        .unwrap();
+    let env_of = |key: &str| {
+        api.env
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    };
+    // The process's own env file overrides the global one
+    assert_eq!(env_of("PORT"), Some("4000"));
+    assert_eq!(env_of("URL"), Some("http://localhost:4000"));
+    assert_eq!(
+        env_of("DATABASE_URL"),
+        Some("postgres://app:pw@localhost/dev")
+    );
+    // Commands are left to the shell, which reads the values from `env`
+    assert_eq!(
+        api.command,
+        "serve --url ${URL} --token ${API_TOKEN} --level ${LEVEL:-info} ${LATER}"
+    );
+
+    let mut config = PanexConfig::from_args(Vec::new(), None, false, 500, 10_000, None, false);
+    config.processes = contents.processes;
+    config.env_files = contents.env_files;
+    let printed = config.format_resolved().unwrap();
+    assert!(!printed.contains("t0ps3cret"), "{printed}");
+    assert!(!printed.contains("app:pw@"), "{printed}");
+    assert!(printed.contains("http://localhost:4000"), "{printed}");
+
+    // A missing env file fails the start
+    config.processes[0]
+        .env_file
+        .push(PathBuf::from("missing.env"));
+    assert!(config.format_resolved().is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn env_values_in_commands_are_not_shell_syntax() {
+    let value = "postgres://db/app?sslmode=require&x=1; echo pwned $(echo subshell)";
+    let text = format!(
+        r#"
+        [processes.db]
+        command = "printf '%s' \"${{DATABASE_URL}}\""
+        env = {{ DATABASE_URL = "{value}" }}
+        "#
+    );
+    let contents = parse_config_file(&text).unwrap();
+    let db = contents.processes[0].resolve(&[], &HashMap::new()).unwrap();
+    assert_eq!(db.command, "printf '%s' \"${DATABASE_URL}\"");
+
+    let output = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(&db.command)
+        .envs(db.env.iter().map(|(k, v)| (k, v)))
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), value);
+}
+
+#[test]
+fn ports_parse_resolve_and_are_written_back() {
+    let text = r#"
+        [processes.api]
+        command = "cargo run"
+        port = "auto"
+
+        [processes.web-app]
+        command = "vite --port $VITE_PORT"
+        port = 5173
+        port_env = "VITE_PORT"
+        env = { API_URL = "http://localhost:${api.port}", PORT = "1" }
+        "#;
+    let contents = parse_config_file(text).unwrap();
+    assert_eq!(contents.processes[0].port, Some(Port::Auto));
+    assert_eq!(contents.processes[1].port, Some(Port::Fixed(5173)));
+    let written = format_config_file(&contents).unwrap();
+    let reparsed = parse_config_file(&written).unwrap();
+    assert_eq!(reparsed.processes[0].port, Some(Port::Auto));
+    assert_eq!(reparsed.processes[1].port_env.as_deref(), Some("VITE_PORT"));
+
+    let ports = HashMap::from([("api".to_string(), 41000), ("web-app".to_string(), 5173)]);
+    let api = contents.processes[0].resolve(&[], &ports).unwrap();
+    assert_eq!(api.env, [("PORT".to_string(), "41000".to_string())]);
+    let web = contents.processes[1].resolve(&[], &ports).unwrap();
+    assert_eq!(
+        web.env,
+        [
+            ("API_URL".to_string(), "http://localhost:41000".to_string()),
+            ("PORT".to_string(), "1".to_string()),
+            ("VITE_PORT".to_string(), "5173".to_string()),
+        ]
+    );
+    // Names with `-` work in references too
+    let mut worker = contents.processes[0].clone();
+    worker.port = None;
+    worker.command = "curl localhost:${web-app.port}".to_string();
+    assert_eq!(
+        worker.resolve(&[], &ports).unwrap().command,
+        "curl localhost:5173"
+    );
+
+    let err = |text: &str| format!("{:#}", parse_config_file(text).unwrap_err());
+    assert!(err("[processes.web]\ncommand = \"x\"\nport = \"any\"").contains("port"));
+    assert!(err("[processes.web]\ncommand = \"x\"\nport = 70000").contains("port"));
+}
+
+#[test]
+fn hint_patterns_must_be_valid_regexes() {
+    let err = parse_config_file("hint_patterns = ['(']").unwrap_err();
+    assert!(format!("{err:#}").contains("hint_patterns"));
+}
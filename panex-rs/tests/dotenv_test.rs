@@ -0,0 +1,79 @@
+use panex::dotenv::{
+    interpolate, interpolate_command, mask_secrets, mask_value, parse_dotenv, MASK,
+};
+
+fn vars(name: &str) -> Option<String> {
+    match name {
+        "HOST" => Some("example.com".to_string()),
+        "EMPTY" => Some(String::new()),
+        _ => None,
+    }
+}
+
+#[test]
+fn quoted_values_span_lines() {
+    let env = parse_dotenv(
+        "export KEY=\"-----BEGIN KEY-----\n\
+         abc\\\"def\n\
+         -----END KEY-----\"\n\
+         SQL='select *\n\
+         from t'\n\
+         UNTERMINATED=\"oops\n\
+         AFTER=1\n",
+    );
+    assert_eq!(
+        env,
+        [
+            (
+                "KEY".into(),
+                "-----BEGIN KEY-----\nabc\"def\n-----END KEY-----".into()
+            ),
+            ("SQL".into(), "select *\nfrom t".into()),
+            ("UNTERMINATED".into(), "\"oops".into()),
+            ("AFTER".into(), "1".into()),
+        ]
+    );
+}
+
+#[test]
+fn references_expand_with_defaults() {
+    assert_eq!(
+        interpolate("https://${HOST}/api", vars),
+        "https://example.com/api"
+    );
+    assert_eq!(interpolate("${MISSING:-local}host", vars), "localhost");
+    assert_eq!(interpolate("${EMPTY:-fallback}", vars), "fallback");
+    assert_eq!(interpolate("${EMPTY-fallback}", vars), "");
+    assert_eq!(interpolate("${MISSING:-${HOST}}", vars), "example.com");
+    assert_eq!(interpolate("[${MISSING}]", vars), "[]");
+    assert_eq!(interpolate("$${HOST} $HOST $", vars), "${HOST} $HOST $");
+    // Commands only get ports; the shell expands the rest itself
+    let port = |process: &str| (process == "web").then_some(5000);
+    assert_eq!(
+        interpolate_command(
+            "echo ${HOST} ${web.port} ${api.port} ${X:-${web.port}} $${web.port} $$",
+            port
+        ),
+        "echo ${HOST} 5000 ${api.port} ${X:-5000} ${web.port} $$"
+    );
+}
+
+#[test]
+fn secret_values_are_masked() {
+    assert_eq!(mask_value("GITHUB_TOKEN", "ghp_123"), MASK);
+    assert_eq!(mask_value("api_key", "abc"), MASK);
+    assert_eq!(mask_value("PORT", "3000"), "3000");
+    assert_eq!(
+        mask_value("DATABASE_URL", "postgres://app:hunter2@db:5432/dev"),
+        format!("postgres://app:{MASK}@db:5432/dev")
+    );
+    assert_eq!(
+        mask_value("HOMEPAGE", "https://user@example.com/"),
+        "https://user@example.com/"
+    );
+    let env = vec![("SECRET".to_string(), "s3cret".to_string())];
+    assert_eq!(
+        mask_secrets("login --password s3cret", &env),
+        format!("login --password {MASK}")
+    );
+}
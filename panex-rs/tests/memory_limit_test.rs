@@ -0,0 +1,104 @@
+use panex::config::{parse_memory_limit, PanexConfig};
+use panex::process::buffer::TerminalBuffer;
+use ratatui::style::Style;
+
+fn numbered_lines(n: usize) -> Vec<u8> {
+    (1..=n)
+        .map(|i| format!("line {i}\r\n"))
+        .collect::<String>()
+        .into_bytes()
+}
+
+#[test]
+fn parse_memory_limit_accepts_units() {
+    assert_eq!(parse_memory_limit("1048576"), Ok(1 << 20));
+    assert_eq!(parse_memory_limit("512K"), Ok(512 << 10));
+    assert_eq!(parse_memory_limit("256M"), Ok(256 << 20));
+    assert_eq!(parse_memory_limit("256mb"), Ok(256 << 20));
+    assert_eq!(parse_memory_limit("1GiB"), Ok(1 << 30));
+    assert_eq!(parse_memory_limit("1.5G"), Ok(3 << 29));
+}
+
+#[test]
+fn parse_memory_limit_rejects_garbage() {
+    assert!(parse_memory_limit("").is_err());
+    assert!(parse_memory_limit("lots").is_err());
+    assert!(parse_memory_limit("10T").is_err());
+    assert!(parse_memory_limit("0").is_err());
+}
+
+#[test]
+fn memory_limit_defaults_to_unlimited() {
+    let config = PanexConfig::from_args(
+        vec!["echo hi".to_string()],
+        None,
+        false,
+        500,
+        10_000,
+        None,
+        false,
+    );
+    assert_eq!(config.memory_limit, None);
+}
+
+#[test]
+fn memory_usage_tracks_written_and_cleared_lines() {
+    let mut buf = TerminalBuffer::new(80, 24);
+    let empty = buf.memory_usage();
+    buf.write(&numbered_lines(100));
+    let full = buf.memory_usage();
+    assert!(full > empty);
+
+    buf.write(b"\x1b[2J");
+    assert!(buf.memory_usage() < full);
+}
+
+#[test]
+fn memory_usage_counts_interned_styles() {
+    let mut plain = TerminalBuffer::new(80, 24);
+    let mut colored = TerminalBuffer::new(80, 24);
+    for i in 0..1000 {
+        plain.write(b"x\r\n");
+        colored.write(format!("\x1b[38;2;{};{};0mx\x1b[m\r\n", i % 256, i / 256).as_bytes());
+    }
+    assert!(colored.memory_usage() >= plain.memory_usage() + 1000 * 2 * size_of::<Style>());
+}
+
+#[test]
+fn memory_usage_shrinks_with_scrollback_trim() {
+    let mut buf = TerminalBuffer::with_max_scrollback(80, 24, 50);
+    buf.write(&numbered_lines(50));
+    let at_cap = buf.memory_usage();
+    buf.write(&numbered_lines(500));
+    // Stays bounded: trimmed lines are subtracted from the running total
+    assert!(buf.memory_usage() < at_cap * 2);
+}
+
+#[test]
+fn evict_oldest_drops_scrollback_but_keeps_screen() {
+    let mut buf = TerminalBuffer::new(80, 10);
+    buf.write(&numbered_lines(30));
+    let before = buf.memory_usage();
+
+    assert_eq!(buf.evict_oldest(5), 5);
+    assert!(buf.memory_usage() < before);
+    assert!(buf.to_test_string().starts_with("line 6\n"));
+
+    // Only lines above the visible screen can go
+    let evicted = buf.evict_oldest(usize::MAX);
+    assert_eq!(buf.get_all_lines().len(), 10);
+    assert_eq!(evicted, 16); // 31 lines (cursor on an empty last line) - 5 - 10
+    assert_eq!(buf.evict_oldest(5), 0);
+
+    // Output continues at the cursor after eviction
+    buf.write(b"next");
+    assert!(buf.to_test_string().ends_with("line 30\nnext"));
+}
+
+#[test]
+fn evict_oldest_leaves_alternate_screen_alone() {
+    let mut buf = TerminalBuffer::new(80, 5);
+    buf.write(b"\x1b[?1049h");
+    buf.write(&numbered_lines(20));
+    assert_eq!(buf.evict_oldest(10), 0);
+}
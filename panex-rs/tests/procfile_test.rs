@@ -0,0 +1,128 @@
+use panex::config::PanexConfig;
+use panex::dotenv::parse_dotenv;
+use panex::procfile::{parse_formation, parse_procfile, procfile_processes};
+
+fn env_of<'a>(env: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    env.iter()
+        .rev()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+}
+
+#[test]
+fn procfile_entries_skip_comments_and_blank_lines() {
+    let entries = parse_procfile(
+        "# development processes\n\
+         web: bundle exec rails server -p $PORT\n\
+         \n\
+         worker:bundle exec sidekiq -c 5   \n",
+    )
+    .unwrap();
+    assert_eq!(
+        entries,
+        [
+            ("web".into(), "bundle exec rails server -p $PORT".into()),
+            ("worker".into(), "bundle exec sidekiq -c 5".into()),
+        ]
+    );
+    // Only the first colon separates the name
+    let entries = parse_procfile("assets: npx vite --host 0.0.0.0:3000").unwrap();
+    assert_eq!(entries[0].1, "npx vite --host 0.0.0.0:3000");
+
+    assert!(parse_procfile("just a command").is_err());
+    assert!(parse_procfile("web server: rails s").is_err());
+    assert!(parse_procfile("web:").is_err());
+    assert!(parse_procfile("web: a\nweb: b").is_err());
+}
+
+#[test]
+fn formation_sets_instance_names_and_ports() {
+    let entries = parse_procfile("web: serve\nworker: work\nclock: tick").unwrap();
+    let formation = parse_formation("web=2, clock=0").unwrap();
+    let env = vec![
+        ("PORT".to_string(), "9".to_string()),
+        ("A".into(), "1".into()),
+    ];
+    let processes = procfile_processes(&entries, &formation, 5000, &env).unwrap();
+
+    let summary: Vec<(&str, Option<&str>)> = processes
+        .iter()
+        .map(|p| (p.name.as_str(), env_of(&p.env, "PORT")))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("web.1", Some("5000")),
+            ("web.2", Some("5001")),
+            ("worker", Some("5100")),
+        ]
+    );
+    assert_eq!(env_of(&processes[0].env, "A"), Some("1"));
+
+    let all = parse_formation("all=2,worker=1").unwrap();
+    let names: Vec<String> = procfile_processes(&entries, &all, 5000, &[])
+        .unwrap()
+        .into_iter()
+        .map(|p| p.name)
+        .collect();
+    assert_eq!(names, ["web.1", "web.2", "worker", "clock.1", "clock.2"]);
+
+    assert!(parse_formation("web").is_err());
+    assert!(parse_formation("web=two").is_err());
+    assert!(parse_formation("web=100").is_ok());
+    assert!(parse_formation("web=101").is_err());
+    assert!(parse_formation("all=18446744073709551615").is_err());
+    let unknown = parse_formation("api=1").unwrap();
+    assert!(procfile_processes(&entries, &unknown, 5000, &[]).is_err());
+}
+
+#[test]
+fn dotenv_values_are_unquoted() {
+    let env = parse_dotenv(
+        "# settings\n\
+         export DATABASE_URL=postgres://localhost/dev\n\
+         GREETING=\"hello\\nworld\"\n\
+         LITERAL='$HOME \\n'\n\
+         PLAIN=value # trailing comment\n\
+         not a variable\n",
+    );
+    assert_eq!(
+        env,
+        [
+            ("DATABASE_URL".into(), "postgres://localhost/dev".into()),
+            ("GREETING".into(), "hello\nworld".into()),
+            ("LITERAL".into(), "$HOME \\n".into()),
+            ("PLAIN".into(), "value".into()),
+        ]
+    );
+}
+
+#[test]
+fn procfile_loads_dotenv_from_its_directory() {
+    let dir = std::env::temp_dir().join(format!("panex-procfile-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("Procfile.dev");
+    std::fs::write(&path, "web: serve\nworker: work\n").unwrap();
+    std::fs::write(dir.join(".env"), "PORT=3000\nSECRET=s3cret\n").unwrap();
+
+    let mut config = PanexConfig::from_args(
+        vec!["cargo run".to_string()],
+        Some("api".to_string()),
+        false,
+        500,
+        10_000,
+        None,
+        false,
+    );
+    config
+        .load_procfile(&path, &parse_formation("worker=2").unwrap())
+        .unwrap();
+    let names: Vec<&str> = config.processes.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["web", "worker.1", "worker.2", "api"]);
+    let worker = &config.processes[2];
+    assert_eq!(env_of(&worker.env, "PORT"), Some("3101"));
+    assert_eq!(env_of(&worker.env, "SECRET"), Some("s3cret"));
+    assert!(config.processes[3].env.is_empty());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,130 @@
+use panex::scripts::{
+    glob_match, parse_pnpm_workspace, script_processes, workspace_script_processes, PackageManager,
+};
+use std::path::{Path, PathBuf};
+
+/// Fresh directory for one test, removed first if left over.
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("panex-scripts-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write(path: &Path, text: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+#[test]
+fn glob_matches_script_names() {
+    assert!(glob_match("dev:*", "dev:api"));
+    assert!(glob_match("dev:*", "dev:"));
+    assert!(!glob_match("dev:*", "build:dev"));
+    assert!(glob_match("*:web", "dev:web"));
+    assert!(glob_match("te?t", "test"));
+    assert!(glob_match("a*b*c", "axxbyybc"));
+    assert!(!glob_match("a*b*c", "axxbyyb"));
+    assert!(glob_match("api", "api"));
+    assert!(!glob_match("api", "api2"));
+}
+
+#[test]
+fn scripts_match_in_file_order_with_detected_package_manager() {
+    let dir = test_dir("match");
+    write(
+        &dir.join("package.json"),
+        r#"{ "scripts": { "dev:web": "vite", "build": "tsc", "dev:api": "node api.js" } }"#,
+    );
+    write(&dir.join("pnpm-lock.yaml"), "");
+    let sub = dir.join("src");
+    std::fs::create_dir_all(&sub).unwrap();
+
+    let processes = script_processes(&dir, &["dev:*".to_string()]).unwrap();
+    let summary: Vec<(&str, &str)> = processes
+        .iter()
+        .map(|p| (p.name.as_str(), p.command.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("dev:web", "pnpm run dev:web"),
+            ("dev:api", "pnpm run dev:api")
+        ]
+    );
+    assert_eq!(processes[0].cwd, None);
+
+    // From a subdirectory, the nearest package.json is used and runs in its directory
+    let processes = script_processes(&sub, &["build".to_string()]).unwrap();
+    assert_eq!(processes[0].cwd.as_deref(), Some(dir.as_path()));
+
+    assert!(script_processes(&dir, &["missing".to_string()]).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn package_manager_field_wins_over_lockfiles() {
+    let dir = test_dir("detect");
+    assert_eq!(PackageManager::detect(&dir, None), PackageManager::Npm);
+    write(&dir.join("yarn.lock"), "");
+    assert_eq!(PackageManager::detect(&dir, None), PackageManager::Yarn);
+    assert_eq!(
+        PackageManager::detect(&dir.join("nested"), None),
+        PackageManager::Yarn
+    );
+    assert_eq!(
+        PackageManager::detect(&dir, Some("bun@1.1.0")),
+        PackageManager::Bun
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn workspace_scripts_run_in_each_package_that_has_them() {
+    let dir = test_dir("workspaces");
+    write(
+        &dir.join("package.json"),
+        r#"{ "name": "root", "workspaces": ["packages/*", "apps/web", "!packages/legacy"] }"#,
+    );
+    write(&dir.join("package-lock.json"), "{}");
+    write(
+        &dir.join("packages/ui/package.json"),
+        r#"{ "name": "@acme/ui", "scripts": { "dev": "tsup --watch" } }"#,
+    );
+    write(
+        &dir.join("packages/legacy/package.json"),
+        r#"{ "name": "legacy", "scripts": { "dev": "gulp" } }"#,
+    );
+    write(
+        &dir.join("packages/types/package.json"),
+        r#"{ "name": "@acme/types", "scripts": { "build": "tsc" } }"#,
+    );
+    write(
+        &dir.join("apps/web/package.json"),
+        r#"{ "scripts": { "dev": "next dev" } }"#,
+    );
+
+    let processes = workspace_script_processes(&dir, "dev").unwrap();
+    let summary: Vec<(&str, &str, Option<&Path>)> = processes
+        .iter()
+        .map(|p| (p.name.as_str(), p.command.as_str(), p.cwd.as_deref()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("web", "npm run dev", Some(Path::new("apps/web"))),
+            ("@acme/ui", "npm run dev", Some(Path::new("packages/ui"))),
+        ]
+    );
+    assert!(workspace_script_processes(&dir, "lint").is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn pnpm_workspace_lists_package_globs() {
+    let yaml = "packages:\n  - 'apps/*'\n  - \"packages/**\"\n  # comment\n  - '!**/test/**'\ncatalog:\n  react: ^18\n";
+    assert_eq!(
+        parse_pnpm_workspace(yaml),
+        ["apps/*", "packages/**", "!**/test/**"]
+    );
+}